@@ -0,0 +1,213 @@
+# Deutsche Meldungen von playsync
+
+## Titles of the commands
+
+setup-intro = 🚀 PlaySync einrichten
+config-intro = 📝 Playlist-Konfiguration
+list-intro = 📜 Alle Playlists:
+sync-intro = 🔄 Playlist-Synchronisierung
+sync-intro-dry-run = 🔍 Playlist-Synchronisierung (Probelauf)
+apply-intro = 📋 Synchronisierungsplan anwenden
+daemon-intro = ⏰ PlaySync-Daemon
+health-intro = 🩺 Zustandsprüfung
+serve-intro = 🌐 PlaySync-API
+self-update-intro = ⬆️ playsync aktualisieren
+plugins-intro = 🧩 Plugins
+service-intro = ⚙️ Hintergrunddienst
+history-intro = 📜 Verlauf der Läufe
+status-intro = 🔎 Playlist-Status
+explain-intro = 🔎 Erklärung
+search-intro = 🔍 Playlists durchsuchen
+merge-intro = 🧩 Playlists zusammenführen
+merge-intro-dry-run = 🔍 Playlists zusammenführen (Probelauf)
+split-intro = ✂️ Playlist aufteilen
+split-intro-dry-run = 🔍 Playlist aufteilen (Probelauf)
+copy-intro = 📋 Playlist kopieren
+copy-intro-dry-run = 🔍 Playlist kopieren (Probelauf)
+move-intro = 🚚 Videos verschieben
+move-intro-dry-run = 🔍 Videos verschieben (Probelauf)
+sort-intro = 🔀 Playlist sortieren
+sort-intro-dry-run = 🔍 Playlist sortieren (Probelauf)
+shuffle-intro = 🎲 Playlist mischen
+shuffle-intro-dry-run = 🔍 Playlist mischen (Probelauf)
+tidy-intro = 🧹 Playlists aufräumen
+tidy-intro-dry-run = 🔍 Playlists aufräumen (Probelauf)
+add-intro = ➕ Videos hinzufügen
+add-intro-dry-run = 🔍 Videos hinzufügen (Probelauf)
+remove-intro = ➖ Videos entfernen
+remove-intro-dry-run = 🔍 Videos entfernen (Probelauf)
+convert-intro = 🔁 Playlist umwandeln
+convert-intro-dry-run = 🔍 Playlist umwandeln (Probelauf)
+download-intro = 💾 Playlists herunterladen
+download-intro-dry-run = 🔍 Playlists herunterladen (Probelauf)
+import-intro = 📥 Playlists importieren
+import-intro-dry-run = 🔍 Playlists importieren (Probelauf)
+stats-intro = 📊 Playlist-Statistiken
+cache-intro = 🗄️ Playlist-Cache
+
+## Questions
+
+setup-add-more =
+    Es gibt bereits eine Konfiguration mit { $count ->
+        [one] einer Playlist
+       *[other] { $count } Playlists
+    }. Weitere Playlists hinzufügen?
+oauth2-json-prompt = Pfad zur OAuth2-JSON-Datei
+no-file-at-path = Unter diesem Pfad gibt es keine Datei
+setup-pick-playlists = Wähle die Playlists, die playsync verwalten soll:
+setup-sync-from = '{ $title }' synchronisieren aus (leer lassen, um sie nur zu verwalten):
+pick-sources = Wähle die Playlists, aus denen synchronisiert wird:
+pick-playlists-to-remove = Wähle die zu entfernenden Playlists:
+pick-playlists-to-sync = Wähle die zu synchronisierenden Playlists:
+pick-imports = Wähle, was importiert werden soll:
+reset-confirm = Soll die Konfiguration wirklich zurückgesetzt werden?
+remove-from-sources =
+    { $count ->
+        [one] Eine Playlist synchronisiert
+       *[other] { $count } Playlists synchronisieren
+    } aus den entfernten Playlists: { $playlists }. Auch aus ihren Quellen entfernen?
+edit-again = Die Datei erneut bearbeiten?
+save-with-warnings = Die Konfiguration trotz dieser Warnungen speichern?
+self-update-confirm = playsync von { $current } auf { $latest } aktualisieren?
+passphrase-prompt = Passphrase der Geheimnisse in der Konfiguration
+passphrase-confirm = Passphrase bestätigen
+
+## Results
+
+missing-oauth2-json = ❌ Der Pfad zur OAuth2-JSON-Datei ist nicht gesetzt. Bitte setze ihn vor dem Synchronisieren.
+client-not-initialized = ❌ Der YouTube-Client ist nicht initialisiert.
+outside-sync-windows = Außerhalb der Synchronisierungsfenster. Nichts synchronisiert.
+outside-sync-windows-until = Außerhalb der Synchronisierungsfenster, das nächste beginnt um { $time }. Nichts synchronisiert.
+setup-cancelled = Einrichtung abgebrochen, die Konfiguration ist unverändert
+sign-in-failed = ❌ Anmeldung fehlgeschlagen: { $error }. Der Pfad zu den Zugangsdaten ist gespeichert, führe `playsync init` erneut aus.
+setup-completed-empty = ✅ Einrichtung abgeschlossen. Füge später Playlists mit `playsync config --add-mine` hinzu
+setup-completed =
+    ✅ Einrichtung mit { $count ->
+        [one] einer Playlist
+       *[other] { $count } Playlists
+    } abgeschlossen. Führe `playsync sync --dry-run` aus, um die erste Synchronisierung vorab zu sehen
+graph-saved = ✅ Synchronisierungsgraph gespeichert
+config-unchanged = Die Konfiguration ist unverändert
+setting-set = ✅ { $key } gesetzt
+config-reset = ✅ Konfiguration zurückgesetzt
+oauth2-json-set = ✅ Pfad zur OAuth2-JSON-Datei gesetzt
+playlist-added = ✅ Playlist hinzugefügt
+playlist-fetch-failed = ❌ Playlist-Informationen konnten nicht abgerufen werden: { $error }
+no-playlists-added = Keine Playlists hinzugefügt
+playlists-added =
+    ✅ { $count ->
+        [one] Eine Playlist
+       *[other] { $count } Playlists
+    } hinzugefügt. Lege ihre Quellen mit `playsync config edit` fest
+playlist-removed = ✅ Playlist entfernt
+no-playlists-configured-yet = Keine Playlists konfiguriert
+no-playlists-removed = Keine Playlists entfernt
+playlists-removed =
+    ✅ { $count ->
+        [one] Eine Playlist
+       *[other] { $count } Playlists
+    } entfernt
+no-playlists-to-list = Keine Playlists zum Anzeigen
+list-completed = ✅ Konfiguration angezeigt
+config-invalid =
+    ❌ { $path } hat { $count ->
+        [one] ein Problem
+       *[other] { $count } Probleme
+    }
+config-valid = ✅ { $path } ist gültig
+config-valid-with-warnings =
+    ⚠️ { $path } ist gültig, mit { $count ->
+        [one] einer Warnung
+       *[other] { $count } Warnungen
+    }
+no-secrets-stored = ✅ In der Konfiguration sind keine API-Schlüssel oder Tokens gespeichert
+secrets-already-encrypted = ✅ Alle Geheimnisse sind bereits verschlüsselt
+no-secrets-encrypted = ✅ Keine Geheimnisse sind verschlüsselt
+secrets-encrypted = ✅ Verschlüsselt: { $names }
+secrets-decrypted = ✅ Entschlüsselt: { $names }
+editor-failed = ❌ Der Editor ({ $editor }) ist fehlgeschlagen, die Konfiguration ist unverändert
+config-saved = ✅ Konfiguration in { $path } gespeichert
+changes-discarded = ❌ Änderungen verworfen, die Konfiguration ist unverändert
+no-playlists-to-sync = ❌ Keine Playlists zum Synchronisieren gefunden
+no-playlists-selected = Keine Playlists ausgewählt
+dry-run-completed = ✅ Probelauf abgeschlossen
+sync-completed = ✅ Synchronisierung abgeschlossen
+plan-empty = Nichts anzuwenden, der Plan enthält keine Änderungen
+plan-refused = ❌ Plan abgelehnt, nichts wurde geändert
+plan-applied = ✅ Plan angewendet
+daemon-stopped = Daemon gestoppt
+unhealthy = ❌ Fehlerhaft
+healthy = ✅ Alles in Ordnung
+server-stopped = Server gestoppt
+up-to-date = ✅ playsync { $version } ist die neueste Version
+update-available = playsync { $latest } ist verfügbar (installiert: { $current }): { $url }
+update-with-package-manager = playsync { $latest } ist verfügbar, aktualisiere es mit { $manager }
+self-update-unchanged = playsync ist unverändert
+updated = ✅ playsync auf { $version } aktualisiert
+no-plugins = Keine Plugins in { $dir }
+plugins-found =
+    { $count ->
+        [one] Ein Plugin
+       *[other] { $count } Plugins
+    } in { $dir }
+service-not-installed = Führe den Befehl mit --install erneut aus, um diese Dateien zu schreiben und zu aktivieren
+service-installed = ✅ Dienst installiert
+no-runs = Keine Läufe gefunden
+runs-shown = ✅ { $shown } von { $total } Läufen angezeigt
+no-sync-relationships = ❌ Keine Synchronisierungsbeziehungen gefunden
+all-up-to-date = ✅ Alle Playlists sind aktuell
+playlists-behind = ⚠️ { $behind } von { $total } Playlists sind nicht synchron
+playlist-not-configured = ❌ Die Playlist { $playlist } ist nicht konfiguriert
+no-sources = '{ $title }' hat keine sync_from-Quellen, Videos werden nur von Hand hinzugefügt
+not-a-video = ❌ '{ $video }' ist keine Video-ID oder -URL
+no-playlists-configured = ❌ Keine Playlists konfiguriert
+no-match = ❌ Kein Video passend zu '{ $query }' gefunden
+matches-found =
+    ✅ { $count ->
+        [one] Ein passender Eintrag
+       *[other] { $count } passende Einträge
+    } gefunden
+merged = ✅ In '{ $title }' zusammengeführt (ID: { $id })
+split-nothing = ❌ Die Playlist würde nicht in mehrere Teile aufgeteilt
+split-completed = ✅ Aufteilung abgeschlossen
+nothing-to-copy = ✅ Nichts zu kopieren
+copy-completed = ✅ Kopieren abgeschlossen
+move-no-filter = ❌ Kein Filter angegeben. Verwende --all, um alle Videos zu verschieben.
+nothing-to-move = ✅ Nichts zu verschieben
+move-completed = ✅ Verschieben abgeschlossen
+already-sorted = ✅ Die Playlist ist bereits in der gewünschten Reihenfolge
+playlist-reordered = ✅ Playlist neu geordnet
+no-playlists-to-tidy = ❌ Keine Playlists zum Aufräumen gefunden
+tidy-completed = ✅ Aufräumen abgeschlossen
+nothing-to-add = ✅ Nichts hinzuzufügen
+videos-added = ✅ Videos hinzugefügt
+remove-nothing-selected = ❌ Nichts ausgewählt. Gib Video-URLs/-IDs, einen Filter oder --placeholders an.
+nothing-to-remove = ✅ Nichts zu entfernen
+videos-removed = ✅ Videos entfernt
+not-a-configured-playlist = ❌ '{ $playlist }' ist keine konfigurierte Playlist
+opened = ✅ '{ $title }' im Browser geöffnet
+nothing-to-convert = ✅ Nichts umzuwandeln
+converted = ✅ In '{ $title }' umgewandelt: { $url }
+no-download-dir = ❌ Keine Playlists mit download_dir gefunden
+download-completed = ✅ Download abgeschlossen
+import-nothing = ❌ Keine YouTube-Playlists oder -Abos in der Datei gefunden
+imported =
+    ✅ { $count ->
+        [one] Eine Playlist
+       *[other] { $count } Playlists
+    } importiert. Führe `playsync sync` aus, um sie auf YouTube anzulegen.
+no-playlists-found = ❌ Keine Playlists gefunden
+stats-completed = ✅ Statistiken abgeschlossen
+cache-empty = Der Cache ist leer
+cache-listed =
+    { $count ->
+        [one] Eine Playlist im Cache
+       *[other] { $count } Playlists im Cache
+    }
+cache-refreshed = ✅ { $count } von { $total } Playlists aktualisiert
+cache-cleared =
+    ✅ { $count ->
+        [0] Keine Playlists aus dem Cache entfernt
+        [one] Eine Playlist aus dem Cache entfernt
+       *[other] { $count } Playlists aus dem Cache entfernt
+    }
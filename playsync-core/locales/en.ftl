@@ -0,0 +1,214 @@
+# English messages of playsync, the reference for the other languages.
+# See https://projectfluent.org/ for the syntax.
+
+## Titles of the commands
+
+setup-intro = 🚀 PlaySync Setup
+config-intro = 📝 Playlist Configuration
+list-intro = 📜 Listing all playlists:
+sync-intro = 🔄 Playlist Sync
+sync-intro-dry-run = 🔍 Playlist Sync (Dry Run)
+apply-intro = 📋 Apply Sync Plan
+daemon-intro = ⏰ PlaySync Daemon
+health-intro = 🩺 Health Check
+serve-intro = 🌐 PlaySync API
+self-update-intro = ⬆️ Update playsync
+plugins-intro = 🧩 Plugins
+service-intro = ⚙️ Background Service
+history-intro = 📜 Run History
+status-intro = 🔎 Playlist Status
+explain-intro = 🔎 Explain
+search-intro = 🔍 Search Playlists
+merge-intro = 🧩 Merge Playlists
+merge-intro-dry-run = 🔍 Merge Playlists (Dry Run)
+split-intro = ✂️ Split Playlist
+split-intro-dry-run = 🔍 Split Playlist (Dry Run)
+copy-intro = 📋 Copy Playlist
+copy-intro-dry-run = 🔍 Copy Playlist (Dry Run)
+move-intro = 🚚 Move Videos
+move-intro-dry-run = 🔍 Move Videos (Dry Run)
+sort-intro = 🔀 Sort Playlist
+sort-intro-dry-run = 🔍 Sort Playlist (Dry Run)
+shuffle-intro = 🎲 Shuffle Playlist
+shuffle-intro-dry-run = 🔍 Shuffle Playlist (Dry Run)
+tidy-intro = 🧹 Tidy Playlists
+tidy-intro-dry-run = 🔍 Tidy Playlists (Dry Run)
+add-intro = ➕ Add Videos
+add-intro-dry-run = 🔍 Add Videos (Dry Run)
+remove-intro = ➖ Remove Videos
+remove-intro-dry-run = 🔍 Remove Videos (Dry Run)
+convert-intro = 🔁 Convert Playlist
+convert-intro-dry-run = 🔍 Convert Playlist (Dry Run)
+download-intro = 💾 Download Playlists
+download-intro-dry-run = 🔍 Download Playlists (Dry Run)
+import-intro = 📥 Import Playlists
+import-intro-dry-run = 🔍 Import Playlists (Dry Run)
+stats-intro = 📊 Playlist Statistics
+cache-intro = 🗄️ Playlist Cache
+
+## Questions
+
+setup-add-more =
+    A configuration with { $count ->
+        [one] one playlist
+       *[other] { $count } playlists
+    } already exists. Add more playlists to it?
+oauth2-json-prompt = Path to the OAuth2 JSON file
+no-file-at-path = No file at this path
+setup-pick-playlists = Select the playlists to manage with playsync:
+setup-sync-from = Sync '{ $title }' from (leave empty to only manage it):
+pick-sources = Select playlists to sync from:
+pick-playlists-to-remove = Select the playlists to remove:
+pick-playlists-to-sync = Select the playlists to sync:
+pick-imports = Select what to import:
+reset-confirm = Are you sure you want to reset the configuration?
+remove-from-sources =
+    { $count ->
+        [one] One playlist syncs
+       *[other] { $count } playlists sync
+    } from the removed playlists: { $playlists }. Remove them from their sources too?
+edit-again = Edit the file again?
+save-with-warnings = Save the configuration with these warnings?
+self-update-confirm = Update playsync from { $current } to { $latest }?
+passphrase-prompt = Passphrase of the configuration secrets
+passphrase-confirm = Confirm the passphrase
+
+## Results
+
+missing-oauth2-json = ❌ The path to the OAuth2 JSON file is not set. Please set it before syncing.
+client-not-initialized = ❌ YouTube client is not initialized.
+outside-sync-windows = Outside the sync windows. Nothing synced.
+outside-sync-windows-until = Outside the sync windows, the next one opens at { $time }. Nothing synced.
+setup-cancelled = Setup cancelled, the configuration is unchanged
+sign-in-failed = ❌ Failed to sign in: { $error }. The credentials path is saved, run `playsync init` again to retry.
+setup-completed-empty = ✅ Setup completed. Add playlists later with `playsync config --add-mine`
+setup-completed =
+    ✅ Setup completed with { $count ->
+        [one] one playlist
+       *[other] { $count } playlists
+    }. Run `playsync sync --dry-run` to preview the first sync
+graph-saved = ✅ Sync graph saved
+config-unchanged = The configuration is unchanged
+setting-set = ✅ Set { $key }
+config-reset = ✅ Configuration reset successfully
+oauth2-json-set = ✅ OAuth2 JSON path set successfully
+playlist-added = ✅ Playlist added successfully
+playlist-fetch-failed = ❌ Failed to fetch playlist info: { $error }
+no-playlists-added = No playlists added
+playlists-added =
+    ✅ Added { $count ->
+        [one] one playlist
+       *[other] { $count } playlists
+    }. Set their sources with `playsync config edit`
+playlist-removed = ✅ Playlist removed successfully
+no-playlists-configured-yet = No playlists configured
+no-playlists-removed = No playlists removed
+playlists-removed =
+    ✅ Removed { $count ->
+        [one] one playlist
+       *[other] { $count } playlists
+    }
+no-playlists-to-list = No playlists to list
+list-completed = ✅ Configuration listing completed
+config-invalid =
+    ❌ { $path } has { $count ->
+        [one] one problem
+       *[other] { $count } problems
+    }
+config-valid = ✅ { $path } is valid
+config-valid-with-warnings =
+    ⚠️ { $path } is valid, with { $count ->
+        [one] one warning
+       *[other] { $count } warnings
+    }
+no-secrets-stored = ✅ No API keys or tokens are stored in the configuration
+secrets-already-encrypted = ✅ All secrets are already encrypted
+no-secrets-encrypted = ✅ No secrets are encrypted
+secrets-encrypted = ✅ Encrypted { $names }
+secrets-decrypted = ✅ Decrypted { $names }
+editor-failed = ❌ The editor ({ $editor }) failed, the configuration is unchanged
+config-saved = ✅ Configuration saved to { $path }
+changes-discarded = ❌ Changes discarded, the configuration is unchanged
+no-playlists-to-sync = ❌ No playlists found to sync
+no-playlists-selected = No playlists selected
+dry-run-completed = ✅ Dry run completed
+sync-completed = ✅ Sync completed
+plan-empty = Nothing to apply, the plan has no changes
+plan-refused = ❌ Plan refused, nothing was changed
+plan-applied = ✅ Plan applied
+daemon-stopped = Daemon stopped
+unhealthy = ❌ Unhealthy
+healthy = ✅ Healthy
+server-stopped = Server stopped
+up-to-date = ✅ playsync { $version } is the latest version
+update-available = playsync { $latest } is available (installed: { $current }): { $url }
+update-with-package-manager = playsync { $latest } is available, update it with { $manager }
+self-update-unchanged = playsync is unchanged
+updated = ✅ Updated playsync to { $version }
+no-plugins = No plugins in { $dir }
+plugins-found =
+    { $count ->
+        [one] One plugin
+       *[other] { $count } plugins
+    } in { $dir }
+service-not-installed = Run again with --install to write and enable these files
+service-installed = ✅ Service installed
+no-runs = No runs found
+runs-shown = ✅ Showing { $shown } of { $total } runs
+no-sync-relationships = ❌ No sync relationships found
+all-up-to-date = ✅ All playlists are up to date
+playlists-behind = ⚠️ { $behind } of { $total } playlists are out of sync
+playlist-not-configured = ❌ Playlist { $playlist } is not configured
+no-sources = '{ $title }' has no sync_from sources, videos are only added to it by hand
+not-a-video = ❌ '{ $video }' is not a video ID or URL
+no-playlists-configured = ❌ No playlists configured
+no-match = ❌ No video matching '{ $query }' found
+matches-found =
+    ✅ Found { $count ->
+        [one] one matching item
+       *[other] { $count } matching items
+    }
+merged = ✅ Merged into '{ $title }' (ID: { $id })
+split-nothing = ❌ The playlist would not be split into several parts
+split-completed = ✅ Split completed
+nothing-to-copy = ✅ Nothing to copy
+copy-completed = ✅ Copy completed
+move-no-filter = ❌ No filter given. Use --all to move every video.
+nothing-to-move = ✅ Nothing to move
+move-completed = ✅ Move completed
+already-sorted = ✅ Playlist is already in the requested order
+playlist-reordered = ✅ Playlist reordered
+no-playlists-to-tidy = ❌ No playlists found to tidy
+tidy-completed = ✅ Tidy completed
+nothing-to-add = ✅ Nothing to add
+videos-added = ✅ Videos added
+remove-nothing-selected = ❌ Nothing selected. Give video URLs/IDs, a filter or --placeholders.
+nothing-to-remove = ✅ Nothing to remove
+videos-removed = ✅ Videos removed
+not-a-configured-playlist = ❌ '{ $playlist }' is not a configured playlist
+opened = ✅ Opened '{ $title }' in the browser
+nothing-to-convert = ✅ Nothing to convert
+converted = ✅ Converted into '{ $title }': { $url }
+no-download-dir = ❌ No playlists with a download_dir found
+download-completed = ✅ Download completed
+import-nothing = ❌ No YouTube playlists or subscriptions found in the file
+imported =
+    ✅ Imported { $count ->
+        [one] one playlist
+       *[other] { $count } playlists
+    }. Run `playsync sync` to create them on YouTube.
+no-playlists-found = ❌ No playlists found
+stats-completed = ✅ Statistics completed
+cache-empty = The cache is empty
+cache-listed =
+    { $count ->
+        [one] One cached playlist
+       *[other] { $count } cached playlists
+    }
+cache-refreshed = ✅ Refreshed { $count } of { $total } playlists
+cache-cleared =
+    ✅ { $count ->
+        [0] No playlists dropped from the cache
+        [one] Dropped one playlist from the cache
+       *[other] Dropped { $count } playlists from the cache
+    }
@@ -0,0 +1,213 @@
+# Messages de playsync en français
+
+## Titles of the commands
+
+setup-intro = 🚀 Configuration de PlaySync
+config-intro = 📝 Configuration des playlists
+list-intro = 📜 Liste des playlists :
+sync-intro = 🔄 Synchronisation des playlists
+sync-intro-dry-run = 🔍 Synchronisation des playlists (simulation)
+apply-intro = 📋 Appliquer le plan de synchronisation
+daemon-intro = ⏰ Démon PlaySync
+health-intro = 🩺 Bilan de santé
+serve-intro = 🌐 API PlaySync
+self-update-intro = ⬆️ Mettre à jour playsync
+plugins-intro = 🧩 Plugins
+service-intro = ⚙️ Service en arrière-plan
+history-intro = 📜 Historique des exécutions
+status-intro = 🔎 État des playlists
+explain-intro = 🔎 Explication
+search-intro = 🔍 Rechercher dans les playlists
+merge-intro = 🧩 Fusionner des playlists
+merge-intro-dry-run = 🔍 Fusionner des playlists (simulation)
+split-intro = ✂️ Découper une playlist
+split-intro-dry-run = 🔍 Découper une playlist (simulation)
+copy-intro = 📋 Copier une playlist
+copy-intro-dry-run = 🔍 Copier une playlist (simulation)
+move-intro = 🚚 Déplacer des vidéos
+move-intro-dry-run = 🔍 Déplacer des vidéos (simulation)
+sort-intro = 🔀 Trier une playlist
+sort-intro-dry-run = 🔍 Trier une playlist (simulation)
+shuffle-intro = 🎲 Mélanger une playlist
+shuffle-intro-dry-run = 🔍 Mélanger une playlist (simulation)
+tidy-intro = 🧹 Ranger les playlists
+tidy-intro-dry-run = 🔍 Ranger les playlists (simulation)
+add-intro = ➕ Ajouter des vidéos
+add-intro-dry-run = 🔍 Ajouter des vidéos (simulation)
+remove-intro = ➖ Retirer des vidéos
+remove-intro-dry-run = 🔍 Retirer des vidéos (simulation)
+convert-intro = 🔁 Convertir une playlist
+convert-intro-dry-run = 🔍 Convertir une playlist (simulation)
+download-intro = 💾 Télécharger des playlists
+download-intro-dry-run = 🔍 Télécharger des playlists (simulation)
+import-intro = 📥 Importer des playlists
+import-intro-dry-run = 🔍 Importer des playlists (simulation)
+stats-intro = 📊 Statistiques des playlists
+cache-intro = 🗄️ Cache des playlists
+
+## Questions
+
+setup-add-more =
+    Une configuration avec { $count ->
+        [one] une playlist
+       *[other] { $count } playlists
+    } existe déjà. Y ajouter d'autres playlists ?
+oauth2-json-prompt = Chemin du fichier JSON OAuth2
+no-file-at-path = Aucun fichier à cet emplacement
+setup-pick-playlists = Sélectionnez les playlists à gérer avec playsync :
+setup-sync-from = Synchroniser « { $title } » depuis (laisser vide pour seulement la gérer) :
+pick-sources = Sélectionnez les playlists sources :
+pick-playlists-to-remove = Sélectionnez les playlists à retirer :
+pick-playlists-to-sync = Sélectionnez les playlists à synchroniser :
+pick-imports = Sélectionnez ce qu'il faut importer :
+reset-confirm = Voulez-vous vraiment réinitialiser la configuration ?
+remove-from-sources =
+    { $count ->
+        [one] Une playlist se synchronise
+       *[other] { $count } playlists se synchronisent
+    } depuis les playlists retirées : { $playlists }. Les retirer aussi de leurs sources ?
+edit-again = Modifier à nouveau le fichier ?
+save-with-warnings = Enregistrer la configuration malgré ces avertissements ?
+self-update-confirm = Mettre à jour playsync de { $current } vers { $latest } ?
+passphrase-prompt = Phrase secrète des secrets de la configuration
+passphrase-confirm = Confirmez la phrase secrète
+
+## Results
+
+missing-oauth2-json = ❌ Le chemin du fichier JSON OAuth2 n'est pas défini. Définissez-le avant de synchroniser.
+client-not-initialized = ❌ Le client YouTube n'est pas initialisé.
+outside-sync-windows = En dehors des plages de synchronisation. Rien n'a été synchronisé.
+outside-sync-windows-until = En dehors des plages de synchronisation, la prochaine commence à { $time }. Rien n'a été synchronisé.
+setup-cancelled = Configuration annulée, rien n'a été modifié
+sign-in-failed = ❌ Échec de la connexion : { $error }. Le chemin des identifiants est enregistré, relancez `playsync init` pour réessayer.
+setup-completed-empty = ✅ Configuration terminée. Ajoutez des playlists plus tard avec `playsync config --add-mine`
+setup-completed =
+    ✅ Configuration terminée avec { $count ->
+        [one] une playlist
+       *[other] { $count } playlists
+    }. Lancez `playsync sync --dry-run` pour prévisualiser la première synchronisation
+graph-saved = ✅ Graphe de synchronisation enregistré
+config-unchanged = La configuration est inchangée
+setting-set = ✅ { $key } défini
+config-reset = ✅ Configuration réinitialisée
+oauth2-json-set = ✅ Chemin du fichier JSON OAuth2 défini
+playlist-added = ✅ Playlist ajoutée
+playlist-fetch-failed = ❌ Impossible de récupérer les informations de la playlist : { $error }
+no-playlists-added = Aucune playlist ajoutée
+playlists-added =
+    ✅ { $count ->
+        [one] Une playlist ajoutée
+       *[other] { $count } playlists ajoutées
+    }. Définissez leurs sources avec `playsync config edit`
+playlist-removed = ✅ Playlist retirée
+no-playlists-configured-yet = Aucune playlist configurée
+no-playlists-removed = Aucune playlist retirée
+playlists-removed =
+    ✅ { $count ->
+        [one] Une playlist retirée
+       *[other] { $count } playlists retirées
+    }
+no-playlists-to-list = Aucune playlist à afficher
+list-completed = ✅ Affichage de la configuration terminé
+config-invalid =
+    ❌ { $path } a { $count ->
+        [one] un problème
+       *[other] { $count } problèmes
+    }
+config-valid = ✅ { $path } est valide
+config-valid-with-warnings =
+    ⚠️ { $path } est valide, avec { $count ->
+        [one] un avertissement
+       *[other] { $count } avertissements
+    }
+no-secrets-stored = ✅ Aucune clé d'API ni aucun jeton n'est enregistré dans la configuration
+secrets-already-encrypted = ✅ Tous les secrets sont déjà chiffrés
+no-secrets-encrypted = ✅ Aucun secret n'est chiffré
+secrets-encrypted = ✅ Chiffré : { $names }
+secrets-decrypted = ✅ Déchiffré : { $names }
+editor-failed = ❌ L'éditeur ({ $editor }) a échoué, la configuration est inchangée
+config-saved = ✅ Configuration enregistrée dans { $path }
+changes-discarded = ❌ Modifications abandonnées, la configuration est inchangée
+no-playlists-to-sync = ❌ Aucune playlist à synchroniser
+no-playlists-selected = Aucune playlist sélectionnée
+dry-run-completed = ✅ Simulation terminée
+sync-completed = ✅ Synchronisation terminée
+plan-empty = Rien à appliquer, le plan ne contient aucune modification
+plan-refused = ❌ Plan refusé, rien n'a été modifié
+plan-applied = ✅ Plan appliqué
+daemon-stopped = Démon arrêté
+unhealthy = ❌ Problèmes détectés
+healthy = ✅ Tout fonctionne
+server-stopped = Serveur arrêté
+up-to-date = ✅ playsync { $version } est la dernière version
+update-available = playsync { $latest } est disponible (installée : { $current }) : { $url }
+update-with-package-manager = playsync { $latest } est disponible, mettez-le à jour avec { $manager }
+self-update-unchanged = playsync n'a pas été modifié
+updated = ✅ playsync mis à jour vers { $version }
+no-plugins = Aucun plugin dans { $dir }
+plugins-found =
+    { $count ->
+        [one] Un plugin
+       *[other] { $count } plugins
+    } dans { $dir }
+service-not-installed = Relancez avec --install pour écrire et activer ces fichiers
+service-installed = ✅ Service installé
+no-runs = Aucune exécution trouvée
+runs-shown = ✅ { $shown } exécutions affichées sur { $total }
+no-sync-relationships = ❌ Aucune relation de synchronisation trouvée
+all-up-to-date = ✅ Toutes les playlists sont à jour
+playlists-behind = ⚠️ { $behind } playlists sur { $total } ne sont pas synchronisées
+playlist-not-configured = ❌ La playlist { $playlist } n'est pas configurée
+no-sources = « { $title } » n'a pas de sources sync_from, les vidéos n'y sont ajoutées qu'à la main
+not-a-video = ❌ « { $video } » n'est pas un ID ou une URL de vidéo
+no-playlists-configured = ❌ Aucune playlist configurée
+no-match = ❌ Aucune vidéo ne correspond à « { $query } »
+matches-found =
+    ✅ { $count ->
+        [one] Un élément trouvé
+       *[other] { $count } éléments trouvés
+    }
+merged = ✅ Fusionnées dans « { $title } » (ID : { $id })
+split-nothing = ❌ La playlist ne serait pas découpée en plusieurs parties
+split-completed = ✅ Découpage terminé
+nothing-to-copy = ✅ Rien à copier
+copy-completed = ✅ Copie terminée
+move-no-filter = ❌ Aucun filtre donné. Utilisez --all pour déplacer toutes les vidéos.
+nothing-to-move = ✅ Rien à déplacer
+move-completed = ✅ Déplacement terminé
+already-sorted = ✅ La playlist est déjà dans l'ordre demandé
+playlist-reordered = ✅ Playlist réordonnée
+no-playlists-to-tidy = ❌ Aucune playlist à ranger
+tidy-completed = ✅ Rangement terminé
+nothing-to-add = ✅ Rien à ajouter
+videos-added = ✅ Vidéos ajoutées
+remove-nothing-selected = ❌ Rien n'est sélectionné. Donnez des URL ou ID de vidéos, un filtre ou --placeholders.
+nothing-to-remove = ✅ Rien à retirer
+videos-removed = ✅ Vidéos retirées
+not-a-configured-playlist = ❌ « { $playlist } » n'est pas une playlist configurée
+opened = ✅ « { $title } » ouverte dans le navigateur
+nothing-to-convert = ✅ Rien à convertir
+converted = ✅ Convertie en « { $title } » : { $url }
+no-download-dir = ❌ Aucune playlist avec un download_dir
+download-completed = ✅ Téléchargement terminé
+import-nothing = ❌ Aucune playlist ni aucun abonnement YouTube dans le fichier
+imported =
+    ✅ { $count ->
+        [one] Une playlist importée
+       *[other] { $count } playlists importées
+    }. Lancez `playsync sync` pour les créer sur YouTube.
+no-playlists-found = ❌ Aucune playlist trouvée
+stats-completed = ✅ Statistiques terminées
+cache-empty = Le cache est vide
+cache-listed =
+    { $count ->
+        [one] Une playlist en cache
+       *[other] { $count } playlists en cache
+    }
+cache-refreshed = ✅ { $count } playlists actualisées sur { $total }
+cache-cleared =
+    ✅ { $count ->
+        [0] Aucune playlist retirée du cache
+        [one] Une playlist retirée du cache
+       *[other] { $count } playlists retirées du cache
+    }
@@ -0,0 +1,280 @@
+use crate::youtube::VideoInfo;
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// How old cached items may be for read-only commands (`status`, `stats`, `explain`,
+/// `search`) to use them instead of fetching the playlist again
+pub const MAX_AGE: TimeDelta = TimeDelta::minutes(15);
+
+/// How long cached items are checked with the ETag of the playlist before it's
+/// downloaded again in full. The ETag is the one of the first page of items, which
+/// stays the same when an item further down is replaced by another.
+pub const REVALIDATE_FOR: TimeDelta = TimeDelta::days(1);
+
+/// Version of [`SCHEMA`]. The cache only holds copies of YouTube data, so a cache
+/// written by another version is dropped instead of migrated.
+const SCHEMA_VERSION: i32 = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE playlists (
+    playlist_id TEXT PRIMARY KEY,
+    etag TEXT,
+    fetched_at TEXT NOT NULL,
+    checked_at TEXT NOT NULL,
+    complete INTEGER NOT NULL
+);
+CREATE TABLE items (
+    playlist_id TEXT NOT NULL REFERENCES playlists (playlist_id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    item_id TEXT NOT NULL,
+    video_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    channel TEXT,
+    added_at TEXT,
+    published_at TEXT,
+    PRIMARY KEY (playlist_id, position)
+);
+";
+
+/// The items of a playlist as they were last fetched
+pub struct CachedPlaylist {
+    pub items: Vec<VideoInfo>,
+    /// ETag of the first page of items
+    pub etag: Option<String>,
+    /// When the items were last downloaded
+    pub fetched_at: DateTime<Utc>,
+    /// When the items were last known to be up to date: downloaded, or found unchanged
+    /// with their ETag
+    pub checked_at: DateTime<Utc>,
+}
+
+/// A cached playlist, as listed by `playsync cache`
+#[derive(Serialize)]
+pub struct CacheEntry {
+    pub playlist_id: String,
+    pub item_count: usize,
+    pub fetched_at: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Local copy of the items of the playlists fetched from YouTube, in `cache.sqlite` in
+/// the config directory
+pub struct Cache {
+    connection: Connection,
+}
+
+impl Cache {
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::open_path(&crate::config::config_dir()?.join("cache.sqlite"))
+    }
+
+    fn open_path(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let connection = Connection::open(path)?;
+        // Several playsync runs (e.g. the daemon and a command) may use the cache at once
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+
+        let version: i32 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            connection.execute_batch(
+                "DROP TABLE IF EXISTS items;
+                 DROP TABLE IF EXISTS playlists;",
+            )?;
+            connection.execute_batch(SCHEMA)?;
+            connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+
+        Ok(Self { connection })
+    }
+
+    /// The cached items of a playlist, in playlist order
+    pub fn playlist(
+        &self,
+        playlist_id: &str,
+    ) -> Result<Option<CachedPlaylist>, Box<dyn std::error::Error>> {
+        let Some((etag, fetched_at, checked_at)) = self
+            .connection
+            .query_row(
+                "SELECT etag, fetched_at, checked_at FROM playlists
+                 WHERE playlist_id = ?1 AND complete",
+                [playlist_id],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut statement = self.connection.prepare(
+            "SELECT item_id, video_id, title, channel, added_at, published_at
+             FROM items
+             WHERE playlist_id = ?1
+             ORDER BY position",
+        )?;
+        let items = statement
+            .query_map([playlist_id], |row| {
+                Ok(VideoInfo {
+                    item_id: row.get(0)?,
+                    video_id: row.get(1)?,
+                    title: row.get(2)?,
+                    channel: row.get(3)?,
+                    added_at: parse_time(row.get(4)?),
+                    published_at: parse_time(row.get(5)?),
+                    details: None,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(CachedPlaylist {
+            items,
+            etag,
+            fetched_at: DateTime::parse_from_rfc3339(&fetched_at)?.with_timezone(&Utc),
+            checked_at: DateTime::parse_from_rfc3339(&checked_at)?.with_timezone(&Utc),
+        }))
+    }
+
+    /// The cached playlists, the most recently checked first
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, Box<dyn std::error::Error>> {
+        let mut statement = self.connection.prepare(
+            "SELECT playlist_id, fetched_at, checked_at,
+                 (SELECT COUNT(*) FROM items WHERE items.playlist_id = playlists.playlist_id)
+             FROM playlists
+             WHERE complete",
+        )?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut entries = Vec::new();
+        for (playlist_id, fetched_at, checked_at, item_count) in rows {
+            entries.push(CacheEntry {
+                playlist_id,
+                item_count: item_count as usize,
+                fetched_at: DateTime::parse_from_rfc3339(&fetched_at)?.with_timezone(&Utc),
+                checked_at: DateTime::parse_from_rfc3339(&checked_at)?.with_timezone(&Utc),
+            });
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.checked_at));
+        Ok(entries)
+    }
+
+    /// Start replacing the cached items of a playlist with the ones being fetched. The
+    /// playlist isn't cached until [`finish_store`](Self::finish_store) is called, after
+    /// its items were stored page by page with [`store_items`](Self::store_items).
+    pub fn start_store(&self, playlist_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Deleting the playlist deletes its items too
+        self.connection.execute(
+            "DELETE FROM playlists WHERE playlist_id = ?1",
+            [playlist_id],
+        )?;
+        let now = Utc::now().to_rfc3339();
+        self.connection.execute(
+            "INSERT INTO playlists (playlist_id, fetched_at, checked_at, complete)
+             VALUES (?1, ?2, ?2, FALSE)",
+            params![playlist_id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Store a page of items of a playlist being fetched, the first one being at
+    /// `position` in the playlist
+    pub fn store_items(
+        &mut self,
+        playlist_id: &str,
+        position: usize,
+        items: &[VideoInfo],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO items
+                 (playlist_id, position, item_id, video_id, title, channel, added_at, published_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (offset, item) in items.iter().enumerate() {
+                statement.execute(params![
+                    playlist_id,
+                    (position + offset) as i64,
+                    item.item_id,
+                    item.video_id,
+                    item.title,
+                    item.channel,
+                    item.added_at.map(|time| time.to_rfc3339()),
+                    item.published_at.map(|time| time.to_rfc3339()),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Mark a playlist as cached once all its items are stored, along with the ETag of
+    /// the response they came from
+    pub fn finish_store(
+        &self,
+        playlist_id: &str,
+        etag: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "UPDATE playlists SET etag = ?2, complete = TRUE WHERE playlist_id = ?1",
+            params![playlist_id, etag],
+        )?;
+        Ok(())
+    }
+
+    /// Record that the cached items of a playlist were found up to date
+    pub fn touch(&self, playlist_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "UPDATE playlists SET checked_at = ?2 WHERE playlist_id = ?1",
+            params![playlist_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Forget the cached items of a playlist, after it was changed. Returns whether the
+    /// playlist was cached.
+    pub fn remove(&self, playlist_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let removed = self.connection.execute(
+            "DELETE FROM playlists WHERE playlist_id = ?1",
+            [playlist_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Forget every cached playlist. Returns how many there were.
+    pub fn clear(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(self.connection.execute("DELETE FROM playlists", [])?)
+    }
+
+    /// Forget the cached items of the playlist holding an item, after it was removed
+    pub fn remove_item(&self, item_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection.execute(
+            "DELETE FROM playlists
+             WHERE playlist_id IN (SELECT playlist_id FROM items WHERE item_id = ?1)",
+            [item_id],
+        )?;
+        Ok(())
+    }
+}
+
+fn parse_time(value: Option<String>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value?)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
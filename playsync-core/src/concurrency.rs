@@ -0,0 +1,123 @@
+use std::sync::Mutex;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Reasons the API gives when it gets too many requests at once
+const THROTTLED_REASONS: &[&str] = &["rateLimitExceeded", "userRateLimitExceeded"];
+
+/// How many requests may be in flight at once, tuned to the responses of the API.
+///
+/// The limit is halved each time the API throttles playsync (`429 Too Many Requests`, or
+/// a rate limit error), and raised by one after as many healthy responses in a row as
+/// the limit, up to `max`.
+pub struct AdaptiveLimit {
+    semaphore: Semaphore,
+    state: Mutex<State>,
+    max: usize,
+}
+
+struct State {
+    limit: usize,
+    /// Healthy responses since the limit last changed
+    successes: usize,
+    /// Permits to drop instead of giving them back, after the limit was lowered while
+    /// they were in use
+    excess: usize,
+}
+
+/// A request slot, given back when dropped
+pub struct Permit<'a> {
+    limit: &'a AdaptiveLimit,
+    permit: Option<SemaphorePermit<'a>>,
+}
+
+impl AdaptiveLimit {
+    pub fn new(initial: usize, max: usize) -> Self {
+        AdaptiveLimit {
+            semaphore: Semaphore::new(initial),
+            state: Mutex::new(State {
+                limit: initial,
+                successes: 0,
+                excess: 0,
+            }),
+            max,
+        }
+    }
+
+    /// Wait for a request slot
+    pub async fn acquire(&self) -> Permit<'_> {
+        Permit {
+            limit: self,
+            // The semaphore is never closed
+            permit: self.semaphore.acquire().await.ok(),
+        }
+    }
+
+    /// Current number of requests allowed at once
+    pub fn current(&self) -> usize {
+        self.state.lock().map_or(1, |state| state.limit)
+    }
+
+    /// Adjust the limit to the outcome of a request
+    pub fn record<T>(&self, result: &Result<T, Box<dyn std::error::Error>>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        match result {
+            Err(e) if is_throttled(e.as_ref()) => {
+                let lowered = (state.limit / 2).max(1);
+                // The free slots go at once, the others when their request is over
+                let dropped = state.limit - lowered;
+                state.excess += dropped - self.semaphore.forget_permits(dropped);
+                state.limit = lowered;
+                state.successes = 0;
+            }
+            // Other errors say nothing about the load
+            Err(_) => {}
+            Ok(_) => {
+                state.successes += 1;
+                if state.successes >= state.limit && state.limit < self.max {
+                    state.limit += 1;
+                    state.successes = 0;
+                    if state.excess > 0 {
+                        state.excess -= 1;
+                    } else {
+                        self.semaphore.add_permits(1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+        if let Ok(mut state) = self.limit.state.lock()
+            && state.excess > 0
+        {
+            state.excess -= 1;
+            permit.forget();
+        }
+    }
+}
+
+/// Whether the API refused a request because of too many requests at once. The daily
+/// quota running out isn't such an error: it doesn't get better by slowing down.
+pub fn is_throttled(error: &(dyn std::error::Error + 'static)) -> bool {
+    match error.downcast_ref::<google_youtube3::Error>() {
+        Some(google_youtube3::Error::BadRequest(body)) => {
+            body["error"]["code"].as_u64() == Some(429)
+                || body["error"]["errors"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e["reason"].as_str())
+                    .any(|reason| THROTTLED_REASONS.contains(&reason))
+        }
+        Some(google_youtube3::Error::Failure(response)) => response.status().as_u16() == 429,
+        _ => false,
+    }
+}
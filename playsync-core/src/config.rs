@@ -0,0 +1,1068 @@
+use crate::exit;
+use crate::filter::FilterArgs;
+use crate::hooks::Hooks;
+use crate::notify::Notifications;
+use crate::ratelimit::RateLimit;
+use crate::report::Reports;
+use crate::source::MusicServices;
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Configuration file given on the command line, overriding the default location
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Profile selected on the command line, `None` for the default profile
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Environment variables starting with `PLAYSYNC_` that aren't settings: options of the
+/// command line, and the variables given to hooks
+const NOT_SETTINGS: [&str; 17] = [
+    "ADDED",
+    "API_TOKEN",
+    "CONFIG",
+    "ERROR",
+    "FAILED",
+    "ITEM_COUNT",
+    "MINISIGN_PUBLIC_KEY",
+    "OUTPUT",
+    "PASSPHRASE",
+    "PLAYLIST_ID",
+    "PLAYLIST_TITLE",
+    "PROFILE",
+    "QUIET",
+    "REMOVED",
+    "REPORT",
+    "REPORT_MARKDOWN",
+    "YES",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Other files holding playlists, merged into this configuration when it's read.
+    /// Relative paths are relative to the directory of the configuration file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+
+    /// OAuth2 JSON file path for YouTube API authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth2_json: Option<String>,
+
+    /// Language of the messages, e.g. `fr`. The locale of the system is used when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Last.fm API key, needed for `lastfm:` sources (can be encrypted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_api_key: Option<String>,
+
+    /// ListenBrainz user token, used for `listenbrainz:` sources (can be encrypted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listenbrainz_token: Option<String>,
+
+    /// Spotify app client ID, needed for `spotify:` sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spotify_client_id: Option<String>,
+
+    /// Spotify app client secret, needed for `spotify:` sources (can be encrypted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spotify_client_secret: Option<String>,
+
+    /// Resolve tracks of music sources on MusicBrainz before searching them on YouTube
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub musicbrainz: bool,
+
+    /// Directory where an .m3u file of each target playlist is written after each sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m3u_dir: Option<PathBuf>,
+
+    /// Where a report of each sync run is written, for automation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reports: Option<Reports>,
+
+    /// Times of day syncs are allowed, e.g. `["02:00-06:00"]`, in local time.
+    /// Honored by `playsync daemon`, and by `playsync sync --respect-windows`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sync_windows: Vec<String>,
+
+    /// Settings inherited by the playlists that don't set them
+    #[serde(default, skip_serializing_if = "Defaults::is_empty")]
+    pub defaults: Defaults,
+
+    /// Where to report the result of each sync
+    #[serde(default, skip_serializing_if = "Notifications::is_empty")]
+    pub notifications: Notifications,
+
+    /// How fast requests are sent to YouTube
+    #[serde(default, skip_serializing_if = "RateLimit::is_empty")]
+    pub rate_limit: RateLimit,
+
+    /// List of playlists to sync
+    pub playlists: Vec<Playlist>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Playlist {
+    /// The ID of the playlist
+    /// Use a placeholder like `new:"Chill Mix"` to have the playlist created on the next sync.
+    pub id: String,
+
+    /// The title of the playlist
+    pub title: String,
+
+    /// Short name accepted instead of the ID on the command line, e.g. `sync --id chill`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+
+    /// Groups the playlist belongs to, e.g. `["music", "podcasts"]`, for `sync --group`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Optionally specify playlists to sync from
+    /// The playlists should be specified as a space-separated list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_from: Option<Vec<String>>,
+
+    /// Description used when the playlist is created by playsync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Privacy status used when the playlist is created by playsync (private, unlisted or public)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<String>,
+
+    /// Recreate the playlist on the next sync if it was deleted on YouTube
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recreate_if_missing: bool,
+
+    /// Copy the title and description of the first source playlist on each sync
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sync_metadata: bool,
+
+    /// Template for the playlist description, updated on each sync.
+    /// `{sources}` is replaced by the source playlist titles and `{date}` by the sync date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_template: Option<String>,
+
+    /// yt-dlp download archive to update with the video IDs of the playlist after each sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_archive: Option<PathBuf>,
+
+    /// Directory kept as an offline copy of the playlist with yt-dlp after each sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+
+    /// Which way videos flow between the playlist and its sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SyncDirection>,
+
+    /// Remove the videos that are no longer in any source on each sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<bool>,
+
+    /// Only add the videos of the sources passing these filters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<FilterArgs>,
+
+    /// Rhai script deciding which videos of the sources are added, after `filter`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
+
+    /// Plugins of the plugins directory filtering the videos added, after `script`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugin_filters: Vec<String>,
+
+    /// Where new videos are inserted in the playlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<InsertPosition>,
+
+    /// Maximum number of videos added per sync, the others are added on the next syncs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_adds: Option<usize>,
+
+    /// Pause between two videos added to the playlist, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_delay_ms: Option<u64>,
+
+    /// Name of the Google account owning the playlist, when it's not the main one.
+    /// Each account signs in once and keeps its own token cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+
+    /// Cron expression of when `playsync daemon` syncs the playlist (e.g. `0 7 * * *`),
+    /// instead of every `--interval`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+
+    /// How often `playsync daemon` syncs the playlist (e.g. `15m`, `6h`, `1d`),
+    /// instead of every `--interval`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+
+    /// Shell commands run before and after each sync of the playlist, and when it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+
+    /// How old the cached items of the playlist may be before a command fetches it
+    /// again (e.g. `15m`, `1h`, `1d`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<String>,
+
+    /// Included file the playlist was read from, `None` for the main configuration file
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+}
+
+/// A file listed in `include`, holding more playlists
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct IncludeFile {
+    #[serde(default)]
+    playlists: Vec<Playlist>,
+}
+
+/// Which way videos flow between a playlist and its sources
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncDirection {
+    /// Videos of the sources are added to the playlist
+    #[default]
+    OneWay,
+    /// Videos of the playlist are also added to the source playlists missing them
+    TwoWay,
+}
+
+/// Where new videos are inserted in a playlist
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InsertPosition {
+    /// After the existing items
+    #[default]
+    End,
+    /// Before the existing items, in the order of the sources
+    Start,
+}
+
+/// Settings inherited by the playlists that don't set them, in the `[defaults]` section
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Defaults {
+    /// Which way videos flow between playlists and their sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SyncDirection>,
+
+    /// Remove the videos that are no longer in any source on each sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<bool>,
+
+    /// Only add the videos of the sources passing these filters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<FilterArgs>,
+
+    /// Where new videos are inserted in playlists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<InsertPosition>,
+
+    /// Maximum number of videos added to a playlist per sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_adds: Option<usize>,
+
+    /// Pause between two videos added to a playlist, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_delay_ms: Option<u64>,
+
+    /// Privacy status of the playlists created by playsync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<String>,
+
+    /// Shell commands run around the sync of each playlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+
+    /// How old the cached items of playlists may be before a command fetches them again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<String>,
+
+    /// Make `sync` a dry run unless `--apply` is given
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+}
+
+impl Defaults {
+    fn is_empty(&self) -> bool {
+        self.direction.is_none()
+            && self.mirror.is_none()
+            && self.filter.is_none()
+            && self.position.is_none()
+            && self.max_adds.is_none()
+            && self.write_delay_ms.is_none()
+            && self.privacy.is_none()
+            && self.hooks.is_none()
+            && self.cache_max_age.is_none()
+            && !self.dry_run
+    }
+}
+
+impl Playlist {
+    /// Whether the playlist is tagged with a group (case-insensitive)
+    pub fn has_tag(&self, group: &str) -> bool {
+        self.tags.iter().any(|tag| tag.eq_ignore_ascii_case(group))
+    }
+
+    /// The title to create the playlist with, if its ID is a `new:"Title"` placeholder
+    pub fn placeholder_title(&self) -> Option<&str> {
+        let title = self.id.strip_prefix("new:")?.trim();
+        let title = title
+            .strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .unwrap_or(title);
+
+        Some(title)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            playlists: Vec::new(),
+            include: Vec::new(),
+            oauth2_json: None,
+            language: None,
+            lastfm_api_key: None,
+            listenbrainz_token: None,
+            spotify_client_id: None,
+            spotify_client_secret: None,
+            musicbrainz: false,
+            m3u_dir: None,
+            reports: None,
+            sync_windows: Vec::new(),
+            defaults: Defaults::default(),
+            notifications: Notifications::default(),
+            rate_limit: RateLimit::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Add a playlist to the configuration
+    pub fn add_playlist(&mut self, playlist: Playlist) -> &Self {
+        self.playlists.push(playlist);
+
+        self
+    }
+
+    /// Remove a playlist by its ID
+    pub fn remove_playlist(&mut self, id: &str) -> &Self {
+        self.playlists.retain(|p| p.id != id);
+
+        self
+    }
+
+    /// Remove playlists from the sync sources of all playlists.
+    ///
+    /// Returns the titles of the playlists left without any source.
+    pub fn remove_sources(&mut self, ids: &[String]) -> Vec<String> {
+        let mut sourceless = Vec::new();
+
+        for playlist in &mut self.playlists {
+            let Some(sync_from) = &mut playlist.sync_from else {
+                continue;
+            };
+            let count = sync_from.len();
+            sync_from.retain(|source| !ids.contains(source));
+
+            if sync_from.is_empty() && count > 0 {
+                playlist.sync_from = None;
+                sourceless.push(playlist.title.clone());
+            }
+        }
+
+        sourceless
+    }
+
+    /// Replace a playlist ID everywhere in the configuration, including sync sources
+    pub fn replace_playlist_id(&mut self, old_id: &str, new_id: &str) {
+        for playlist in &mut self.playlists {
+            if playlist.id == old_id {
+                playlist.id = new_id.to_string();
+            }
+
+            if let Some(sync_from) = &mut playlist.sync_from {
+                for source_id in sync_from.iter_mut().filter(|id| *id == old_id) {
+                    *source_id = new_id.to_string();
+                }
+            }
+        }
+    }
+
+    /// Find a configured playlist by its ID, its alias or its title (case-insensitive)
+    pub fn find_playlist(&self, query: &str) -> Option<&Playlist> {
+        self.playlists
+            .iter()
+            .find(|p| p.id == query)
+            .or_else(|| {
+                self.playlists.iter().find(|p| {
+                    p.alias
+                        .as_deref()
+                        .is_some_and(|alias| alias.eq_ignore_ascii_case(query))
+                })
+            })
+            .or_else(|| {
+                self.playlists
+                    .iter()
+                    .find(|p| p.title.eq_ignore_ascii_case(query))
+            })
+    }
+
+    /// Resolve a playlist given on the command line to its ID.
+    ///
+    /// Configured playlists can be given by ID, alias or title. Anything else that looks
+    /// like a playlist ID is returned as is, since playlists don't need to be configured
+    /// to be read. Otherwise the error suggests the closest alias or title.
+    pub fn resolve_playlist(&self, query: &str) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(playlist) = self.find_playlist(query) {
+            return Ok(playlist.id.clone());
+        }
+
+        let looks_like_id = query.starts_with("new:")
+            || query == "LL"
+            || (query.len() >= 10
+                && query
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        if looks_like_id {
+            return Ok(query.to_string());
+        }
+
+        let query_lower = query.to_lowercase();
+        let suggestion = self
+            .playlists
+            .iter()
+            .flat_map(|p| p.alias.iter().chain([&p.title]))
+            .map(|name| {
+                let name_lower = name.to_lowercase();
+                let distance = if name_lower.contains(&query_lower) {
+                    0
+                } else {
+                    edit_distance(&query_lower, &name_lower)
+                };
+                (distance, name)
+            })
+            .filter(|(distance, _)| *distance <= (query.chars().count() / 3).max(2))
+            .min_by_key(|(distance, _)| *distance);
+
+        Err(match suggestion {
+            Some((_, name)) => format!(
+                "No configured playlist matches '{}'. Did you mean '{}'?",
+                query, name
+            ),
+            None => format!(
+                "No configured playlist matches '{}'. Run `playsync config --list` to see the playlists.",
+                query
+            ),
+        }
+        .into())
+    }
+
+    /// A copy of a playlist with the `[defaults]` filled in for the settings it doesn't set
+    pub fn with_defaults(&self, playlist: &Playlist) -> Playlist {
+        let defaults = &self.defaults;
+        let mut playlist = playlist.clone();
+
+        playlist.direction = playlist.direction.or(defaults.direction);
+        playlist.mirror = playlist.mirror.or(defaults.mirror);
+        playlist.filter = playlist.filter.or_else(|| defaults.filter.clone());
+        playlist.position = playlist.position.or(defaults.position);
+        playlist.max_adds = playlist.max_adds.or(defaults.max_adds);
+        playlist.write_delay_ms = playlist.write_delay_ms.or(defaults.write_delay_ms);
+        playlist.privacy = playlist.privacy.or_else(|| defaults.privacy.clone());
+        playlist.hooks = playlist.hooks.or_else(|| defaults.hooks.clone());
+        playlist.cache_max_age = playlist
+            .cache_max_age
+            .or_else(|| defaults.cache_max_age.clone());
+
+        playlist
+    }
+
+    /// How old the cached items of the configured playlists may be, from their
+    /// `cache_max_age`. For `sync`, the playlists syncing from sources are always fetched
+    /// again, so the changes are computed from their current items.
+    pub fn cache_max_ages(&self, sync: bool) -> HashMap<String, Option<TimeDelta>> {
+        self.playlists
+            .iter()
+            .map(|playlist| {
+                let max_age = if sync && playlist.sync_from.is_some() {
+                    None
+                } else {
+                    self.with_defaults(playlist)
+                        .cache_max_age
+                        .and_then(|value| crate::schedule::parse_interval(&value).ok())
+                };
+                (playlist.id.clone(), max_age)
+            })
+            .filter(|(_, max_age)| sync || max_age.is_some())
+            .collect()
+    }
+
+    /// The credentials of the music services, for the clients reading music sources
+    pub fn music_services(&self) -> MusicServices {
+        MusicServices {
+            lastfm_api_key: self.lastfm_api_key.clone(),
+            listenbrainz_token: self.listenbrainz_token.clone(),
+            spotify_client_id: self.spotify_client_id.clone(),
+            spotify_client_secret: self.spotify_client_secret.clone(),
+            musicbrainz: self.musicbrainz,
+        }
+    }
+
+    /// Get a setting by its dotted path, e.g. `m3u_dir` or `playlists.chill.tags`
+    pub fn get_value(&self, key: &str) -> Result<toml::Value, Box<dyn std::error::Error>> {
+        let root = toml::Value::try_from(self)?;
+        let mut current = &root;
+
+        for part in key.split('.') {
+            current = match current {
+                toml::Value::Table(table) => table.get(part),
+                toml::Value::Array(items) => {
+                    self.array_index(items, part).and_then(|i| items.get(i))
+                }
+                _ => None,
+            }
+            .ok_or_else(|| format!("'{}' is not set", key))?;
+        }
+
+        Ok(current.clone())
+    }
+
+    /// Change a setting by its dotted path, e.g. `playlists.chill.max_adds`.
+    ///
+    /// The value is read as a TOML value, or as text if it isn't one or the setting
+    /// expects text. Unknown settings and values of the wrong type are errors.
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let parsed = toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut table| table.remove("value"));
+
+        let mut candidates = Vec::new();
+        if let Some(parsed) = parsed.filter(|v| !v.is_str()) {
+            candidates.push(parsed);
+        }
+        candidates.push(toml::Value::String(value.to_string()));
+
+        let mut last_error = None;
+        for candidate in candidates {
+            match self.with_value(key, candidate) {
+                Ok(cfg) => {
+                    *self = cfg;
+                    return Ok(());
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| "Invalid value".into()))
+    }
+
+    /// A copy of the configuration with a setting changed
+    fn with_value(
+        &self,
+        key: &str,
+        value: toml::Value,
+    ) -> Result<Config, Box<dyn std::error::Error>> {
+        self.edited(key, |table, name| {
+            table.insert(name.to_string(), value);
+        })
+    }
+
+    /// A copy of the configuration with a setting back to its default
+    fn without_value(&self, key: &str) -> Result<Config, Box<dyn std::error::Error>> {
+        self.edited(key, |table, name| {
+            table.remove(name);
+        })
+    }
+
+    /// A copy of the configuration with the table holding a setting edited, given the
+    /// name of the setting in it
+    fn edited(
+        &self,
+        key: &str,
+        edit: impl FnOnce(&mut toml::Table, &str),
+    ) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut root = toml::Value::try_from(self)?;
+        let (path, name) = key.rsplit_once('.').unwrap_or(("", key));
+
+        let mut current = &mut root;
+        for part in path.split('.').filter(|part| !part.is_empty()) {
+            let index = match &*current {
+                toml::Value::Array(items) => self.array_index(items, part),
+                _ => None,
+            };
+            current = match current {
+                toml::Value::Table(table) => table
+                    .entry(part)
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new())),
+                toml::Value::Array(items) => index
+                    .and_then(|i| items.get_mut(i))
+                    .ok_or_else(|| format!("No playlist matches '{}'", part))?,
+                _ => return Err(format!("'{}' is not a section", part).into()),
+            };
+        }
+
+        let toml::Value::Table(table) = current else {
+            return Err(format!("'{}' can't be set, set the whole list instead", key).into());
+        };
+        edit(table, name);
+
+        let mut unknown = Vec::new();
+        let mut cfg: Config =
+            serde_ignored::deserialize(root, |field| unknown.push(field.to_string()))
+                .map_err(|e| format!("Invalid value for '{}': {}", key, e))?;
+        if !unknown.is_empty() {
+            return Err(format!("Unknown setting '{}'", key).into());
+        }
+
+        // Where the playlists came from is not part of the serialized configuration
+        for playlist in &mut cfg.playlists {
+            playlist.origin = self
+                .playlists
+                .iter()
+                .find(|p| p.id == playlist.id)
+                .and_then(|p| p.origin.clone());
+        }
+
+        Ok(cfg)
+    }
+
+    /// Index of an item of a list in a dotted path: a number, or a playlist by ID,
+    /// alias or title for the list of playlists
+    fn array_index(&self, items: &[toml::Value], part: &str) -> Option<usize> {
+        if let Ok(index) = part.parse::<usize>() {
+            return (index < items.len()).then_some(index);
+        }
+
+        let playlist = self.find_playlist(part)?;
+        self.playlists.iter().position(|p| p.id == playlist.id)
+    }
+
+    /// Find playlists syncing from each other in a loop.
+    ///
+    /// Returns the IDs along the loop, starting and ending with the same playlist.
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        fn visit<'a>(
+            cfg: &'a Config,
+            id: &'a str,
+            path: &mut Vec<&'a str>,
+            done: &mut HashSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|p| *p == id) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|p| p.to_string()).collect();
+                cycle.push(id.to_string());
+                return Some(cycle);
+            }
+            if done.contains(id) {
+                return None;
+            }
+
+            path.push(id);
+            let sources = cfg
+                .playlists
+                .iter()
+                .filter(|p| p.id == id)
+                .flat_map(|p| p.sync_from.iter().flatten());
+            for source in sources {
+                if let Some(cycle) = visit(cfg, source, path, done) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            done.insert(id);
+
+            None
+        }
+
+        let mut done = HashSet::new();
+        self.playlists
+            .iter()
+            .find_map(|p| visit(self, &p.id, &mut Vec::new(), &mut done))
+    }
+
+    /// Settings holding API keys and tokens, which can be encrypted, by name
+    pub fn secrets_mut(&mut self) -> Vec<(&'static str, &mut Option<String>)> {
+        let mut secrets = vec![
+            ("lastfm_api_key", &mut self.lastfm_api_key),
+            ("listenbrainz_token", &mut self.listenbrainz_token),
+            ("spotify_client_secret", &mut self.spotify_client_secret),
+        ];
+        if let Some(smtp) = self
+            .notifications
+            .email
+            .as_mut()
+            .and_then(|e| e.smtp.as_mut())
+        {
+            secrets.push(("notifications.email.smtp.password", &mut smtp.password));
+        }
+        if let Some(ntfy) = self.notifications.ntfy.as_mut() {
+            secrets.push(("notifications.ntfy.token", &mut ntfy.token));
+        }
+        secrets
+    }
+
+    /// Set the OAuth2 JSON file path for the configuration
+    pub fn set_oauth_path(&mut self, oauth2_json: Option<String>) {
+        self.oauth2_json = oauth2_json;
+    }
+
+    /// Lock the configuration files, see [`ConfigLock`]. Waits for other playsync runs
+    /// to release them.
+    pub fn lock() -> Result<ConfigLock, Box<dyn std::error::Error>> {
+        let path = config_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let file = File::create(path.with_extension("lock"))?;
+        file.lock()?;
+        Ok(ConfigLock { _file: file })
+    }
+
+    /// Lock the configuration files, read them, make a change and write them back. They
+    /// are left as they were if the change fails.
+    pub fn update<T>(
+        change: impl FnOnce(&mut Config) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let lock = Config::lock()?;
+        let mut cfg = Config::read()?;
+        let changed = change(&mut cfg)?;
+        cfg.write(&lock)?;
+
+        Ok(changed)
+    }
+
+    /// Read the configuration from the file, with `PLAYSYNC_*` environment
+    /// variables overriding its values
+    pub fn read() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut cfg = Config::read_files(&config_path()?)?;
+        cfg.apply_env_overrides()
+            .map_err(|e| exit::failure(exit::Code::Config, e))?;
+
+        Ok(cfg)
+    }
+
+    /// Read the configuration file and its included files, as they are written
+    fn read_files(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut cfg: Config = confy::load_path(path)?;
+        cfg.load_includes(path)
+            .map_err(|e| exit::failure(exit::Code::Config, e))?;
+
+        Ok(cfg)
+    }
+
+    /// Add the playlists of the included files.
+    ///
+    /// A playlist defined in two files is an error, since playsync couldn't tell which
+    /// definition to use or where to save changes to it.
+    pub fn load_includes(&mut self, config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        for include in self.include_paths(config_path) {
+            let content = std::fs::read_to_string(&include).map_err(|e| {
+                format!("Failed to read included file {}: {}", include.display(), e)
+            })?;
+            let file: IncludeFile = toml::from_str(&content)
+                .map_err(|e| format!("Invalid included file {}: {}", include.display(), e))?;
+
+            for mut playlist in file.playlists {
+                if let Some(existing) = self.playlists.iter().find(|p| p.id == playlist.id) {
+                    let defined_in = existing
+                        .origin
+                        .as_deref()
+                        .unwrap_or(config_path)
+                        .display()
+                        .to_string();
+                    return Err(format!(
+                        "Playlist {} is defined in both {} and {}",
+                        playlist.id,
+                        defined_in,
+                        include.display()
+                    )
+                    .into());
+                }
+
+                playlist.origin = Some(include.clone());
+                self.playlists.push(playlist);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Paths of the included files
+    fn include_paths(&self, config_path: &Path) -> Vec<PathBuf> {
+        let dir = config_path.parent().unwrap_or(Path::new("."));
+        self.include.iter().map(|path| dir.join(path)).collect()
+    }
+
+    /// Replace settings with the values of their `PLAYSYNC_*` environment variables, e.g.
+    /// `PLAYSYNC_NOTIFICATIONS__NTFY__TOPIC` for `notifications.ntfy.topic`. Variables
+    /// that don't name a setting are errors.
+    pub fn apply_env_overrides(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        for (variable, key, value) in env_overrides() {
+            self.set_value(&key, &value)
+                .map_err(|e| format!("Invalid {}: {}", variable, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the configuration to the file.
+    ///
+    /// Values coming from environment variables are not written: the file keeps its own.
+    /// Playlists of included files are written back to the file they came from.
+    /// The file is replaced atomically, see [`write_atomic`], under the lock taken before
+    /// the configuration was read.
+    pub fn write(&self, lock: &ConfigLock) -> Result<(), Box<dyn std::error::Error>> {
+        let path = config_path()?;
+        let mut written = self.clone();
+
+        let overrides = env_overrides();
+        if !overrides.is_empty() {
+            let file = Config::read_files(&path)?;
+            for (_, key, _) in overrides {
+                // Settings of playlists removed since they were read are gone either way
+                let restored = match file.get_value(&key) {
+                    Ok(value) => written.with_value(&key, value),
+                    Err(_) => written.without_value(&key),
+                };
+                if let Ok(restored) = restored {
+                    written = restored;
+                }
+            }
+        }
+        let mut cfg = written.clone();
+        cfg.playlists.retain(|p| p.origin.is_none());
+
+        write_atomic(&path, &toml::to_string_pretty(&cfg)?, lock)?;
+
+        // Included files are only rewritten when their playlists changed
+        for include in self.include_paths(&path) {
+            let file = IncludeFile {
+                playlists: written
+                    .playlists
+                    .iter()
+                    .filter(|p| p.origin.as_ref() == Some(&include))
+                    .cloned()
+                    .collect(),
+            };
+            let content = toml::to_string_pretty(&file)?;
+
+            let current = std::fs::read_to_string(&include)
+                .ok()
+                .and_then(|c| toml::from_str::<IncludeFile>(&c).ok())
+                .and_then(|f| toml::to_string_pretty(&f).ok());
+            if current.as_ref() != Some(&content) {
+                write_atomic(&include, &content, lock)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Exclusive access to the configuration files, from reading them to writing them back.
+///
+/// An advisory lock on a `.lock` file next to the configuration, taken with
+/// [`Config::lock`] before reading the configuration to change it, so concurrent
+/// playsync runs don't overwrite each other's changes. Released when dropped.
+pub struct ConfigLock {
+    _file: File,
+}
+
+/// Write a configuration file without ever leaving it half-written.
+///
+/// The new content goes to a new temporary file that then replaces the configuration,
+/// so a crash mid-write keeps the previous file intact.
+pub fn write_atomic(
+    path: &Path,
+    content: &str,
+    _lock: &ConfigLock,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    temp_file.write_all(content.as_bytes())?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path)?;
+
+    Ok(())
+}
+
+/// Value of the `PLAYSYNC_<NAME>` environment variable overriding a setting, if set
+fn env_override(name: &str) -> Option<String> {
+    std::env::var(format!("PLAYSYNC_{}", name))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// The settings overridden by `PLAYSYNC_*` environment variables, as the variable, the
+/// dotted path of the setting (see [`Config::set_value`]) and the value.
+///
+/// `__` separates the parts of the path, e.g. `PLAYSYNC_DEFAULTS__MAX_ADDS` for
+/// `defaults.max_adds`. Parts are lowercase, except for the playlists, found by ID,
+/// alias or title, and the `-` of filter settings are written `_`.
+fn env_overrides() -> Vec<(String, String, String)> {
+    let mut overrides: Vec<_> = std::env::vars_os()
+        .filter_map(|(variable, value)| {
+            let variable = variable.into_string().ok()?;
+            let value = value.into_string().ok().filter(|value| !value.is_empty())?;
+            let name = variable.strip_prefix("PLAYSYNC_")?;
+            if NOT_SETTINGS.contains(&name) {
+                return None;
+            }
+            Some((variable.clone(), setting_key(name), value))
+        })
+        .collect();
+    // Applied in the same order on every run
+    overrides.sort();
+    overrides
+}
+
+/// Dotted path of the setting of a `PLAYSYNC_<NAME>` environment variable
+fn setting_key(name: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for part in name.split("__") {
+        let part = match parts.last().map(String::as_str) {
+            Some("playlists") => part.to_string(),
+            Some("filter") => part.to_lowercase().replace('_', "-"),
+            _ => part.to_lowercase(),
+        };
+        parts.push(part);
+    }
+    parts.join(".")
+}
+
+/// The `language` setting, read before the command runs. A missing configuration file
+/// isn't created: only `PLAYSYNC_LANGUAGE` applies then.
+pub fn language() -> Option<String> {
+    if let Some(language) = env_override("LANGUAGE") {
+        return Some(language);
+    }
+    if !config_path().ok()?.exists() {
+        return None;
+    }
+    Config::read().ok()?.language
+}
+
+/// Use another configuration file than the default one for the rest of the run
+pub fn set_config_path(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    // Resolved now, so state files don't move if the working directory changes
+    let _ = CONFIG_PATH.set(std::path::absolute(path)?);
+
+    Ok(())
+}
+
+/// Use a named profile for the rest of the run.
+///
+/// Each profile is stored in its own directory (`playsync-<name>`), so it has its own
+/// credentials, token cache, history and playlists.
+pub fn set_profile(name: String) -> Result<(), Box<dyn std::error::Error>> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid profile name '{}': use only letters, digits, '-' and '_'",
+            name
+        )
+        .into());
+    }
+
+    let _ = PROFILE.set(name);
+
+    Ok(())
+}
+
+/// The profile selected for this run, `None` for the default profile
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Path of the configuration file: the one given on the command line, the one of
+/// the selected profile, or the default one in the system's config directory
+pub fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return Ok(path.clone());
+    }
+
+    let app_name = match PROFILE.get() {
+        Some(profile) => format!("playsync-{}", profile),
+        None => "playsync".to_string(),
+    };
+
+    Ok(confy::get_configuration_file_path(
+        &app_name,
+        Some("playsync"),
+    )?)
+}
+
+/// Number of single-character edits turning a string into another (Levenshtein distance)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Get the directory holding the configuration file, creating it if needed.
+///
+/// Other state files (token cache, history, ...) are stored next to the configuration.
+pub fn config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = config_path()?
+        .parent()
+        .ok_or("Failed to get config directory")?
+        .to_path_buf();
+
+    std::fs::create_dir_all(&dir)?;
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_environment_variables_to_settings() {
+        assert_eq!(setting_key("LASTFM_API_KEY"), "lastfm_api_key");
+        assert_eq!(setting_key("DEFAULTS__MAX_ADDS"), "defaults.max_adds");
+        assert_eq!(
+            setting_key("PLAYLISTS__PLxYz__FILTER__MIN_DURATION"),
+            "playlists.PLxYz.filter.min-duration"
+        );
+    }
+
+    #[test]
+    fn unsets_a_setting() {
+        let mut cfg = Config::default();
+        cfg.add_playlist(Playlist {
+            id: "PLxYz".to_string(),
+            title: "Chill".to_string(),
+            ..Default::default()
+        });
+        cfg.set_value("playlists.chill.max_adds", "5").unwrap();
+        assert_eq!(cfg.playlists[0].max_adds, Some(5));
+
+        let cfg = cfg.without_value("playlists.chill.max_adds").unwrap();
+        assert_eq!(cfg.playlists[0].max_adds, None);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::youtube::VideoInfo;
+use std::collections::HashSet;
+
+/// Unchanged items shown around each change
+const CONTEXT: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Keep,
+    Add,
+    Remove,
+}
+
+struct Line<'a> {
+    change: Change,
+    video: &'a VideoInfo,
+    /// Position in the playlist before the sync, from 1
+    old: Option<usize>,
+    /// Position in the playlist after the sync, from 1
+    new: Option<usize>,
+}
+
+/// Render the changes a sync would make to a playlist like a unified diff: `+` for the
+/// videos added, `-` for the videos removed, with their positions and a few unchanged
+/// neighbors around them. Additions go before the current items with `at_start`.
+pub fn render(
+    title: &str,
+    current: &[VideoInfo],
+    to_add: &[VideoInfo],
+    to_remove: &[VideoInfo],
+    at_start: bool,
+) -> String {
+    let removed: HashSet<&str> = to_remove.iter().map(|v| v.item_id.as_str()).collect();
+    let existing = current.iter().map(|video| {
+        if removed.contains(video.item_id.as_str()) {
+            (Change::Remove, video)
+        } else {
+            (Change::Keep, video)
+        }
+    });
+    let added = to_add.iter().map(|video| (Change::Add, video));
+    let sequence: Vec<(Change, &VideoInfo)> = if at_start {
+        added.chain(existing).collect()
+    } else {
+        existing.chain(added).collect()
+    };
+
+    let (mut old, mut new) = (0, 0);
+    let lines: Vec<Line> = sequence
+        .into_iter()
+        .map(|(change, video)| {
+            let old_position = (change != Change::Add).then(|| {
+                old += 1;
+                old
+            });
+            let new_position = (change != Change::Remove).then(|| {
+                new += 1;
+                new
+            });
+            Line {
+                change,
+                video,
+                old: old_position,
+                new: new_position,
+            }
+        })
+        .collect();
+
+    let mut diff = format!(
+        "--- {} ({} items)\n+++ {} after the sync ({} items)",
+        title, old, title, new
+    );
+    let width = old.max(new).to_string().len();
+
+    for (start, end) in hunks(&lines) {
+        let hunk = &lines[start..end];
+        let old_before = lines[..start].iter().filter(|l| l.old.is_some()).count();
+        let new_before = lines[..start].iter().filter(|l| l.new.is_some()).count();
+        let old_len = hunk.iter().filter(|l| l.old.is_some()).count();
+        let new_len = hunk.iter().filter(|l| l.new.is_some()).count();
+
+        // Like unified diffs, empty ranges start at the line before them
+        diff.push_str(&format!(
+            "\n@@ -{},{} +{},{} @@",
+            old_before + usize::from(old_len > 0),
+            old_len,
+            new_before + usize::from(new_len > 0),
+            new_len
+        ));
+        for line in hunk {
+            let (sign, position) = match line.change {
+                Change::Keep => (' ', line.new),
+                Change::Add => ('+', line.new),
+                Change::Remove => ('-', line.old),
+            };
+            diff.push_str(&format!(
+                "\n{} {:>width$}. {}",
+                sign,
+                position.unwrap_or_default(),
+                line.video.title,
+                width = width
+            ));
+        }
+    }
+
+    diff
+}
+
+/// Ranges of the lines to show: the changes with their context, merged when they overlap
+fn hunks(lines: &[Line]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.change == Change::Keep {
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
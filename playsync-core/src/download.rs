@@ -0,0 +1,141 @@
+use crate::config::Playlist;
+use crate::ui::log;
+use crate::youtube::VideoInfo;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// yt-dlp output template. The video ID in brackets lets files be matched to playlist items.
+const OUTPUT_TEMPLATE: &str = "%(title)s [%(id)s].%(ext)s";
+
+/// Outcome of mirroring a playlist into a local directory
+#[derive(Debug, Default)]
+pub struct DownloadReport {
+    /// Videos downloaded (or that would be downloaded, on a dry run)
+    pub downloaded: usize,
+    /// Files removed because their video left the playlist
+    pub removed: usize,
+    /// Videos yt-dlp failed to download, or files that could not be removed
+    pub failed: usize,
+}
+
+/// Make a directory mirror the given videos: new videos are downloaded with yt-dlp
+/// (which must be installed and in the PATH), and files of videos that are no longer
+/// in the playlist are removed.
+///
+/// Only files named by playsync (ending in `[VIDEO_ID].ext`) are considered, so other
+/// files in the directory are left alone.
+pub async fn mirror(
+    dir: &Path,
+    videos: &[VideoInfo],
+    dry_run: bool,
+) -> Result<DownloadReport, Box<dyn std::error::Error>> {
+    let mut report = DownloadReport::default();
+    let existing = downloaded_files(dir)?;
+    if !dry_run {
+        std::fs::create_dir_all(dir)?;
+    }
+    let wanted: HashSet<&str> = videos.iter().map(|v| v.video_id.as_str()).collect();
+
+    let mut seen = HashSet::new();
+    for video in videos {
+        if existing.contains_key(&video.video_id) || !seen.insert(&video.video_id) {
+            continue;
+        }
+
+        if dry_run {
+            log::info(format!("Would download: {}", video.title))?;
+            report.downloaded += 1;
+            continue;
+        }
+
+        let status = Command::new("yt-dlp")
+            .arg("--quiet")
+            .arg("--no-playlist")
+            .arg("-o")
+            .arg(dir.join(OUTPUT_TEMPLATE))
+            .arg("--")
+            .arg(&video.video_id)
+            .status()
+            .await
+            .map_err(|e| format!("Failed to run yt-dlp (is it installed?): {}", e))?;
+
+        if status.success() {
+            log::info(format!("Downloaded: {}", video.title))?;
+            report.downloaded += 1;
+        } else {
+            log::warning(format!("Failed to download '{}'", video.title))?;
+            report.failed += 1;
+        }
+    }
+
+    for (video_id, files) in &existing {
+        if wanted.contains(video_id.as_str()) {
+            continue;
+        }
+
+        for file in files {
+            if dry_run {
+                log::info(format!("Would remove: {}", file.display()))?;
+                report.removed += 1;
+            } else if let Err(e) = std::fs::remove_file(file) {
+                log::warning(format!("Failed to remove {}: {}", file.display(), e))?;
+                report.failed += 1;
+            } else {
+                log::info(format!("Removed: {}", file.display()))?;
+                report.removed += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Mirror a playlist into its download directory with [`mirror`] and log a summary
+pub async fn mirror_playlist(
+    playlist: &Playlist,
+    dir: &Path,
+    videos: &[VideoInfo],
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = mirror(dir, videos, dry_run).await?;
+
+    log::success(format!(
+        "{}: {} downloaded, {} removed, {} failed",
+        playlist.title, report.downloaded, report.removed, report.failed
+    ))?;
+    Ok(())
+}
+
+/// Find the downloaded files in a directory, by video ID.
+///
+/// Partial downloads (`.part` and `.ytdl` files) are ignored.
+fn downloaded_files(
+    dir: &Path,
+) -> Result<HashMap<String, Vec<PathBuf>>, Box<dyn std::error::Error>> {
+    let mut files: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    let pattern = Regex::new(r"\[([A-Za-z0-9_-]{11})\]\.[A-Za-z0-9]+$")?;
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if name.ends_with(".part") || name.ends_with(".ytdl") {
+            continue;
+        }
+
+        if let Some(captures) = pattern.captures(name) {
+            files.entry(captures[1].to_string()).or_default().push(path);
+        }
+    }
+
+    Ok(files)
+}
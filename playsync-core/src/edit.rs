@@ -0,0 +1,494 @@
+use crate::config::Config;
+use crate::filter::FilterArgs;
+use crate::history::{self, PlaylistRecord};
+use crate::provider::Provider;
+use crate::run::Outcome;
+use crate::sort::{self, SortKey};
+use crate::sync::{self, SyncReport};
+use crate::ui::{self, log};
+use crate::youtube::{self, VideoInfo};
+use crate::{matcher, source};
+use std::collections::HashSet;
+
+/// Merge playlists into a new one, without duplicates, like `playsync merge`.
+///
+/// Returns the ID of the new playlist.
+pub async fn merge(
+    client: &dyn Provider,
+    sources: &[String],
+    title: &str,
+    description: &str,
+    privacy: &str,
+    sort: Option<SortKey>,
+    dry_run: bool,
+) -> Result<Outcome<String>, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start(&format!("Fetching {} playlists", sources.len()));
+
+    let mut seen = HashSet::new();
+    let mut videos = Vec::new();
+    for source_id in sources {
+        for video in client.get_playlist_items(source_id).await? {
+            if seen.insert(video.video_id.clone()) {
+                videos.push(video);
+            }
+        }
+    }
+
+    if let Some(key) = sort {
+        if key.needs_details() {
+            client.enrich_videos(&mut videos).await?;
+        }
+        sort::sort_videos(&mut videos, key);
+    }
+
+    sp.stop(&format!(
+        "Found {} unique videos in {} playlists",
+        videos.len(),
+        sources.len()
+    ));
+
+    if dry_run {
+        log::info(format!(
+            "Would create {} playlist '{}' with {} videos:",
+            privacy,
+            title,
+            videos.len()
+        ))?;
+        for video in &videos {
+            log::info(format!("  - {}", video.title))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let playlist_id = client.create_playlist(title, description, privacy).await?;
+    log::success(format!(
+        "Created playlist '{}' (ID: {})",
+        title, playlist_id
+    ))?;
+
+    let mut report = SyncReport::default();
+    sync::add_videos(client, &playlist_id, videos, &mut report).await?;
+
+    history::record_run(
+        "merge",
+        dry_run,
+        vec![PlaylistRecord::from_report(&playlist_id, title, &report)],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done(playlist_id))
+}
+
+/// Add the videos of `source` missing from `target`, up to `limit`, like `playsync copy`
+pub async fn copy(
+    client: &dyn Provider,
+    source: &str,
+    target: &str,
+    limit: Option<usize>,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Comparing playlists");
+    let target_title = client.get_playlist_title(target).await?;
+    let pending = sync::pending_changes(client, target, &[source.to_string()]).await?;
+    let mut videos = pending.videos_to_add;
+    if let Some(limit) = limit {
+        videos.truncate(limit);
+    }
+    sp.stop(&format!(
+        "Found {} videos to copy to '{}'",
+        videos.len(),
+        target_title
+    ));
+
+    if videos.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        log::info(format!("Would add {} videos:", videos.len()))?;
+        for video in &videos {
+            log::info(format!("  - {}", video.title))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut report = SyncReport {
+        item_count: pending.target_count,
+        ..Default::default()
+    };
+    sync::add_videos(client, target, videos, &mut report).await?;
+
+    history::record_run(
+        "copy",
+        dry_run,
+        vec![PlaylistRecord::from_report(target, &target_title, &report)],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done(()))
+}
+
+/// Move the videos of `source` matching `filter` to `target`, like `playsync move`.
+///
+/// Videos are only removed from `source` once they are in `target`.
+pub async fn move_videos(
+    client: &dyn Provider,
+    source: &str,
+    target: &str,
+    filter: &FilterArgs,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Fetching playlists");
+    let source_title = client.get_playlist_title(source).await?;
+    let target_title = client.get_playlist_title(target).await?;
+    let mut source_videos = client.get_playlist_items(source).await?;
+    if filter.needs_details() {
+        client.enrich_videos(&mut source_videos).await?;
+    }
+    let selected: Vec<VideoInfo> = source_videos
+        .into_iter()
+        .filter(|video| filter.matches(video))
+        .collect();
+    let target_videos = client.get_playlist_items(target).await?;
+    sp.stop(&format!(
+        "Selected {} videos in '{}'",
+        selected.len(),
+        source_title
+    ));
+
+    if selected.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    let mut in_target: HashSet<String> = target_videos
+        .iter()
+        .map(|video| video.video_id.clone())
+        .collect();
+    let mut queued = HashSet::new();
+    let videos_to_add: Vec<VideoInfo> = selected
+        .iter()
+        .filter(|video| {
+            !in_target.contains(&video.video_id) && queued.insert(video.video_id.clone())
+        })
+        .cloned()
+        .collect();
+
+    if dry_run {
+        log::info(format!(
+            "Would move {} videos from '{}' to '{}':",
+            selected.len(),
+            source_title,
+            target_title
+        ))?;
+        for video in &selected {
+            log::info(format!("  - {}", video.title))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut report = SyncReport {
+        item_count: target_videos.len(),
+        ..Default::default()
+    };
+    sync::add_videos(client, target, videos_to_add, &mut report).await?;
+    in_target.extend(report.added.iter().map(|video| video.video_id.clone()));
+
+    // Only remove the videos that are now safely in the target playlist
+    let moved: Vec<VideoInfo> = selected
+        .into_iter()
+        .filter(|video| in_target.contains(&video.video_id))
+        .collect();
+    let mut removal = SyncReport::default();
+    sync::remove_videos(client, source, moved, &mut removal).await?;
+    let source_record = PlaylistRecord {
+        removed: removal.removed.len(),
+        failed: removal.failed.len(),
+        ..PlaylistRecord::new(source, &source_title)
+    };
+
+    history::record_run(
+        "move",
+        dry_run,
+        vec![
+            source_record,
+            PlaylistRecord::from_report(target, &target_title, &report),
+        ],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done(()))
+}
+
+/// Add videos given by URL or ID to a playlist, leaving out the ones already in it, like
+/// `playsync add-video`
+pub async fn add_videos(
+    client: &dyn Provider,
+    playlist_id: &str,
+    videos: &[String],
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let mut video_ids = Vec::new();
+    for input in videos {
+        match youtube::parse_video_id(input) {
+            Some(video_id) if !video_ids.contains(&video_id) => video_ids.push(video_id),
+            Some(_) => {}
+            None => log::warning(format!("Not a video URL or ID: {}", input))?,
+        }
+    }
+
+    let cfg = Config::read().unwrap_or_default();
+    let title = match cfg.playlists.iter().find(|p| p.id == playlist_id) {
+        Some(playlist) => playlist.title.clone(),
+        None => client.get_playlist_title(playlist_id).await?,
+    };
+
+    let sp = ui::spinner();
+    sp.start("Checking videos");
+    let existing = client.get_playlist_items(playlist_id).await?;
+    let existing_ids: HashSet<&str> = existing.iter().map(|v| v.video_id.as_str()).collect();
+
+    let (duplicates, new_ids): (Vec<String>, Vec<String>) = video_ids
+        .into_iter()
+        .partition(|video_id| existing_ids.contains(video_id.as_str()));
+    let found = client.get_videos(&new_ids).await?;
+    sp.stop(&format!("Found {} new videos for '{}'", found.len(), title));
+
+    for video_id in &duplicates {
+        log::info(format!("Already in playlist: {}", video_id))?;
+    }
+    for video_id in new_ids
+        .iter()
+        .filter(|id| !found.iter().any(|v| v.video_id == **id))
+    {
+        log::warning(format!("Video not found: {}", video_id))?;
+    }
+
+    if found.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        log::info(format!("Would add {} videos:", found.len()))?;
+        for video in &found {
+            log::info(format!("  - {}", video.title))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut report = SyncReport {
+        item_count: existing.len(),
+        ..Default::default()
+    };
+    sync::add_videos(client, playlist_id, found, &mut report).await?;
+
+    history::record_run(
+        "add-video",
+        dry_run,
+        vec![PlaylistRecord::from_report(playlist_id, &title, &report)],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done(()))
+}
+
+/// Remove items from a playlist, like `playsync remove-video`: the videos given by URL or
+/// ID, the items matching `filter`, and with `placeholders`, the placeholders of deleted
+/// and private videos
+pub async fn remove_videos(
+    client: &dyn Provider,
+    playlist_id: &str,
+    videos: &[String],
+    filter: &FilterArgs,
+    placeholders: bool,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let mut video_ids = Vec::new();
+    for input in videos {
+        match youtube::parse_video_id(input) {
+            Some(video_id) => video_ids.push(video_id),
+            None => log::warning(format!("Not a video URL or ID: {}", input))?,
+        }
+    }
+
+    let sp = ui::spinner();
+    sp.start("Fetching playlist items");
+    let title = client.get_playlist_title(playlist_id).await?;
+    let mut items = client.get_playlist_items(playlist_id).await?;
+    if filter.needs_details() {
+        client.enrich_videos(&mut items).await?;
+    }
+
+    let selected: Vec<&VideoInfo> = items
+        .iter()
+        .filter(|video| {
+            if video_ids.contains(&video.video_id) {
+                true
+            } else if placeholders {
+                // Only the title filter applies to placeholders, which have no channel or date
+                video.is_placeholder()
+                    && filter
+                        .title
+                        .as_ref()
+                        .is_none_or(|title| title.is_match(&video.title))
+            } else {
+                !filter.is_empty() && filter.matches(video)
+            }
+        })
+        .collect();
+    sp.stop(&format!("Selected {} items in '{}'", selected.len(), title));
+
+    if selected.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        log::info(format!("Would remove {} items:", selected.len()))?;
+        for video in &selected {
+            log::info(format!("  - {}", video.title))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut report = SyncReport {
+        item_count: items.len(),
+        ..Default::default()
+    };
+    sync::remove_videos(
+        client,
+        playlist_id,
+        selected.into_iter().cloned().collect(),
+        &mut report,
+    )
+    .await?;
+
+    history::record_run(
+        "remove-video",
+        dry_run,
+        vec![PlaylistRecord::from_report(playlist_id, &title, &report)],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done(()))
+}
+
+/// Copy a source into a YouTube playlist, like `playsync convert`: `to` is a playlist ID,
+/// or `new` to create a playlist titled `title`, or after the source. Tracks of music
+/// services are matched to videos, searching at most `max_lookups` of them.
+///
+/// Returns the ID and title of the playlist.
+pub async fn convert(
+    client: &dyn Provider,
+    from: &str,
+    to: &str,
+    title: Option<String>,
+    privacy: &str,
+    max_lookups: usize,
+    dry_run: bool,
+) -> Result<Outcome<(String, String)>, Box<dyn std::error::Error>> {
+    let source = source::Source::parse(from.strip_prefix("youtube:").unwrap_or(from));
+    let target = to.strip_prefix("youtube:").unwrap_or(to);
+
+    let sp = ui::spinner();
+    sp.start(&format!("Fetching {}", source.describe()));
+
+    let videos = if source.is_music() {
+        let cfg = Config::read()?;
+        let tracks = source::fetch_tracks(&source, &cfg.music_services()).await?;
+        sp.set_message(&format!("Matching {} tracks on YouTube", tracks.len()));
+        let report = matcher::match_tracks(client, &tracks, cfg.musicbrainz, max_lookups).await?;
+        sp.stop(&format!(
+            "Matched {} of {} tracks",
+            report.matched.len(),
+            tracks.len()
+        ));
+
+        let mut body = String::new();
+        for (track, video) in &report.matched {
+            body.push_str(&format!(
+                "✓ {} - {} → {}\n",
+                track.artist, track.title, video.title
+            ));
+        }
+        for track in &report.unmatched {
+            body.push_str(&format!(
+                "✗ {} - {} (no match)\n",
+                track.artist, track.title
+            ));
+        }
+        if !report.deferred.is_empty() {
+            body.push_str(&format!(
+                "… {} tracks not searched, raise --max-lookups to include them\n",
+                report.deferred.len()
+            ));
+        }
+        ui::note("Match report", body.trim_end())?;
+
+        report.videos()
+    } else {
+        let videos = source::fetch_videos(client, &source).await?;
+        sp.stop(&format!("Found {} videos", videos.len()));
+        videos
+    };
+
+    // Skip videos already in the target, and videos matched by several tracks
+    let mut seen = HashSet::new();
+    let mut item_count = 0;
+    let target_title = if target == "new" {
+        title.unwrap_or_else(|| source.describe())
+    } else {
+        let items = client.get_playlist_items(target).await?;
+        item_count = items.len();
+        seen.extend(items.into_iter().map(|video| video.video_id));
+        client.get_playlist_title(target).await?
+    };
+    let videos: Vec<VideoInfo> = videos
+        .into_iter()
+        .filter(|video| seen.insert(video.video_id.clone()))
+        .collect();
+
+    if videos.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        log::info(format!(
+            "Would add {} videos to '{}'",
+            videos.len(),
+            target_title
+        ))?;
+        return Ok(Outcome::Previewed);
+    }
+
+    let target_id = if target == "new" {
+        let playlist_id = client.create_playlist(&target_title, "", privacy).await?;
+        log::success(format!(
+            "Created playlist '{}' (ID: {})",
+            target_title, playlist_id
+        ))?;
+        playlist_id
+    } else {
+        target.to_string()
+    };
+
+    let mut report = SyncReport {
+        item_count,
+        ..Default::default()
+    };
+    sync::add_videos(client, &target_id, videos, &mut report).await?;
+
+    history::record_run(
+        "convert",
+        dry_run,
+        vec![PlaylistRecord::from_report(
+            &target_id,
+            &target_title,
+            &report,
+        )],
+        client.quota_used(),
+    )?;
+
+    Ok(Outcome::Done((target_id, target_title)))
+}
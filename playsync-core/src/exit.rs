@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Exit codes of playsync, so scripts can tell failures apart without parsing messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Code {
+    Ok = 0,
+    /// Any other error
+    Error = 1,
+    /// The run went through, but some videos could not be added or removed
+    Partial = 2,
+    /// Signing in to YouTube failed, or the API refused the credentials
+    Auth = 3,
+    /// The YouTube API quota is exhausted
+    Quota = 4,
+    /// The configuration can't be read or is invalid
+    Config = 5,
+    /// A service could not be reached
+    Network = 6,
+    /// The command line is invalid, like `EX_USAGE` of sysexits
+    Usage = 64,
+}
+
+/// Exit code of a run that went through, raised when videos fail
+static OUTCOME: AtomicU8 = AtomicU8::new(Code::Ok as u8);
+
+/// Remember that a video could not be added or removed, so playsync exits with
+/// [`Code::Partial`], or with [`Code::Quota`] if the quota ran out
+pub fn mark_failed_item(error: &(dyn Error + 'static)) {
+    let code = match code_for(error) {
+        Code::Quota => Code::Quota,
+        _ => Code::Partial,
+    };
+    OUTCOME.fetch_max(code as u8, Ordering::Relaxed);
+}
+
+/// An error with the exit code it should end playsync with
+pub struct Failure {
+    code: Code,
+    message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// Printed like the plain string errors when main returns
+impl fmt::Debug for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.message)
+    }
+}
+
+impl Error for Failure {}
+
+/// Turn an error into one ending playsync with the given code
+pub fn failure(code: Code, error: impl fmt::Display) -> Box<dyn Error> {
+    Box::new(Failure {
+        code,
+        message: error.to_string(),
+    })
+}
+
+/// The exit code of a run that didn't end with an error
+pub fn success() -> u8 {
+    OUTCOME.load(Ordering::Relaxed)
+}
+
+/// The exit code of a run that ended with an error
+pub fn code_for(error: &(dyn Error + 'static)) -> Code {
+    if let Some(failure) = error.downcast_ref::<Failure>() {
+        return failure.code;
+    }
+    if let Some(error) = error.downcast_ref::<google_youtube3::Error>() {
+        return api_code(error);
+    }
+    if error.downcast_ref::<toml::de::Error>().is_some()
+        || error.downcast_ref::<confy::ConfyError>().is_some()
+    {
+        return Code::Config;
+    }
+    if error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+    {
+        return Code::Network;
+    }
+
+    Code::Error
+}
+
+/// Reasons the API gives when the quota is exhausted
+const QUOTA_REASONS: &[&str] = &["quotaExceeded", "dailyLimitExceeded", "rateLimitExceeded"];
+
+fn api_code(error: &google_youtube3::Error) -> Code {
+    match error {
+        google_youtube3::Error::MissingToken(_) => Code::Auth,
+        google_youtube3::Error::HttpError(_) => Code::Network,
+        google_youtube3::Error::BadRequest(body) => {
+            let reasons = body["error"]["errors"].as_array().into_iter().flatten();
+            if reasons
+                .filter_map(|e| e["reason"].as_str())
+                .any(|reason| QUOTA_REASONS.contains(&reason))
+            {
+                Code::Quota
+            } else if body["error"]["code"].as_u64() == Some(401) {
+                Code::Auth
+            } else {
+                Code::Error
+            }
+        }
+        google_youtube3::Error::Failure(response) if response.status().as_u16() == 401 => {
+            Code::Auth
+        }
+        _ => Code::Error,
+    }
+}
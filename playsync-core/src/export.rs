@@ -0,0 +1,78 @@
+use crate::youtube::VideoInfo;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Characters that are not allowed in file names on at least one platform
+const RESERVED_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Extractor name yt-dlp uses for YouTube videos in download archives
+const ARCHIVE_EXTRACTOR: &str = "youtube";
+
+/// Update a yt-dlp download archive so its YouTube entries are exactly the given videos.
+///
+/// Entries of other extractors are kept as they are, so one archive can be shared
+/// with downloads from other sites.
+pub fn write_download_archive(
+    path: &Path,
+    videos: &[VideoInfo],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| {
+            !line.trim().is_empty() && line.split_whitespace().next() != Some(ARCHIVE_EXTRACTOR)
+        })
+        .map(str::to_string)
+        .collect();
+
+    let mut seen = HashSet::new();
+    for video in videos {
+        if seen.insert(&video.video_id) {
+            lines.push(format!("{} {}", ARCHIVE_EXTRACTOR, video.video_id));
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, lines.join("\n") + "\n")?;
+
+    Ok(())
+}
+
+/// Write an extended M3U playlist of the videos' watch URLs into `dir`, named after
+/// the playlist title, so players like mpv or VLC can play it directly.
+pub fn write_m3u(
+    dir: &Path,
+    title: &str,
+    videos: &[VideoInfo],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut content = String::from("#EXTM3U\n");
+    content.push_str(&format!("#PLAYLIST:{}\n", title));
+
+    for video in videos {
+        let seconds = video
+            .duration()
+            .map_or(-1, |duration| duration.as_secs() as i64);
+        content.push_str(&format!(
+            "#EXTINF:{},{}\nhttps://www.youtube.com/watch?v={}\n",
+            seconds, video.title, video.video_id
+        ));
+    }
+
+    let file_name: String = title
+        .chars()
+        .map(|c| if RESERVED_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    let path = dir.join(format!("{}.m3u", file_name.trim()));
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, content)?;
+
+    Ok(path)
+}
@@ -0,0 +1,245 @@
+use crate::sync::{SkipCode, SkipReason};
+use crate::youtube::VideoInfo;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::Duration;
+
+/// Options for selecting a subset of playlist items.
+///
+/// Also used as the `filter` of a configured playlist, with the same names as the flags.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct FilterArgs {
+    /// Only select videos whose title matches this regular expression
+    #[cfg_attr(
+        feature = "clap",
+        clap(short = 'm', long = "match", value_name = "REGEX")
+    )]
+    #[serde(
+        rename = "match",
+        with = "regex_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub title: Option<Regex>,
+
+    /// Only select videos from this channel (case-insensitive)
+    #[cfg_attr(feature = "clap", clap(short = 'c', long, value_name = "CHANNEL"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+
+    /// Only select videos published on or after this date (YYYY-MM-DD)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "DATE"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<NaiveDate>,
+
+    /// Only select videos published before this date (YYYY-MM-DD)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "DATE"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<NaiveDate>,
+
+    /// Only select videos at least this long (SECONDS, MM:SS or HH:MM:SS)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "LENGTH", value_parser = parse_length))]
+    #[serde(with = "length_option", skip_serializing_if = "Option::is_none")]
+    pub min_duration: Option<Duration>,
+
+    /// Only select videos at most this long (SECONDS, MM:SS or HH:MM:SS)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "LENGTH", value_parser = parse_length))]
+    #[serde(with = "length_option", skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<Duration>,
+
+    /// Leave out upcoming and ongoing live streams
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub no_live: bool,
+}
+
+impl FilterArgs {
+    /// Whether no filter was given, i.e. every video is selected
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.channel.is_none()
+            && self.after.is_none()
+            && self.before.is_none()
+            && !self.needs_details()
+    }
+
+    /// Whether the filters need the video details (see `YouTubeClient::enrich_videos`)
+    pub fn needs_details(&self) -> bool {
+        self.min_duration.is_some() || self.max_duration.is_some() || self.no_live
+    }
+
+    /// Whether a video passes all the given filters
+    pub fn matches(&self, video: &VideoInfo) -> bool {
+        self.rejection(video).is_none()
+    }
+
+    /// Why a video doesn't pass the filters, or `None` if it does
+    pub fn rejection(&self, video: &VideoInfo) -> Option<SkipReason> {
+        if let Some(title) = &self.title
+            && !title.is_match(&video.title)
+        {
+            return Some(SkipReason::new(
+                SkipCode::FilteredTitle,
+                format!("the title doesn't match `{}`", title),
+            ));
+        }
+
+        if let Some(channel) = &self.channel {
+            let matches_channel = video
+                .channel
+                .as_ref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(channel));
+            if !matches_channel {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredChannel,
+                    format!("not from the channel '{}'", channel),
+                ));
+            }
+        }
+
+        if self.after.is_some() || self.before.is_some() {
+            // Videos without a publish date (e.g. private videos) can't be placed in a range
+            let Some(published) = video.published_at.map(|date| date.date_naive()) else {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDate,
+                    "the publish date is unknown",
+                ));
+            };
+
+            if let Some(after) = self.after.filter(|&after| published < after) {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDate,
+                    format!("published on {}, before {}", published, after),
+                ));
+            }
+
+            if let Some(before) = self.before.filter(|&before| published >= before) {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDate,
+                    format!("published on {}, not before {}", published, before),
+                ));
+            }
+        }
+
+        if self.min_duration.is_some() || self.max_duration.is_some() {
+            let Some(duration) = video.duration() else {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDuration,
+                    "the duration is unknown",
+                ));
+            };
+
+            if let Some(min) = self.min_duration.filter(|&min| duration < min) {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDuration,
+                    format!(
+                        "{} long, shorter than {}",
+                        format_length(duration),
+                        format_length(min)
+                    ),
+                ));
+            }
+
+            if let Some(max) = self.max_duration.filter(|&max| duration > max) {
+                return Some(SkipReason::new(
+                    SkipCode::FilteredDuration,
+                    format!(
+                        "{} long, longer than {}",
+                        format_length(duration),
+                        format_length(max)
+                    ),
+                ));
+            }
+        }
+
+        if self.no_live && video.is_live() {
+            return Some(SkipReason::new(
+                SkipCode::Live,
+                "a live stream or upcoming premiere",
+            ));
+        }
+
+        None
+    }
+}
+
+/// Parse a video length given as seconds, `MM:SS` or `HH:MM:SS`
+fn parse_length(value: &str) -> Result<Duration, String> {
+    let mut seconds = 0u64;
+
+    for part in value.split(':') {
+        let amount: u64 = part.trim().parse().map_err(|_| {
+            format!(
+                "invalid length '{}', expected e.g. 90, 4:30 or 1:02:00",
+                value
+            )
+        })?;
+        seconds = seconds * 60 + amount;
+    }
+
+    if value.split(':').count() > 3 {
+        return Err(format!(
+            "invalid length '{}', expected at most HH:MM:SS",
+            value
+        ));
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Format a video length as `MM:SS` or `HH:MM:SS`, the way [`parse_length`] reads it
+fn format_length(length: Duration) -> String {
+    let seconds = length.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Store a title filter in the configuration as the text of the regular expression
+mod regex_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Regex>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(Regex::as_str).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Regex>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|pattern| Regex::new(&pattern).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Store a length filter in the configuration as `MM:SS` or `HH:MM:SS`
+mod length_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(format_length).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|length| parse_length(&length).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::config::{Config, Playlist};
+use crate::source::Source;
+use std::collections::HashMap;
+
+/// Format of `playsync graph`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum GraphFormat {
+    /// Graphviz, e.g. `playsync graph | dot -Tsvg > graph.svg`
+    Dot,
+    /// Mermaid flowchart, rendered by GitHub and GitLab in Markdown files
+    Mermaid,
+}
+
+/// Depth of each configured playlist in the sync graph, by ID: 0 for playlists without
+/// configured playlists as sources, one more than their deepest source otherwise.
+/// Playlists in a loop get the depth they have where the loop is found.
+pub fn depths(cfg: &Config) -> HashMap<&str, usize> {
+    fn visit<'a>(
+        cfg: &'a Config,
+        playlist: &'a Playlist,
+        depths: &mut HashMap<&'a str, usize>,
+        visiting: &mut Vec<&'a str>,
+    ) -> usize {
+        if let Some(depth) = depths.get(playlist.id.as_str()) {
+            return *depth;
+        }
+        if visiting.contains(&playlist.id.as_str()) {
+            return 0;
+        }
+
+        visiting.push(&playlist.id);
+        let depth = playlist
+            .sync_from
+            .iter()
+            .flatten()
+            .filter_map(|source| cfg.playlists.iter().find(|p| p.id == *source))
+            .map(|source| visit(cfg, source, depths, visiting) + 1)
+            .max()
+            .unwrap_or_default();
+        visiting.pop();
+
+        depths.insert(&playlist.id, depth);
+        depth
+    }
+
+    let mut depths = HashMap::new();
+    for playlist in &cfg.playlists {
+        visit(cfg, playlist, &mut depths, &mut Vec::new());
+    }
+    depths
+}
+
+/// The configured playlists in the order videos flow through them: sources before the
+/// playlists syncing from them, then in the order of the configuration
+pub fn flow_order(cfg: &Config) -> Vec<&Playlist> {
+    let depths = depths(cfg);
+    let mut playlists: Vec<&Playlist> = cfg.playlists.iter().collect();
+    // Stable, so playlists of the same depth keep their order
+    playlists.sort_by_key(|playlist| depths.get(playlist.id.as_str()).copied());
+    playlists
+}
+
+/// Whether `target` syncing from `source` is part of a loop found by
+/// [`Config::find_cycle`], which lists each playlist before its source
+pub fn in_cycle(cycle: &[String], target: &str, source: &str) -> bool {
+    cycle
+        .windows(2)
+        .any(|pair| pair[0] == target && pair[1] == source)
+}
+
+/// A node of the exported graph: a configured playlist or another source
+struct Node {
+    key: String,
+    label: String,
+    configured: bool,
+}
+
+/// The nodes and edges of the sync graph, in the order videos flow.
+/// Edges go from a source to the playlist syncing from it, by node index, and tell
+/// whether they are part of a loop.
+fn nodes_and_edges(cfg: &Config) -> (Vec<Node>, Vec<(usize, usize, bool)>) {
+    let playlists = flow_order(cfg);
+    let mut nodes: Vec<Node> = playlists
+        .iter()
+        .map(|playlist| Node {
+            key: playlist.id.clone(),
+            label: playlist.title.clone(),
+            configured: true,
+        })
+        .collect();
+
+    let cycle = cfg.find_cycle().unwrap_or_default();
+    let mut edges = Vec::new();
+    for (target, playlist) in playlists.iter().enumerate() {
+        for source in playlist.sync_from.iter().flatten() {
+            let from = match nodes.iter().position(|node| node.key == *source) {
+                Some(index) => index,
+                None => {
+                    nodes.push(Node {
+                        key: source.clone(),
+                        label: Source::parse(source).describe(),
+                        configured: false,
+                    });
+                    nodes.len() - 1
+                }
+            };
+            edges.push((from, target, in_cycle(&cycle, &playlist.id, source)));
+        }
+    }
+
+    (nodes, edges)
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the sync graph as Graphviz DOT. Sources that aren't configured playlists
+/// are dashed, edges of a loop are red.
+pub fn to_dot(cfg: &Config) -> String {
+    let (nodes, edges) = nodes_and_edges(cfg);
+    let mut dot = String::from("digraph playsync {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    for node in &nodes {
+        dot.push_str(&format!(
+            "    {} [label={}{}];\n",
+            dot_string(&node.key),
+            dot_string(&node.label),
+            if node.configured {
+                ""
+            } else {
+                ", style=dashed"
+            }
+        ));
+    }
+    for (from, to, looped) in edges {
+        dot.push_str(&format!(
+            "    {} -> {}{};\n",
+            dot_string(&nodes[from].key),
+            dot_string(&nodes[to].key),
+            if looped { " [color=red]" } else { "" }
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Render the sync graph as a Mermaid flowchart. Nodes are numbered since IDs and
+/// URLs aren't valid Mermaid identifiers; sources that aren't configured playlists
+/// are rounded, edges of a loop are red.
+pub fn to_mermaid(cfg: &Config) -> String {
+    let (nodes, edges) = nodes_and_edges(cfg);
+    let mut mermaid = String::from("flowchart LR\n");
+
+    for (index, node) in nodes.iter().enumerate() {
+        let label = node.label.replace('"', "#quot;");
+        if node.configured {
+            mermaid.push_str(&format!("    n{}[\"{}\"]\n", index, label));
+        } else {
+            mermaid.push_str(&format!("    n{}([\"{}\"])\n", index, label));
+        }
+    }
+
+    let mut looped = Vec::new();
+    for (position, (from, to, in_loop)) in edges.into_iter().enumerate() {
+        mermaid.push_str(&format!("    n{} --> n{}\n", from, to));
+        if in_loop {
+            looped.push(position.to_string());
+        }
+    }
+    if !looped.is_empty() {
+        mermaid.push_str(&format!("    linkStyle {} stroke:red\n", looped.join(",")));
+    }
+
+    mermaid
+}
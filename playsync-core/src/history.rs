@@ -0,0 +1,262 @@
+use crate::sync::SyncReport;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+pub struct HistoryArgs {
+    /// Only show runs that touched this playlist
+    #[cfg_attr(
+        feature = "clap",
+        clap(short = 'i', long = "id", value_name = "PLAYLIST_ID")
+    )]
+    pub playlist_id: Option<String>,
+
+    /// Only show runs on or after this date (YYYY-MM-DD)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "DATE"))]
+    pub since: Option<NaiveDate>,
+
+    /// Only show runs on or before this date (YYYY-MM-DD)
+    #[cfg_attr(feature = "clap", clap(long, value_name = "DATE"))]
+    pub until: Option<NaiveDate>,
+
+    /// Only show runs that had failures or errors
+    #[cfg_attr(feature = "clap", clap(long))]
+    pub failed: bool,
+
+    /// Maximum number of runs to show
+    #[cfg_attr(feature = "clap", clap(short = 'n', long, default_value_t = 20))]
+    pub limit: usize,
+}
+
+/// A single run of a playsync command, as stored in the history log
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryEntry {
+    /// When the run started
+    pub timestamp: DateTime<Utc>,
+
+    /// The command that was run (e.g. "sync")
+    pub command: String,
+
+    /// Whether the run was a dry run
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Per-playlist results of the run
+    #[serde(default)]
+    pub playlists: Vec<PlaylistRecord>,
+
+    /// Estimated API quota units used by the run
+    #[serde(default)]
+    pub quota_used: u64,
+
+    /// The error that aborted the run, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The result of a run for a single playlist
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaylistRecord {
+    /// The ID of the playlist
+    pub id: String,
+
+    /// The title of the playlist
+    pub title: String,
+
+    /// Number of items added to the playlist
+    #[serde(default)]
+    pub added: usize,
+
+    /// Number of items removed from the playlist
+    #[serde(default)]
+    pub removed: usize,
+
+    /// Number of items that failed to be added or removed
+    #[serde(default)]
+    pub failed: usize,
+
+    /// Number of videos of the sources left out by filters, scripts, plugins or `max_adds`
+    #[serde(default)]
+    pub skipped: usize,
+
+    /// Number of items in the playlist at the end of the run, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_count: Option<usize>,
+}
+
+impl HistoryEntry {
+    /// Start a new history entry for the given command, timestamped now
+    pub fn new(command: &str, dry_run: bool) -> Self {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            command: command.to_string(),
+            dry_run,
+            playlists: Vec::new(),
+            quota_used: 0,
+            error: None,
+        }
+    }
+
+    /// Whether the run aborted or had items that failed
+    pub fn has_failures(&self) -> bool {
+        self.error.is_some() || self.playlists.iter().any(|p| p.failed > 0)
+    }
+
+    /// A single line summing up the run, e.g.
+    /// `playlists=4 added=37 removed=0 skipped=12 failed=1 quota=2150 duration=41s`,
+    /// for cron mail filters and log searches
+    pub fn summary_line(&self, duration: Duration) -> String {
+        let total = |count: fn(&PlaylistRecord) -> usize| -> usize {
+            self.playlists.iter().map(count).sum()
+        };
+
+        let mut line = format!(
+            "playlists={} added={} removed={} skipped={} failed={} quota={} duration={}s",
+            self.playlists.len(),
+            total(|p| p.added),
+            total(|p| p.removed),
+            total(|p| p.skipped),
+            total(|p| p.failed),
+            self.quota_used,
+            duration.as_secs()
+        );
+        if self.dry_run {
+            line.push_str(" dry_run=true");
+        }
+        if self.error.is_some() {
+            line.push_str(" error=true");
+        }
+        line
+    }
+}
+
+impl PlaylistRecord {
+    pub fn new(id: &str, title: &str) -> Self {
+        PlaylistRecord {
+            id: id.to_string(),
+            title: title.to_string(),
+            added: 0,
+            removed: 0,
+            failed: 0,
+            skipped: 0,
+            item_count: None,
+        }
+    }
+
+    /// The record of the changes an engine function made to a playlist
+    pub fn from_report(id: &str, title: &str, report: &SyncReport) -> Self {
+        PlaylistRecord {
+            added: report.added.len(),
+            removed: report.removed.len(),
+            failed: report.failed.len(),
+            item_count: Some(report.item_count),
+            ..PlaylistRecord::new(id, title)
+        }
+    }
+}
+
+/// Path of the history log, stored next to the configuration file
+pub fn history_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(crate::config::config_dir()?.join("history.jsonl"))
+}
+
+/// Append an entry to the history log
+pub fn record(entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path()?)?;
+
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+    Ok(())
+}
+
+/// Record a run of a command other than `sync`, with the changes it made to each
+/// playlist and the quota it used
+pub fn record_run(
+    command: &str,
+    dry_run: bool,
+    playlists: Vec<PlaylistRecord>,
+    quota_used: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut entry = HistoryEntry::new(command, dry_run);
+    entry.playlists = playlists;
+    entry.quota_used = quota_used;
+    record(&entry)
+}
+
+/// Read all entries from the history log, oldest first.
+///
+/// Lines that cannot be parsed are skipped, so a single corrupted line
+/// does not make the whole history unreadable.
+pub fn read_all() -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut entries = Vec::new();
+
+    for line in reader.lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Find the most recently recorded item count of a playlist
+pub fn last_item_count(entries: &[HistoryEntry], playlist_id: &str) -> Option<usize> {
+    entries
+        .iter()
+        .rev()
+        .flat_map(|entry| entry.playlists.iter())
+        .find(|p| p.id == playlist_id && p.item_count.is_some())
+        .and_then(|p| p.item_count)
+}
+
+/// Find the last sync of a playlist that wasn't a dry run, with its result
+pub fn last_sync<'a>(
+    entries: &'a [HistoryEntry],
+    playlist_id: &str,
+) -> Option<(&'a HistoryEntry, &'a PlaylistRecord)> {
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.command == "sync" && !entry.dry_run)
+        .find_map(|entry| {
+            let record = entry.playlists.iter().find(|p| p.id == playlist_id)?;
+            Some((entry, record))
+        })
+}
+
+/// Whether an entry matches the filters given on the command line
+pub fn matches(entry: &HistoryEntry, args: &HistoryArgs) -> bool {
+    let date = entry.timestamp.with_timezone(&Local).date_naive();
+
+    if args.since.is_some_and(|since| date < since) {
+        return false;
+    }
+
+    if args.until.is_some_and(|until| date > until) {
+        return false;
+    }
+
+    if args.failed && !entry.has_failures() {
+        return false;
+    }
+
+    if let Some(id) = &args.playlist_id {
+        return entry.playlists.iter().any(|p| p.id == *id);
+    }
+
+    true
+}
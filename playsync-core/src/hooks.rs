@@ -0,0 +1,174 @@
+use crate::config::Playlist;
+use crate::report::{self, PlaylistReport};
+use crate::sync::SyncReport;
+use crate::youtube::VideoInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Shell commands run around the sync of a playlist, in a `hooks` table
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Hooks {
+    /// Run before the playlist is synced. If it fails, the playlist is skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sync: Option<String>,
+
+    /// Run after the playlist was synced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_sync: Option<String>,
+
+    /// Run when the sync of the playlist errors out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+}
+
+/// What a hook is told about the sync of its playlist
+pub enum Outcome<'a> {
+    /// The sync didn't start yet
+    Pending,
+    Synced(&'a SyncReport),
+    Failed(&'a str),
+}
+
+/// Path of the report of the last sync of a playlist, next to the configuration
+fn report_path(playlist_id: &str, extension: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = crate::config::config_dir()?.join("reports");
+    std::fs::create_dir_all(&dir)?;
+
+    // Placeholder IDs like `new:"Chill Mix"` aren't valid file names
+    let name: String = playlist_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(dir.join(format!("{}.{}", name, extension)))
+}
+
+fn video_json(video: &VideoInfo) -> Value {
+    json!({
+        "video_id": video.video_id,
+        "title": video.title,
+        "channel": video.channel,
+    })
+}
+
+/// Write the report of a finished sync for the hooks to read, returning its path
+fn write_report(
+    playlist: &Playlist,
+    outcome: &Outcome,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut report = json!({
+        "id": playlist.id,
+        "title": playlist.title,
+    });
+    match outcome {
+        Outcome::Pending => {}
+        Outcome::Synced(sync) => {
+            report["added"] = sync.added.iter().map(video_json).collect();
+            report["removed"] = sync.removed.iter().map(video_json).collect();
+            report["failed"] = sync
+                .failed
+                .iter()
+                .map(|(video, error)| {
+                    let mut failed = video_json(video);
+                    failed["error"] = Value::String(error.clone());
+                    failed
+                })
+                .collect();
+            report["item_count"] = sync.item_count.into();
+        }
+        Outcome::Failed(error) => report["error"] = Value::String(error.to_string()),
+    }
+
+    let path = report_path(&playlist.id, "json")?;
+    std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    Ok(path)
+}
+
+/// Write the report of a finished sync as Markdown, for hooks posting it to GitHub issues
+/// or chat tools, returning its path
+fn write_markdown(
+    playlist: &Playlist,
+    outcome: &Outcome,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    // Playlist sections don't show the quota and the duration
+    let report = match outcome {
+        Outcome::Pending => return Err("The sync didn't end yet".into()),
+        Outcome::Synced(sync) => {
+            PlaylistReport::synced(&playlist.id, &playlist.title, sync, 0, Duration::ZERO)
+        }
+        Outcome::Failed(error) => {
+            PlaylistReport::failed(&playlist.id, &playlist.title, error, 0, Duration::ZERO)
+        }
+    };
+
+    let path = report_path(&playlist.id, "md")?;
+    std::fs::write(&path, report::playlist_markdown(&report))?;
+    Ok(path)
+}
+
+/// Run a hook command with the shell.
+///
+/// It gets the playlist in `PLAYSYNC_PLAYLIST_ID` and `PLAYSYNC_PLAYLIST_TITLE`, and
+/// once the sync ended, the paths of its JSON and Markdown reports in `PLAYSYNC_REPORT`
+/// and `PLAYSYNC_REPORT_MARKDOWN`. After a sync,
+/// `PLAYSYNC_ADDED`, `PLAYSYNC_REMOVED`, `PLAYSYNC_FAILED` and `PLAYSYNC_ITEM_COUNT`
+/// hold its counts, and after a failure, `PLAYSYNC_ERROR` holds the error.
+pub async fn run(
+    command: &str,
+    playlist: &Playlist,
+    outcome: Outcome<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("PLAYSYNC_PLAYLIST_ID", &playlist.id)
+        .env("PLAYSYNC_PLAYLIST_TITLE", &playlist.title);
+
+    if !matches!(outcome, Outcome::Pending) {
+        shell
+            .env("PLAYSYNC_REPORT", write_report(playlist, &outcome)?)
+            .env(
+                "PLAYSYNC_REPORT_MARKDOWN",
+                write_markdown(playlist, &outcome)?,
+            );
+    }
+    match outcome {
+        Outcome::Pending => {}
+        Outcome::Synced(report) => {
+            shell
+                .env("PLAYSYNC_ADDED", report.added.len().to_string())
+                .env("PLAYSYNC_REMOVED", report.removed.len().to_string())
+                .env("PLAYSYNC_FAILED", report.failed.len().to_string())
+                .env("PLAYSYNC_ITEM_COUNT", report.item_count.to_string());
+        }
+        Outcome::Failed(error) => {
+            shell.env("PLAYSYNC_ERROR", error);
+        }
+    }
+
+    let status = shell
+        .status()
+        .await
+        .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+    if !status.success() {
+        return Err(format!("`{}` exited with {}", command, status).into());
+    }
+
+    Ok(())
+}
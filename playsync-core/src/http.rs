@@ -0,0 +1,180 @@
+use crate::ui::log;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request body accepted
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Longest request line or header line accepted
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most headers accepted in a request
+const MAX_HEADERS: usize = 100;
+
+/// How long a client has to send its whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request received by one of playsync's HTTP listeners.
+///
+/// playsync only needs a handful of endpoints, so this is a minimal HTTP/1.1 server:
+/// one request per connection, with a `Content-Length` body.
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Answer the connections of `listener` with `handle`, forever. Each connection is read
+/// and answered on its own task, so a slow client doesn't keep the others waiting, and
+/// requests that can't be read get `400 Bad Request`.
+pub async fn serve<H, F>(listener: TcpListener, handle: H)
+where
+    H: Fn(Request, TcpStream) -> F + Send + Sync + 'static,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let handle = Arc::new(handle);
+    loop {
+        // Failures like running out of file descriptors pass, the listener keeps going
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                let _ = log::warning(format!("Failed to accept a connection: {}", e));
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            // The error is dropped right away, it can't be held across the next `.await`
+            let Some(request) = read_request(&mut stream).await.ok() else {
+                let _ = respond(&mut stream, 400, "text/plain", b"Bad request").await;
+                return;
+            };
+            handle(request, stream).await;
+        });
+    }
+}
+
+/// Read a request from a connection. Clients that send too much or too slowly are
+/// turned away, so they don't hold the listener.
+pub async fn read_request(stream: &mut TcpStream) -> Result<Request, Box<dyn std::error::Error>> {
+    tokio::time::timeout(READ_TIMEOUT, read(stream))
+        .await
+        .map_err(|_| "Timed out reading the request")?
+}
+
+async fn read(stream: &mut TcpStream) -> Result<Request, Box<dyn std::error::Error>> {
+    let mut reader = BufReader::new(stream);
+
+    let line = read_line(&mut reader).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or("Empty request")?.to_string();
+    let target = parts.next().ok_or("Request without a path")?;
+
+    // The host doesn't matter, it's only there to parse the path and query
+    let url = reqwest::Url::parse(&format!("http://localhost{}", target))?;
+    let query = url.query_pairs().into_owned().collect();
+
+    let mut headers = HashMap::new();
+    for count in 0.. {
+        let line = read_line(&mut reader).await?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err("Too many request headers".into());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return Err("Request body too large".into());
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Request {
+        method,
+        path: url.path().to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Read a line of the request head, empty at the end of the connection
+async fn read_line(
+    reader: &mut BufReader<&mut TcpStream>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64).read_line(&mut line).await?;
+    if line.len() == MAX_LINE && !line.ends_with('\n') {
+        return Err("Request line too long".into());
+    }
+    Ok(line)
+}
+
+/// Write a complete response and close the connection
+pub async fn respond(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status),
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Start a response whose body is written afterwards, as it comes (e.g. server-sent events)
+pub async fn start_stream(
+    stream: &mut TcpStream,
+    content_type: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        content_type
+    );
+    stream.write_all(head.as_bytes()).await?;
+
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,97 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Translations of the messages, built into the binary. English is the reference:
+/// messages missing from another language are shown in English.
+const LOCALES: [(&str, &str); 3] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("fr", include_str!("../locales/fr.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+struct Translations {
+    /// The chosen language, `None` for English
+    selected: Option<FluentBundle<FluentResource>>,
+    english: FluentBundle<FluentResource>,
+}
+
+static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+
+fn bundle(language: &str, source: &str) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = language.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // The Unicode isolation marks around arguments show up as garbage in terminals
+    bundle.set_use_isolating(false);
+
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+/// The language subtag of a setting or locale, e.g. `fr` for `fr_FR.UTF-8` or `fr-CA`
+fn language_of(locale: &str) -> String {
+    locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Choose the language of the messages for the rest of the run: the `language`
+/// setting, otherwise the locale of the system (`LC_ALL`, `LC_MESSAGES`, `LANG`).
+/// Languages without translations fall back to English.
+pub fn set_language(setting: Option<&str>) {
+    let language = setting
+        .map(str::to_string)
+        .or_else(sys_locale::get_locale)
+        .map(|locale| language_of(&locale))
+        .unwrap_or_default();
+
+    let _ = TRANSLATIONS.set(translations(&language));
+}
+
+fn translations(language: &str) -> Translations {
+    let (_, english) = LOCALES[0];
+    Translations {
+        selected: LOCALES
+            .iter()
+            .skip(1)
+            .find(|(id, _)| *id == language)
+            .map(|(id, source)| bundle(id, source)),
+        english: bundle("en", english),
+    }
+}
+
+/// The message with this ID in the chosen language
+pub fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// The message with this ID in the chosen language, with its `{ $name }` arguments
+pub fn tr_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let translations = TRANSLATIONS.get_or_init(|| translations("en"));
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+
+    for bundle in translations
+        .selected
+        .iter()
+        .chain(std::iter::once(&translations.english))
+    {
+        if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+            let mut errors = Vec::new();
+            return bundle
+                .format_pattern(pattern, Some(&fluent_args), &mut errors)
+                .into_owned();
+        }
+    }
+
+    // A message missing from English is a mistake, the ID is better than nothing
+    id.to_string()
+}
@@ -0,0 +1,346 @@
+use crate::config::{Config, Playlist};
+use crate::source;
+use crate::ui::log;
+use crate::youtube::{self, VideoInfo};
+use chrono::DateTime;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// NewPipe service ID of YouTube, the only service playsync can sync
+const NEWPIPE_YOUTUBE_SERVICE: i64 = 0;
+
+/// Name of the choice importing the subscriptions, which become a single playlist fed by
+/// the channels' RSS feeds
+pub const SUBSCRIPTIONS: &str = "\0subscriptions";
+
+/// Apps whose exports can be imported
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ImportFormat {
+    /// NewPipe subscriptions export (.json) or database export (.zip)
+    #[cfg_attr(feature = "clap", value(name = "newpipe"))]
+    NewPipe,
+    /// FreeTube playlists or profiles export (.db)
+    #[cfg_attr(feature = "clap", value(name = "freetube"))]
+    FreeTube,
+}
+
+impl ImportFormat {
+    /// Prefix of the `sync_from` entries reading from an export of this format
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ImportFormat::NewPipe => "newpipe",
+            ImportFormat::FreeTube => "freetube",
+        }
+    }
+
+    /// Find the format a `sync_from` prefix stands for
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        [ImportFormat::NewPipe, ImportFormat::FreeTube]
+            .into_iter()
+            .find(|format| format.prefix() == prefix)
+    }
+}
+
+/// A playlist stored locally by another app
+#[derive(Debug)]
+pub struct LocalPlaylist {
+    pub name: String,
+    pub videos: Vec<VideoInfo>,
+}
+
+/// Playlists and subscriptions read from an export file
+#[derive(Debug, Default)]
+pub struct Library {
+    pub playlists: Vec<LocalPlaylist>,
+    /// IDs of the subscribed channels
+    pub channels: Vec<String>,
+}
+
+/// Read the playlists and subscriptions of an export file. Non-YouTube items are skipped.
+pub fn read_library(
+    format: ImportFormat,
+    path: &Path,
+) -> Result<Library, Box<dyn std::error::Error>> {
+    match format {
+        ImportFormat::NewPipe => read_newpipe(path),
+        ImportFormat::FreeTube => read_freetube(path),
+    }
+}
+
+#[derive(Deserialize)]
+struct NewPipeSubscriptions {
+    subscriptions: Vec<NewPipeSubscription>,
+}
+
+#[derive(Deserialize)]
+struct NewPipeSubscription {
+    service_id: i64,
+    url: String,
+}
+
+/// Read a NewPipe export.
+///
+/// The subscriptions export is a JSON file. Local playlists are only part of the
+/// database export, a zip archive holding the `newpipe.db` SQLite database.
+fn read_newpipe(path: &Path) -> Result<Library, Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let export: NewPipeSubscriptions = serde_json::from_reader(File::open(path)?)?;
+
+        let channels = export
+            .subscriptions
+            .into_iter()
+            .filter(|s| s.service_id == NEWPIPE_YOUTUBE_SERVICE)
+            .filter_map(|s| channel_id_from_url(&s.url))
+            .collect();
+
+        return Ok(Library {
+            playlists: Vec::new(),
+            channels,
+        });
+    }
+
+    // The database has to be extracted to a file before SQLite can open it. The file is
+    // deleted when dropped, and is unique so that imports running at once don't clash
+    let mut db_file = tempfile::Builder::new()
+        .prefix("playsync-newpipe-")
+        .suffix(".db")
+        .tempfile()?;
+    if path.extension().is_some_and(|ext| ext == "zip") {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut db = archive.by_name("newpipe.db")?;
+        std::io::copy(&mut db, db_file.as_file_mut())?;
+    } else {
+        std::io::copy(&mut File::open(path)?, db_file.as_file_mut())?;
+    }
+
+    read_newpipe_db(db_file.path())
+}
+
+/// Read local playlists and subscriptions from a NewPipe SQLite database
+fn read_newpipe_db(db_path: &Path) -> Result<Library, Box<dyn std::error::Error>> {
+    let connection = rusqlite::Connection::open(db_path)?;
+    let mut library = Library::default();
+
+    let mut statement = connection.prepare(
+        "SELECT p.name, s.url, s.title, s.uploader
+         FROM playlists p
+         JOIN playlist_stream_join j ON j.playlist_id = p.uid
+         JOIN streams s ON s.uid = j.stream_id
+         WHERE s.service_id = ?1
+         ORDER BY p.uid, j.join_index",
+    )?;
+    let rows = statement.query_map([NEWPIPE_YOUTUBE_SERVICE], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    for row in rows {
+        let (name, url, title, uploader) = row?;
+        let Some(video_id) = youtube::parse_video_id(&url) else {
+            continue;
+        };
+
+        if library.playlists.last().is_none_or(|p| p.name != name) {
+            library.playlists.push(LocalPlaylist {
+                name,
+                videos: Vec::new(),
+            });
+        }
+
+        if let Some(playlist) = library.playlists.last_mut() {
+            playlist.videos.push(VideoInfo {
+                video_id,
+                title,
+                channel: uploader,
+                item_id: String::new(),
+                added_at: None,
+                published_at: None,
+                details: None,
+            });
+        }
+    }
+
+    let mut statement =
+        connection.prepare("SELECT url FROM subscriptions WHERE service_id = ?1")?;
+    let rows = statement.query_map([NEWPIPE_YOUTUBE_SERVICE], |row| row.get::<_, String>(0))?;
+
+    for row in rows {
+        if let Some(id) = channel_id_from_url(&row?) {
+            library.channels.push(id);
+        }
+    }
+
+    Ok(library)
+}
+
+/// A line of a FreeTube export: either a playlist or a profile
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FreeTubeRecord {
+    playlist_name: Option<String>,
+    #[serde(default)]
+    videos: Vec<FreeTubeVideo>,
+    #[serde(default)]
+    subscriptions: Vec<FreeTubeChannel>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FreeTubeVideo {
+    video_id: String,
+    #[serde(default)]
+    title: String,
+    author: Option<String>,
+    /// Milliseconds since the epoch
+    time_added: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct FreeTubeChannel {
+    id: String,
+}
+
+/// Read a FreeTube export.
+///
+/// FreeTube exports playlists and profiles (which hold the subscriptions) as
+/// separate files with one JSON record per line. Both can be read here.
+fn read_freetube(path: &Path) -> Result<Library, Box<dyn std::error::Error>> {
+    let mut library = Library::default();
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: FreeTubeRecord = serde_json::from_str(&line)?;
+
+        if let Some(name) = record.playlist_name {
+            let videos = record
+                .videos
+                .into_iter()
+                .map(|video| VideoInfo {
+                    video_id: video.video_id,
+                    title: video.title,
+                    channel: video.author,
+                    item_id: String::new(),
+                    added_at: video.time_added.and_then(DateTime::from_timestamp_millis),
+                    published_at: None,
+                    details: None,
+                })
+                .collect();
+
+            library.playlists.push(LocalPlaylist { name, videos });
+        }
+
+        // Every profile repeats channels of the "All Channels" profile
+        for channel in record.subscriptions {
+            if !library.channels.contains(&channel.id) {
+                library.channels.push(channel.id);
+            }
+        }
+    }
+
+    Ok(library)
+}
+
+/// Extract the channel ID from a `youtube.com/channel/UC...` URL
+fn channel_id_from_url(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/channel/")?;
+    let id = rest.split(['/', '?', '#']).next()?;
+
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// URL of the RSS feed of a channel's latest uploads, readable without API quota
+pub fn channel_feed_url(channel_id: &str) -> String {
+    format!(
+        "https://www.youtube.com/feeds/videos.xml?channel_id={}",
+        channel_id
+    )
+}
+
+/// What can be imported from a library: the name, label and hint of each playlist, then
+/// of the [`SUBSCRIPTIONS`] if there are any
+pub fn choices(library: &Library) -> Vec<(String, String, String)> {
+    let mut choices: Vec<(String, String, String)> = library
+        .playlists
+        .iter()
+        .map(|p| {
+            (
+                p.name.clone(),
+                p.name.clone(),
+                format!("{} videos", p.videos.len()),
+            )
+        })
+        .collect();
+    if !library.channels.is_empty() {
+        choices.push((
+            SUBSCRIPTIONS.to_string(),
+            "Subscriptions".to_string(),
+            format!("{} channels", library.channels.len()),
+        ));
+    }
+    choices
+}
+
+/// Add a playlist to the configuration for each choice of `names`, like `playsync import`,
+/// created on the next sync and synced from the export at `file`. Choices imported
+/// before are left out. Returns the number of playlists added.
+pub fn add_playlists(
+    cfg: &mut Config,
+    from: ImportFormat,
+    file: &Path,
+    library: &Library,
+    names: &[String],
+    dry_run: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut imported = 0;
+
+    for name in names {
+        let (title, sync_from) = if name == SUBSCRIPTIONS {
+            (
+                format!("{} subscriptions", from.prefix()),
+                library
+                    .channels
+                    .iter()
+                    .map(|channel_id| channel_feed_url(channel_id))
+                    .collect(),
+            )
+        } else {
+            (name.clone(), vec![source::import_source(from, file, name)])
+        };
+
+        // Importing the same export twice shouldn't duplicate targets
+        if cfg
+            .playlists
+            .iter()
+            .any(|p| p.sync_from.as_ref() == Some(&sync_from))
+        {
+            log::info(format!("'{}' is already imported", title))?;
+            continue;
+        }
+
+        if dry_run {
+            log::info(format!("Would import '{}'", title))?;
+            continue;
+        }
+
+        cfg.add_playlist(Playlist {
+            id: format!("new:\"{}\"", title),
+            title,
+            sync_from: Some(sync_from),
+            ..Default::default()
+        });
+        imported += 1;
+    }
+
+    Ok(imported)
+}
@@ -0,0 +1,83 @@
+use crate::matcher::Track;
+use serde::Deserialize;
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Number of tracks per page, the maximum allowed by Last.fm
+const PAGE_SIZE: u32 = 1000;
+
+#[derive(Deserialize)]
+struct LovedTracksResponse {
+    lovedtracks: LovedTracks,
+}
+
+#[derive(Deserialize)]
+struct LovedTracks {
+    track: Vec<LovedTrack>,
+    #[serde(rename = "@attr")]
+    attr: PageAttributes,
+}
+
+#[derive(Deserialize)]
+struct LovedTrack {
+    name: String,
+    artist: Artist,
+}
+
+#[derive(Deserialize)]
+struct Artist {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PageAttributes {
+    #[serde(rename = "totalPages")]
+    total_pages: String,
+}
+
+/// Get the tracks loved by a Last.fm user, oldest first.
+///
+/// Last.fm no longer exposes user playlists through its API, so only loved tracks
+/// are available.
+pub async fn loved_tracks(
+    api_key: &str,
+    user: &str,
+) -> Result<Vec<Track>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let mut tracks = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let response: LovedTracksResponse = client
+            .get(API_URL)
+            .query(&[
+                ("method", "user.getlovedtracks"),
+                ("user", user),
+                ("api_key", api_key),
+                ("format", "json"),
+                ("limit", &PAGE_SIZE.to_string()),
+                ("page", &page.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        tracks.extend(response.lovedtracks.track.into_iter().map(|track| Track {
+            artist: track.artist.name,
+            title: track.name,
+            duration: None,
+        }));
+
+        let total_pages: u32 = response.lovedtracks.attr.total_pages.parse().unwrap_or(1);
+        if page >= total_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    // Last.fm lists the most recently loved tracks first
+    tracks.reverse();
+    Ok(tracks)
+}
@@ -0,0 +1,130 @@
+//! The sync engine of [playsync](https://github.com/KilDesu/playsync), for embedding it in
+//! other tools. The `playsync` command line is a thin layer over this crate.
+//!
+//! The main entry points are:
+//!
+//! - [`Config`], the configuration file with its playlists and settings
+//! - [`YouTubeClient`], the YouTube Data API client, with its quota count, cache and
+//!   rate limits
+//! - [`run::sync`], syncing the configured playlists then recording and reporting the
+//!   run like `playsync sync`, and [`run::daemon`] like `playsync daemon`
+//! - [`sync_playlist`], syncing a single target playlist from its sources
+//! - [`source::fetch_videos`], reading the videos of a `sync_from` source
+//! - [`Provider`], what the sync engine reads and writes playlists with, and
+//!   [`InMemoryProvider`] to run it without YouTube in tests
+//! - [`report`], [`history`] and [`notify`], reporting the result of a sync
+//!
+//! Syncing the configured playlists:
+//!
+//! ```no_run
+//! use playsync_core::{Config, YouTubeClient, run};
+//!
+//! # async fn sync() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut config = Config::read()?;
+//! let client = YouTubeClient::new(config.oauth2_json.as_deref().ok_or("Not set up")?).await?;
+//!
+//! run::sync(&mut config, &client, &run::Selection::default(), false, None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Errors are boxed, and those of the YouTube API can be downcast to
+//! `google_youtube3::Error`. Messages are plain lines on stderr, and questions fail,
+//! unless [`ui::set`] sets a UI showing and asking them, like the terminal of the command
+//! line. The `clap` feature derives the command line arguments of the types that double
+//! as them, like [`filter::FilterArgs`].
+
+/// Local copy of the playlist items fetched from YouTube
+pub mod cache;
+/// Number of API requests run at once, adapting to throttling
+mod concurrency;
+/// The configuration file and its playlists
+pub mod config;
+/// Changes of a sync rendered like a unified diff
+pub mod diff;
+/// Mirroring playlists into local directories with yt-dlp
+pub mod download;
+/// Changing playlists outside of syncs: merging, copying, moving and converting them,
+/// adding and removing videos
+pub mod edit;
+/// Exit codes, telling failures apart
+pub mod exit;
+/// Keeping yt-dlp download archives in line with playlists
+mod export;
+/// Selecting a subset of playlist items
+pub mod filter;
+/// Graph of the playlists syncing from each other
+pub mod graph;
+/// Append-only log of the runs
+pub mod history;
+/// Shell commands run around the sync of a playlist
+pub mod hooks;
+/// Minimal HTTP server of the listeners
+pub mod http;
+/// Translations of the messages
+pub mod i18n;
+/// Reading the exports of other apps
+pub mod import;
+/// Last.fm sources
+mod lastfm;
+/// ListenBrainz sources
+mod listenbrainz;
+/// Matching tracks of music services to YouTube videos
+pub mod matcher;
+/// Counters of the syncs run by the process
+pub mod metrics;
+/// Resolving tracks on MusicBrainz
+mod musicbrainz;
+/// Desktop, webhook, email and ntfy notifications
+pub mod notify;
+/// Output format and terminal style of the run
+pub mod output;
+/// Plans of dry runs, applied as they are later
+pub mod plan;
+/// WebAssembly plugins providing and filtering videos
+pub mod plugin;
+/// Events of the syncs running in the process
+pub mod progress;
+/// Where the sync engine reads and writes playlists: YouTube, or memory in tests
+pub mod provider;
+/// Request rate limits shared by the whole process
+pub mod ratelimit;
+/// Reports of the sync runs
+pub mod report;
+/// Runs of the sync engine: the configured playlists synced, recorded and reported
+pub mod run;
+/// Cron schedules, intervals and sync windows
+pub mod schedule;
+/// Rhai scripts filtering the videos of a playlist
+mod script;
+/// Encrypted secrets of the configuration
+pub mod secret;
+/// Sorting and shuffling playlists
+pub mod sort;
+/// The sources of videos of a playlist
+pub mod source;
+/// Splitting a playlist into several
+pub mod split;
+/// Spotify sources
+mod spotify;
+/// Statistics of playlists
+pub mod stats;
+/// How far playlists are behind their sources, and why a video is synced or not
+pub mod status;
+/// The sync engine
+pub mod sync;
+/// Maintenance of playlists
+pub mod tidy;
+/// How the library shows messages and progress and asks questions
+pub mod ui;
+/// Checking the configuration
+pub mod validate;
+/// Push notifications of new uploads through WebSub
+mod websub;
+/// The YouTube Data API client
+pub mod youtube;
+
+pub use config::{Config, Playlist};
+pub use provider::{InMemoryProvider, Provider};
+pub use sync::{SyncReport, sync_playlist};
+pub use youtube::YouTubeClient;
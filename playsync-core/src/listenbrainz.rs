@@ -0,0 +1,151 @@
+use crate::matcher::Track;
+use serde::Deserialize;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.listenbrainz.org/1";
+
+/// Number of feedback items per page, the maximum allowed by ListenBrainz
+const PAGE_SIZE: usize = 1000;
+
+#[derive(Deserialize)]
+struct FeedbackResponse {
+    feedback: Vec<Feedback>,
+    total_count: usize,
+}
+
+#[derive(Deserialize)]
+struct Feedback {
+    track_metadata: Option<TrackMetadata>,
+}
+
+#[derive(Deserialize)]
+struct TrackMetadata {
+    artist_name: String,
+    track_name: String,
+}
+
+#[derive(Deserialize)]
+struct PlaylistsResponse {
+    playlists: Vec<PlaylistWrapper>,
+}
+
+#[derive(Deserialize)]
+struct PlaylistWrapper {
+    playlist: Playlist,
+}
+
+/// A playlist in the JSPF format used by ListenBrainz
+#[derive(Deserialize)]
+struct Playlist {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    identifier: String,
+    #[serde(default)]
+    track: Vec<PlaylistTrack>,
+}
+
+#[derive(Deserialize)]
+struct PlaylistTrack {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    creator: String,
+    /// Length in milliseconds
+    duration: Option<u64>,
+}
+
+/// Send a GET request to the ListenBrainz API, authenticated when a token is given
+async fn get<T: serde::de::DeserializeOwned>(
+    path: &str,
+    token: Option<&str>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let mut request = reqwest::Client::new().get(format!("{}{}", API_URL, path));
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Token {}", token));
+    }
+
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+/// Get the recordings loved by a user, oldest first
+pub async fn loved_recordings(
+    user: &str,
+    token: Option<&str>,
+) -> Result<Vec<Track>, Box<dyn std::error::Error>> {
+    let mut tracks = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        let response: FeedbackResponse = get(
+            &format!(
+                "/feedback/user/{}/get-feedback?score=1&metadata=true&count={}&offset={}",
+                user, PAGE_SIZE, offset
+            ),
+            token,
+        )
+        .await?;
+
+        let count = response.feedback.len();
+        tracks.extend(
+            response
+                .feedback
+                .into_iter()
+                .filter_map(|feedback| feedback.track_metadata)
+                .map(|metadata| Track {
+                    artist: metadata.artist_name,
+                    title: metadata.track_name,
+                    duration: None,
+                }),
+        );
+
+        offset += count;
+        if count == 0 || offset >= response.total_count {
+            break;
+        }
+    }
+
+    // Feedback is listed most recent first
+    tracks.reverse();
+    Ok(tracks)
+}
+
+/// Get the tracks of the latest playlist generated for a user whose title starts with
+/// `name` (case-insensitive, dashes read as spaces), e.g. `weekly-jams`
+pub async fn generated_playlist(
+    user: &str,
+    name: &str,
+    token: Option<&str>,
+) -> Result<Vec<Track>, Box<dyn std::error::Error>> {
+    let name = name.replace('-', " ").to_lowercase();
+
+    // Generated playlists are listed newest first
+    let response: PlaylistsResponse =
+        get(&format!("/user/{}/playlists/createdfor", user), token).await?;
+    let playlist = response
+        .playlists
+        .into_iter()
+        .map(|wrapper| wrapper.playlist)
+        .find(|playlist| playlist.title.to_lowercase().starts_with(&name))
+        .ok_or_else(|| format!("No '{}' playlist generated for {}", name, user))?;
+
+    // The listing doesn't include the tracks, so fetch the playlist itself
+    let mbid = playlist
+        .identifier
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let response: PlaylistWrapper = get(&format!("/playlist/{}", mbid), token).await?;
+
+    Ok(response
+        .playlist
+        .track
+        .into_iter()
+        .map(|track| Track {
+            artist: track.creator,
+            title: track.title,
+            duration: track.duration.map(Duration::from_millis),
+        })
+        .collect())
+}
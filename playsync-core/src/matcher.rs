@@ -0,0 +1,246 @@
+use crate::musicbrainz;
+use crate::provider::Provider;
+use crate::ui::log;
+use crate::youtube::{SearchQuery, VideoInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Maximum number of tracks looked up on YouTube per source and sync.
+///
+/// Each lookup is a search costing 100 quota units, so a large library is matched
+/// over several runs instead of exhausting the daily quota at once.
+pub const MAX_LOOKUPS_PER_RUN: usize = 25;
+
+/// Number of search results considered for each track
+const CANDIDATES: u32 = 5;
+
+/// Words marking a different version of a track, only accepted when the track has them too
+const VERSION_WORDS: [&str; 5] = ["cover", "remix", "karaoke", "live", "instrumental"];
+
+/// A music track from another service, to be matched to a YouTube video
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub artist: String,
+    pub title: String,
+    /// Length of the track, used to prefer videos of the same length
+    pub duration: Option<Duration>,
+}
+
+impl Track {
+    /// Key of the track in the match cache
+    fn key(&self) -> String {
+        format!("{} - {}", normalize(&self.artist), normalize(&self.title))
+    }
+}
+
+/// Outcome of matching tracks to YouTube videos
+#[derive(Debug, Default)]
+pub struct MatchReport {
+    /// Tracks with the video they were matched to, in the order of the tracks
+    pub matched: Vec<(Track, VideoInfo)>,
+    /// Tracks without a good match on YouTube
+    pub unmatched: Vec<Track>,
+    /// Tracks not looked up because the lookup limit was reached
+    pub deferred: Vec<Track>,
+}
+
+impl MatchReport {
+    /// The matched videos, in the order of the tracks
+    pub fn videos(&self) -> Vec<VideoInfo> {
+        self.matched
+            .iter()
+            .map(|(_, video)| video.clone())
+            .collect()
+    }
+}
+
+/// Cache of previous matches, stored next to the configuration.
+///
+/// Tracks without a match are cached too (as `None`), so they aren't searched again on
+/// every run. Delete `matches.json` to retry them.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct MatchCache {
+    tracks: HashMap<String, Option<CachedVideo>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedVideo {
+    video_id: String,
+    title: String,
+}
+
+fn cache_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(crate::config::config_dir()?.join("matches.json"))
+}
+
+impl MatchCache {
+    fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(cache_path()?) {
+            Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(cache_path()?, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Find a YouTube video for each track.
+///
+/// Tracks that were matched before are taken from the cache, and at most
+/// `max_lookups` other tracks are searched on YouTube. With `use_musicbrainz`, tracks
+/// are first resolved to their canonical artist, title and length on MusicBrainz,
+/// which makes the YouTube search more accurate.
+pub async fn match_tracks(
+    provider: &dyn Provider,
+    tracks: &[Track],
+    use_musicbrainz: bool,
+    max_lookups: usize,
+) -> Result<MatchReport, Box<dyn std::error::Error>> {
+    let mut cache = MatchCache::load()?;
+    let mut report = MatchReport::default();
+    let mut lookups = 0;
+
+    for track in tracks {
+        let key = track.key();
+
+        let cached = match cache.tracks.get(&key) {
+            Some(cached) => cached.clone(),
+            None if lookups < max_lookups => {
+                lookups += 1;
+
+                let resolved = if use_musicbrainz {
+                    musicbrainz::resolve(track).await.unwrap_or_else(|e| {
+                        let _ = log::warning(format!("MusicBrainz lookup failed: {}", e));
+                        None
+                    })
+                } else {
+                    None
+                };
+
+                let found = find_video(provider, resolved.as_ref().unwrap_or(track))
+                    .await?
+                    .map(|video| CachedVideo {
+                        video_id: video.video_id,
+                        title: video.title,
+                    });
+
+                cache.tracks.insert(key, found.clone());
+                found
+            }
+            None => {
+                report.deferred.push(track.clone());
+                continue;
+            }
+        };
+
+        match cached {
+            Some(video) => report.matched.push((
+                track.clone(),
+                VideoInfo {
+                    video_id: video.video_id,
+                    title: video.title,
+                    channel: None,
+                    item_id: String::new(),
+                    added_at: None,
+                    published_at: None,
+                    details: None,
+                },
+            )),
+            None => report.unmatched.push(track.clone()),
+        }
+    }
+
+    if lookups > 0 {
+        cache.save()?;
+    }
+
+    Ok(report)
+}
+
+/// Search YouTube for a track and pick the most likely video.
+///
+/// A candidate must contain the track title, and the artist in its title or channel.
+/// Covers, remixes and other versions are skipped unless the track is one. Official
+/// uploads (auto-generated "Artist - Topic" channels, "official" in the title) and
+/// videos as long as the track are preferred.
+pub async fn find_video(
+    provider: &dyn Provider,
+    track: &Track,
+) -> Result<Option<VideoInfo>, Box<dyn std::error::Error>> {
+    let mut candidates = provider
+        .search_videos(&SearchQuery {
+            query: format!("{} {}", track.artist, track.title),
+            max_results: CANDIDATES,
+            ..Default::default()
+        })
+        .await?;
+
+    if track.duration.is_some() {
+        provider.enrich_videos(&mut candidates).await?;
+    }
+
+    let artist = normalize(&track.artist);
+    let title = normalize(&track.title);
+
+    let best = candidates
+        .into_iter()
+        .filter_map(|video| {
+            let video_title = normalize(&video.title);
+            let channel = normalize(video.channel.as_deref().unwrap_or_default());
+
+            if !video_title.contains(&title)
+                || !(video_title.contains(&artist) || channel.contains(&artist))
+            {
+                return None;
+            }
+
+            let is_other_version = VERSION_WORDS.iter().any(|word| {
+                has_word(&video_title, word) && !has_word(&title, word) && !has_word(&artist, word)
+            });
+            if is_other_version {
+                return None;
+            }
+
+            let mut score = 0;
+            if channel.ends_with("topic") {
+                score += 2;
+            }
+            if video_title.contains("official") {
+                score += 1;
+            }
+            if let (Some(expected), Some(actual)) = (track.duration, video.duration())
+                && expected.abs_diff(actual) <= Duration::from_secs(10)
+            {
+                score += 2;
+            }
+
+            Some((score, video))
+        })
+        // Ties go to the first (most relevant) result
+        .rev()
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, video)| video);
+
+    Ok(best)
+}
+
+/// Whether a normalized string contains a whole word
+fn has_word(value: &str, word: &str) -> bool {
+    value.split(' ').any(|w| w == word)
+}
+
+/// Lowercase a string and keep only letters, digits and single spaces
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,216 @@
+use crate::history::HistoryEntry;
+use crate::http;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Upper bounds of the API request latency histogram, in seconds
+const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Counters of the syncs run by this process, for `daemon` and `serve`
+#[derive(Default)]
+struct Metrics {
+    /// Items added, removed and failed per playlist ID
+    added: BTreeMap<String, u64>,
+    removed: BTreeMap<String, u64>,
+    failed: BTreeMap<String, u64>,
+    /// Unix time of the last sync of each playlist that completed without failures
+    last_success: BTreeMap<String, i64>,
+    syncs_succeeded: u64,
+    syncs_failed: u64,
+    /// Error of the last sync, dry runs included
+    last_error: Option<String>,
+    quota_used: u64,
+    /// Number of API requests at or below each bucket of [`LATENCY_BUCKETS`]
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+    latency_count: u64,
+}
+
+static METRICS: OnceLock<Mutex<Metrics>> = OnceLock::new();
+
+fn with_metrics<T>(f: impl FnOnce(&mut Metrics) -> T) -> T {
+    let mut metrics = METRICS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut metrics)
+}
+
+/// Run an API request, recording how long it took
+pub async fn timed<F: Future>(request: F) -> F::Output {
+    let start = Instant::now();
+    let output = request.await;
+    observe_latency(start.elapsed());
+    output
+}
+
+fn observe_latency(elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    with_metrics(|metrics| {
+        for (bucket, bound) in LATENCY_BUCKETS.iter().enumerate() {
+            if seconds <= *bound {
+                metrics.latency_buckets[bucket] += 1;
+            }
+        }
+        metrics.latency_sum += seconds;
+        metrics.latency_count += 1;
+    });
+}
+
+/// Count a finished sync. Dry runs only count the quota they used.
+pub fn record(entry: &HistoryEntry) {
+    with_metrics(|metrics| {
+        metrics.quota_used += entry.quota_used;
+        metrics.last_error = entry.error.clone();
+        if entry.dry_run {
+            return;
+        }
+
+        if entry.error.is_some() {
+            metrics.syncs_failed += 1;
+        } else {
+            metrics.syncs_succeeded += 1;
+        }
+
+        for playlist in &entry.playlists {
+            *metrics.added.entry(playlist.id.clone()).or_default() += playlist.added as u64;
+            *metrics.removed.entry(playlist.id.clone()).or_default() += playlist.removed as u64;
+            *metrics.failed.entry(playlist.id.clone()).or_default() += playlist.failed as u64;
+            if playlist.failed == 0 {
+                metrics
+                    .last_success
+                    .insert(playlist.id.clone(), entry.timestamp.timestamp());
+            }
+        }
+    });
+}
+
+/// The metrics in the Prometheus text format
+pub fn render() -> String {
+    with_metrics(|metrics| {
+        let mut out = String::new();
+
+        for (name, help, values) in [
+            (
+                "playsync_items_added_total",
+                "Videos added to playlists",
+                &metrics.added,
+            ),
+            (
+                "playsync_items_removed_total",
+                "Videos removed from playlists",
+                &metrics.removed,
+            ),
+            (
+                "playsync_items_failed_total",
+                "Videos that failed to be added",
+                &metrics.failed,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (playlist, value) in values {
+                let _ = writeln!(
+                    out,
+                    "{}{{playlist=\"{}\"}} {}",
+                    name,
+                    escape(playlist),
+                    value
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP playsync_last_success_timestamp_seconds Last sync of the playlist without failures\n# TYPE playsync_last_success_timestamp_seconds gauge"
+        );
+        for (playlist, timestamp) in &metrics.last_success {
+            let _ = writeln!(
+                out,
+                "playsync_last_success_timestamp_seconds{{playlist=\"{}\"}} {}",
+                escape(playlist),
+                timestamp
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP playsync_syncs_total Syncs run, by result\n# TYPE playsync_syncs_total counter\nplaysync_syncs_total{{result=\"success\"}} {}\nplaysync_syncs_total{{result=\"failure\"}} {}",
+            metrics.syncs_succeeded, metrics.syncs_failed
+        );
+        let _ = writeln!(
+            out,
+            "# HELP playsync_quota_used_total Estimated YouTube API quota units used\n# TYPE playsync_quota_used_total counter\nplaysync_quota_used_total {}",
+            metrics.quota_used
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP playsync_api_request_duration_seconds YouTube API request latency\n# TYPE playsync_api_request_duration_seconds histogram"
+        );
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(metrics.latency_buckets) {
+            let _ = writeln!(
+                out,
+                "playsync_api_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "playsync_api_request_duration_seconds_bucket{{le=\"+Inf\"}} {}\nplaysync_api_request_duration_seconds_sum {}\nplaysync_api_request_duration_seconds_count {}",
+            metrics.latency_count, metrics.latency_sum, metrics.latency_count
+        );
+
+        out
+    })
+}
+
+/// Error of the last sync, `None` when it succeeded or nothing was synced yet
+pub fn last_error() -> Option<String> {
+    with_metrics(|metrics| metrics.last_error.clone())
+}
+
+/// Answer a health check: `200 OK`, or `503` with the error of the last sync
+pub async fn respond_health(stream: &mut TcpStream) -> Result<(), Box<dyn std::error::Error>> {
+    match last_error() {
+        None => http::respond(stream, 200, "text/plain", b"ok").await,
+        Some(error) => http::respond(stream, 503, "text/plain", error.as_bytes()).await,
+    }
+}
+
+/// Label values are quoted, so backslashes, quotes and newlines are escaped
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serve the metrics at `/metrics` and the health check at `/healthz` on `address`,
+/// for the daemon
+pub async fn listen(address: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(address).await?;
+
+    http::serve(listener, |request, mut stream| async move {
+        let _ = match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/metrics") => {
+                http::respond(
+                    &mut stream,
+                    200,
+                    "text/plain; version=0.0.4",
+                    render().as_bytes(),
+                )
+                .await
+            }
+            ("GET", "/healthz") => respond_health(&mut stream).await,
+            _ => http::respond(&mut stream, 404, "text/plain", b"Not found").await,
+        };
+    })
+    .await;
+
+    Ok(())
+}
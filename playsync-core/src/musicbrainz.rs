@@ -0,0 +1,95 @@
+use crate::matcher::Track;
+use serde::Deserialize;
+use std::time::Duration;
+
+const API_URL: &str = "https://musicbrainz.org/ws/2/recording";
+
+/// MusicBrainz asks clients to identify themselves
+const USER_AGENT: &str = concat!(
+    "playsync/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/KilDesu/playsync)"
+);
+
+/// MusicBrainz allows one request per second
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Minimum search score (out of 100) for a recording to be trusted
+const MIN_SCORE: u32 = 90;
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    recordings: Vec<Recording>,
+}
+
+#[derive(Deserialize)]
+struct Recording {
+    title: String,
+    #[serde(default)]
+    score: u32,
+    /// Length in milliseconds
+    length: Option<u64>,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCredit {
+    name: String,
+    #[serde(default)]
+    joinphrase: String,
+}
+
+/// Look up a track on MusicBrainz and return it with its canonical artist, title and
+/// length, or `None` when no recording matches closely enough.
+pub async fn resolve(track: &Track) -> Result<Option<Track>, Box<dyn std::error::Error>> {
+    tokio::time::sleep(REQUEST_INTERVAL).await;
+
+    let query = format!(
+        "recording:\"{}\" AND artist:\"{}\"",
+        escape(&track.title),
+        escape(&track.artist)
+    );
+
+    let response: SearchResponse = reqwest::Client::new()
+        .get(API_URL)
+        .header("User-Agent", USER_AGENT)
+        .query(&[("query", query.as_str()), ("fmt", "json"), ("limit", "1")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let Some(recording) = response
+        .recordings
+        .into_iter()
+        .find(|recording| recording.score >= MIN_SCORE)
+    else {
+        return Ok(None);
+    };
+
+    let artist: String = recording
+        .artist_credit
+        .iter()
+        .map(|credit| format!("{}{}", credit.name, credit.joinphrase))
+        .collect();
+
+    Ok(Some(Track {
+        artist: if artist.is_empty() {
+            track.artist.clone()
+        } else {
+            artist
+        },
+        title: recording.title,
+        duration: recording
+            .length
+            .map(Duration::from_millis)
+            .or(track.duration),
+    }))
+}
+
+/// Escape the characters that have a meaning in Lucene queries
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
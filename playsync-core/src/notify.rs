@@ -0,0 +1,650 @@
+use crate::history::{self, HistoryEntry, PlaylistRecord};
+use crate::{schedule, secret};
+use chrono::{DateTime, Datelike, Local, Utc};
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSendmailTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Files storing when the last digests were sent, next to the configuration
+const EMAIL_DIGEST_STATE: &str = "email_digest.txt";
+const DIGEST_STATE: &str = "notification_digest.txt";
+
+/// Whether the syncs of this process are notified in a digest, see [`batch_into_digest`]
+static DIGEST_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Where to report the result of each sync, in the `[notifications]` section
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Notifications {
+    /// Show a desktop notification after each sync
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub desktop: bool,
+
+    /// Only notify when a sync changed something or had errors
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub only_on_changes: bool,
+
+    /// In daemon mode, notify a digest of the syncs every period (e.g. `24h`) instead of
+    /// each sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    /// POST a JSON payload to a URL after each sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<Webhook>,
+
+    /// Email the report of each sync, or a digest of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<Email>,
+
+    /// Push the summary of each sync to an ntfy topic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ntfy: Option<Ntfy>,
+}
+
+impl Notifications {
+    /// Period of the digest. An invalid one is reported by `config validate`, and syncs
+    /// are notified one by one until it's fixed.
+    fn digest_period(&self) -> Option<chrono::Duration> {
+        self.digest
+            .as_deref()
+            .and_then(|period| schedule::parse_interval(period).ok())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.desktop
+            && !self.only_on_changes
+            && self.digest.is_none()
+            && self.webhook.is_none()
+            && self.email.is_none()
+            && self.ntfy.is_none()
+    }
+}
+
+/// A URL receiving the report of each sync, in the `[notifications.webhook]` section
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+
+    /// Extra request headers, e.g. `Authorization`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    /// Body to send instead of the full report. `{summary}`, `{details}`, `{added}`,
+    /// `{removed}`, `{failed}` and `{error}` are replaced by JSON-escaped text (to use inside
+    /// strings), and `{report}` by the full report as a JSON object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+/// An ntfy topic receiving push notifications, in the `[notifications.ntfy]` section
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ntfy {
+    pub topic: String,
+
+    /// Server hosting the topic
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+
+    /// Access token for protected topics. Can be encrypted with `playsync config encrypt`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Priority of the notifications of successful syncs
+    #[serde(default)]
+    pub priority: NtfyPriority,
+
+    /// Priority of the notifications of syncs that errored out
+    #[serde(default = "default_error_priority")]
+    pub error_priority: NtfyPriority,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+fn default_error_priority() -> NtfyPriority {
+    NtfyPriority::Urgent
+}
+
+/// Priority of an ntfy message, deciding how insistently the phone notifies
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NtfyPriority {
+    Min,
+    Low,
+    #[default]
+    Default,
+    High,
+    Urgent,
+}
+
+impl NtfyPriority {
+    fn as_str(self) -> &'static str {
+        match self {
+            NtfyPriority::Min => "min",
+            NtfyPriority::Low => "low",
+            NtfyPriority::Default => "default",
+            NtfyPriority::High => "high",
+            NtfyPriority::Urgent => "urgent",
+        }
+    }
+}
+
+/// Where to email reports, in the `[notifications.email]` section.
+/// Without an `[notifications.email.smtp]` section, emails are sent with the local `sendmail`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Email {
+    /// Sender address, e.g. `playsync <playsync@example.com>`
+    pub from: String,
+
+    /// Recipient addresses
+    pub to: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp: Option<Smtp>,
+
+    /// Send one email per day or week listing the syncs since the last one, instead of
+    /// one per sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<Digest>,
+}
+
+/// The SMTP server sending the emails
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Smtp {
+    pub host: String,
+
+    /// Defaults to the standard port of the `tls` mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Can be encrypted with `playsync config encrypt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    #[serde(default)]
+    pub tls: SmtpTls,
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS (port 587)
+    #[default]
+    Starttls,
+    /// Connect with TLS right away (port 465)
+    Tls,
+    /// Don't encrypt the connection, e.g. for a local relay (port 25)
+    None,
+}
+
+/// How often an email digest is sent
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum Digest {
+    Daily,
+    Weekly,
+}
+
+impl Digest {
+    /// Digests are sent by the first sync of a new day or week, in local time
+    fn period(self, time: DateTime<Utc>) -> (i32, u32) {
+        let time = time.with_timezone(&Local);
+        match self {
+            Digest::Daily => (time.year(), time.ordinal()),
+            Digest::Weekly => (time.iso_week().year(), time.iso_week().week()),
+        }
+    }
+}
+
+/// Whether a sync added or removed videos
+fn has_changes(entry: &HistoryEntry) -> bool {
+    entry.playlists.iter().any(|p| p.added > 0 || p.removed > 0)
+}
+
+/// One-line summary of a sync, e.g. `12 added, 1 failed in 3 playlists`
+pub fn summary(entry: &HistoryEntry) -> String {
+    let prefix = if entry.dry_run { "Dry run: " } else { "" };
+    if entry.error.is_some() {
+        return format!("{}sync failed", prefix);
+    }
+
+    format!("{}{}", prefix, counts(entry))
+}
+
+/// Number of videos added, removed and failed, e.g. `12 added, 1 failed in 3 playlist(s)`
+fn counts(entry: &HistoryEntry) -> String {
+    let total =
+        |count: fn(&PlaylistRecord) -> usize| -> usize { entry.playlists.iter().map(count).sum() };
+    let mut counts = vec![format!("{} added", total(|p| p.added))];
+    for (label, count) in [
+        ("removed", total(|p| p.removed)),
+        ("failed", total(|p| p.failed)),
+    ] {
+        if count > 0 {
+            counts.push(format!("{} {}", count, label));
+        }
+    }
+
+    format!(
+        "{} in {} playlist(s)",
+        counts.join(", "),
+        entry.playlists.len()
+    )
+}
+
+/// Details of a sync: the error, then one line per playlist that changed or failed
+pub fn details(entry: &HistoryEntry) -> String {
+    let mut lines: Vec<String> = entry.error.iter().cloned().collect();
+
+    for playlist in &entry.playlists {
+        if playlist.added == 0 && playlist.removed == 0 && playlist.failed == 0 {
+            continue;
+        }
+        let title = if playlist.title.is_empty() {
+            &playlist.id
+        } else {
+            &playlist.title
+        };
+        lines.push(format!(
+            "{}: +{} −{}{}",
+            title,
+            playlist.added,
+            playlist.removed,
+            if playlist.failed > 0 {
+                format!(" ({} failed)", playlist.failed)
+            } else {
+                String::new()
+            }
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// The report of a sync as JSON: the history entry, with its summary
+fn report(entry: &HistoryEntry, summary: &str) -> Result<Value, serde_json::Error> {
+    let mut report = serde_json::to_value(entry)?;
+    if let Value::Object(fields) = &mut report {
+        fields.insert("summary".to_string(), Value::String(summary.to_string()));
+    }
+
+    Ok(report)
+}
+
+/// Fill the placeholders of a webhook template
+fn render_template(
+    template: &str,
+    entry: &HistoryEntry,
+    summary: &str,
+) -> Result<String, serde_json::Error> {
+    // JSON-escaped, without the surrounding quotes
+    let escape = |text: &str| -> Result<String, serde_json::Error> {
+        let quoted = serde_json::to_string(text)?;
+        Ok(quoted[1..quoted.len() - 1].to_string())
+    };
+    let total = |count: fn(&PlaylistRecord) -> usize| -> String {
+        entry.playlists.iter().map(count).sum::<usize>().to_string()
+    };
+
+    Ok(template
+        .replace("{summary}", &escape(summary)?)
+        .replace("{details}", &escape(&details(entry))?)
+        .replace(
+            "{error}",
+            &escape(entry.error.as_deref().unwrap_or_default())?,
+        )
+        .replace("{added}", &total(|p| p.added))
+        .replace("{removed}", &total(|p| p.removed))
+        .replace("{failed}", &total(|p| p.failed))
+        .replace("{report}", &report(entry, summary)?.to_string()))
+}
+
+async fn post_webhook(
+    webhook: &Webhook,
+    entry: &HistoryEntry,
+    summary: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let body = match &webhook.template {
+        Some(template) => render_template(template, entry, summary)?,
+        None => report(entry, summary)?.to_string(),
+    };
+
+    let mut request = reqwest::Client::new()
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(format!("{} answered {}", webhook.url, response.status()).into());
+    }
+
+    Ok(())
+}
+
+async fn publish_ntfy(
+    ntfy: &Ntfy,
+    entry: &HistoryEntry,
+    summary: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (priority, tags) = if entry.error.is_some() {
+        (ntfy.error_priority, "rotating_light")
+    } else if entry.has_failures() {
+        (ntfy.priority, "warning")
+    } else {
+        (ntfy.priority, "white_check_mark")
+    };
+    let details = details(entry);
+    let url = format!("{}/{}", ntfy.server.trim_end_matches('/'), ntfy.topic);
+
+    let mut request = reqwest::Client::new()
+        .post(&url)
+        .header("Title", format!("playsync: {}", summary))
+        .header("Priority", priority.as_str())
+        .header("Tags", tags)
+        .body(if details.is_empty() {
+            summary.to_string()
+        } else {
+            details
+        });
+    if let Some(token) = &ntfy.token {
+        request = request.bearer_auth(secret::reveal(token)?);
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(format!("{} answered {}", url, response.status()).into());
+    }
+
+    Ok(())
+}
+
+async fn send_email(
+    email: &Email,
+    subject: &str,
+    body: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut message = Message::builder()
+        .from(email.from.parse::<Mailbox>()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for recipient in &email.to {
+        message = message.to(recipient.parse::<Mailbox>()?);
+    }
+    let message = message.body(body)?;
+
+    let Some(smtp) = &email.smtp else {
+        AsyncSendmailTransport::<Tokio1Executor>::new()
+            .send(message)
+            .await?;
+        return Ok(());
+    };
+
+    let mut transport = match smtp.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?,
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+    };
+    if let Some(port) = smtp.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &smtp.username {
+        let password = match &smtp.password {
+            Some(password) => secret::reveal(password)?,
+            None => String::new(),
+        };
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+/// When a digest was last sent, from its state file.
+///
+/// The first time, the state is created and `None` returned: the first digest covers the
+/// syncs from now on.
+fn last_sent(state: &str) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+    let path = crate::config::config_dir()?.join(state);
+    let last_sent = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|time| DateTime::parse_from_rfc3339(time.trim()).ok())
+        .map(|time| time.with_timezone(&Utc));
+
+    if last_sent.is_none() {
+        std::fs::write(&path, Utc::now().to_rfc3339())?;
+    }
+    Ok(last_sent)
+}
+
+fn mark_sent(state: &str, time: DateTime<Utc>) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(crate::config::config_dir()?.join(state), time.to_rfc3339())?;
+    Ok(())
+}
+
+/// The syncs recorded in the history since `since`, skipping the quiet ones with
+/// `only_on_changes`
+fn syncs_since(
+    since: DateTime<Utc>,
+    only_on_changes: bool,
+) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+    Ok(history::read_all()?
+        .into_iter()
+        .filter(|entry| entry.command == "sync" && entry.timestamp > since)
+        .filter(|entry| !only_on_changes || has_changes(entry) || entry.has_failures())
+        .collect())
+}
+
+/// Email the syncs since the last digest, if a new day or week started since then
+async fn send_email_digest(
+    email: &Email,
+    digest: Digest,
+    only_on_changes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = Utc::now();
+    let Some(last_sent) = last_sent(EMAIL_DIGEST_STATE)? else {
+        return Ok(());
+    };
+    if digest.period(now) == digest.period(last_sent) {
+        return Ok(());
+    }
+
+    let entries = syncs_since(last_sent, only_on_changes)?;
+
+    if !entries.is_empty() {
+        let mut body = String::new();
+        for entry in &entries {
+            body.push_str(&format!(
+                "{}: {}\n",
+                entry
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M"),
+                summary(entry)
+            ));
+            for line in details(entry).lines() {
+                body.push_str(&format!("    {}\n", line));
+            }
+        }
+
+        let period = match digest {
+            Digest::Daily => "daily",
+            Digest::Weekly => "weekly",
+        };
+        send_email(
+            email,
+            &format!("playsync: {} digest, {} sync(s)", period, entries.len()),
+            body,
+        )
+        .await?;
+    }
+
+    mark_sent(EMAIL_DIGEST_STATE, now)
+}
+
+/// Add up syncs into a single entry, with the totals of each playlist and their errors
+fn merge(entries: &[HistoryEntry], since: DateTime<Utc>) -> HistoryEntry {
+    let mut merged = HistoryEntry::new("digest", false);
+    merged.timestamp = since;
+
+    for entry in entries {
+        merged.quota_used += entry.quota_used;
+        for playlist in &entry.playlists {
+            let index = match merged.playlists.iter().position(|p| p.id == playlist.id) {
+                Some(index) => index,
+                None => {
+                    merged
+                        .playlists
+                        .push(PlaylistRecord::new(&playlist.id, &playlist.title));
+                    merged.playlists.len() - 1
+                }
+            };
+            let total = &mut merged.playlists[index];
+            total.title.clone_from(&playlist.title);
+            total.added += playlist.added;
+            total.removed += playlist.removed;
+            total.failed += playlist.failed;
+            total.item_count = playlist.item_count.or(total.item_count);
+        }
+    }
+
+    let errors: Vec<&str> = entries.iter().filter_map(|e| e.error.as_deref()).collect();
+    if !errors.is_empty() {
+        merged.error = Some(errors.join("\n"));
+    }
+
+    merged
+}
+
+/// Notify a digest of the syncs since the last one, once its period has passed.
+///
+/// Called regularly by the daemon, which batches its notifications with
+/// [`batch_into_digest`]. Like [`send`], errors are returned to be shown as warnings.
+pub async fn send_digest(notifications: &Notifications) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(period) = notifications.digest_period() else {
+        return errors;
+    };
+
+    let now = Utc::now();
+    let since = match last_sent(DIGEST_STATE) {
+        Ok(Some(last_sent)) if now - last_sent >= period => Ok(last_sent),
+        Ok(_) => return errors,
+        Err(e) => Err(e),
+    };
+    let (last_sent, entries) = match since.and_then(|last_sent| {
+        syncs_since(last_sent, notifications.only_on_changes).map(|e| (last_sent, e))
+    }) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(format!("Failed to prepare the digest: {}", e));
+            return errors;
+        }
+    };
+
+    if !entries.is_empty() {
+        let merged = merge(&entries, last_sent);
+        let failed_syncs = entries.iter().filter(|e| e.error.is_some()).count();
+        let summary = format!(
+            "Last {}: {} over {} sync(s){}",
+            notifications.digest.as_deref().unwrap_or_default(),
+            counts(&merged),
+            entries.len(),
+            if failed_syncs > 0 {
+                format!(", {} failed", failed_syncs)
+            } else {
+                String::new()
+            }
+        );
+        errors.extend(deliver(notifications, &merged, &summary).await);
+    }
+
+    if let Err(e) = mark_sent(DIGEST_STATE, now) {
+        errors.push(format!("Failed to save when the digest was sent: {}", e));
+    }
+    errors
+}
+
+/// Notify the syncs of this process in a digest instead of one by one, when the
+/// notifications have a `digest` period
+pub fn batch_into_digest() {
+    DIGEST_MODE.store(true, Ordering::Relaxed);
+}
+
+/// Send the notifications configured for a finished sync.
+///
+/// Failing to notify doesn't fail the sync, so errors are returned to be shown as warnings.
+pub async fn send(notifications: &Notifications, entry: &HistoryEntry) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    // Digests are checked after every sync, they skip the quiet ones themselves
+    if let Some(email) = &notifications.email
+        && let Some(digest) = email.digest
+        && let Err(e) = send_email_digest(email, digest, notifications.only_on_changes).await
+    {
+        errors.push(format!("Failed to email the digest: {}", e));
+    }
+
+    if DIGEST_MODE.load(Ordering::Relaxed) && notifications.digest_period().is_some() {
+        return errors;
+    }
+    if notifications.only_on_changes && !has_changes(entry) && !entry.has_failures() {
+        return errors;
+    }
+
+    errors.extend(deliver(notifications, entry, &summary(entry)).await);
+    errors
+}
+
+/// Send a notification to every configured channel, returning the errors
+async fn deliver(
+    notifications: &Notifications,
+    entry: &HistoryEntry,
+    summary: &str,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if notifications.desktop
+        && let Err(e) = notify_rust::Notification::new()
+            .appname("playsync")
+            .summary(&format!("playsync: {}", summary))
+            .body(&details(entry))
+            .show()
+    {
+        errors.push(format!("Failed to show the desktop notification: {}", e));
+    }
+
+    if let Some(webhook) = &notifications.webhook
+        && let Err(e) = post_webhook(webhook, entry, summary).await
+    {
+        errors.push(format!("Failed to call the webhook: {}", e));
+    }
+
+    if let Some(ntfy) = &notifications.ntfy
+        && let Err(e) = publish_ntfy(ntfy, entry, summary).await
+    {
+        errors.push(format!("Failed to publish to ntfy: {}", e));
+    }
+
+    if let Some(email) = notifications.email.as_ref().filter(|e| e.digest.is_none())
+        && let Err(e) = send_email(email, &format!("playsync: {}", summary), details(entry)).await
+    {
+        errors.push(format!("Failed to send the email: {}", e));
+    }
+
+    errors
+}
@@ -0,0 +1,225 @@
+use crate::exit::{self, Code};
+use crate::report::{self, RunReport};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How commands print their results, chosen with the global `--output` flag
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    Table,
+    /// JSON on stdout, for scripts and other programs
+    Json,
+    /// YAML on stdout
+    Yaml,
+    /// Human-readable output, with GitHub Actions annotations and a job summary for syncs
+    Gha,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Set the output format for the whole run
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Show more details, like why each video of the sources was skipped
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Answer yes to every confirmation instead of asking, for unattended runs
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Ask for a confirmation, or take it as given with `--yes`
+pub fn confirm(prompt: impl std::fmt::Display, initial_value: bool) -> std::io::Result<bool> {
+    if assume_yes() {
+        return Ok(true);
+    }
+    crate::ui::get().confirm(&prompt.to_string(), initial_value)
+}
+
+/// Fail instead of asking a question `--yes` can't answer, like a choice of playlists
+pub fn ensure_interactive(question: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if assume_yes() {
+        return Err(exit::failure(
+            Code::Usage,
+            format!("{} needs an answer, run it without --yes", question),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether results are printed for programs rather than people. Prompts, progress and
+/// messages still go to stderr, so stdout only holds the result.
+pub fn is_structured() -> bool {
+    matches!(
+        FORMAT.get().copied().unwrap_or_default(),
+        OutputFormat::Json | OutputFormat::Yaml
+    )
+}
+
+/// Whether syncs report to GitHub Actions, see [`github_actions`]
+pub fn is_github_actions() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Gha
+}
+
+/// Print the result of a command on stdout, in JSON or YAML.
+/// Nothing is printed with the `table` and `gha` formats, which show results as they go.
+pub fn print<T: Serialize + ?Sized>(value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    match FORMAT.get().copied().unwrap_or_default() {
+        OutputFormat::Table | OutputFormat::Gha => {}
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+    }
+
+    Ok(())
+}
+
+/// Escape the message of a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command, like its title
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Report a sync run to GitHub Actions: an `::error` annotation per playlist that failed,
+/// a `::warning` per video that failed, a `::notice` with the summary line, and the
+/// Markdown report appended to the job summary (`GITHUB_STEP_SUMMARY`)
+pub fn github_actions(run: &RunReport, summary: &str) -> Result<(), Box<dyn std::error::Error>> {
+    for playlist in &run.playlists {
+        if let Some(error) = &playlist.error {
+            println!(
+                "::error title={}::{}",
+                escape_property(&format!("Failed to sync {}", playlist.title)),
+                escape_data(error)
+            );
+        }
+        for video in &playlist.failed {
+            println!(
+                "::warning title={}::{}: {}",
+                escape_property(&format!("Failed to update {}", playlist.title)),
+                escape_data(&video.title),
+                escape_data(video.reason.as_deref().unwrap_or_default())
+            );
+        }
+    }
+    if let Some(error) = run
+        .error
+        .as_ref()
+        .filter(|_| run.playlists.iter().all(|p| p.error.is_none()))
+    {
+        println!("::error title=playsync::{}", escape_data(error));
+    }
+    println!("::notice title=playsync::{}", escape_data(summary));
+
+    if let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", report::to_markdown(run))?;
+    }
+
+    Ok(())
+}
+
+/// A configured playlist, as listed by `config --list`
+#[derive(Serialize)]
+pub struct PlaylistListing {
+    pub id: String,
+    pub title: String,
+    pub alias: Option<String>,
+    pub tags: Vec<String>,
+    /// The Google account owning the playlist, `None` for the main one
+    pub account: Option<String>,
+    pub sync_from: Vec<SourceListing>,
+    /// Number of items, read from YouTube with `--live`, otherwise at the last run
+    /// that counted them
+    pub item_count: Option<usize>,
+    /// Privacy status read with `--live`, or `unavailable` when the playlist can't be read
+    pub privacy: Option<String>,
+    /// When the playlist was last synced, not counting dry runs
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Outcome of the last sync: `ok`, `failed` or `aborted`
+    pub last_result: Option<String>,
+}
+
+/// A `sync_from` entry, with what it refers to
+#[derive(Serialize)]
+pub struct SourceListing {
+    pub entry: String,
+    pub description: String,
+}
+
+/// How far a playlist is behind its sources, as shown by `status`
+#[derive(Serialize)]
+pub struct PlaylistStatus {
+    pub id: String,
+    pub title: String,
+    /// Videos of the sources missing from the playlist
+    pub behind: usize,
+    pub item_count: Option<usize>,
+    /// Privacy status, or `unavailable` when the playlist can't be read
+    pub privacy: String,
+    /// Playlist sources that can't be read
+    pub unavailable_sources: Vec<String>,
+}
+
+/// Statistics of the playlists, as shown by `stats`
+#[derive(Serialize)]
+pub struct Stats {
+    pub playlists: Vec<PlaylistStats>,
+    pub overlaps: Vec<Overlap>,
+}
+
+#[derive(Serialize)]
+pub struct PlaylistStats {
+    pub id: String,
+    pub title: String,
+    pub items: usize,
+    /// Change in the number of items since the last recorded run
+    pub items_change: Option<i64>,
+    pub total_duration_seconds: u64,
+    pub unavailable: usize,
+    pub live: usize,
+    pub top_channels: Vec<ChannelCount>,
+}
+
+#[derive(Serialize)]
+pub struct ChannelCount {
+    pub channel: String,
+    pub videos: usize,
+}
+
+/// Number of videos two playlists share
+#[derive(Serialize)]
+pub struct Overlap {
+    pub a: String,
+    pub b: String,
+    pub shared: usize,
+}
@@ -0,0 +1,148 @@
+use crate::config::{InsertPosition, Playlist};
+use crate::sync::SyncReport;
+use crate::youtube::VideoInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Version of the plan format, so plans of other playsync versions aren't misread
+pub const PLAN_VERSION: u32 = 1;
+
+/// The changes a dry run would make, written with `sync --plan` and executed as they are
+/// with `sync --apply PLAN`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Plan {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// The target playlists with changes
+    pub playlists: Vec<PlannedPlaylist>,
+}
+
+/// The changes planned for a target playlist
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlannedPlaylist {
+    pub id: String,
+    pub title: String,
+
+    /// The account the playlist belongs to, if not the default one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+
+    /// Item IDs of the playlist when the plan was made, in order.
+    /// The plan is refused if the playlist changed since.
+    pub items: Vec<String>,
+
+    /// Insert the videos before the existing items instead of after them
+    #[serde(default)]
+    pub at_start: bool,
+
+    #[serde(default)]
+    pub add: Vec<PlannedVideo>,
+
+    #[serde(default)]
+    pub remove: Vec<PlannedVideo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlannedVideo {
+    pub video_id: String,
+    pub title: String,
+
+    /// The playlist item to remove
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub item_id: String,
+}
+
+impl PlannedVideo {
+    fn new(video: &VideoInfo) -> Self {
+        PlannedVideo {
+            video_id: video.video_id.clone(),
+            title: video.title.clone(),
+            item_id: video.item_id.clone(),
+        }
+    }
+
+    fn to_video(&self) -> VideoInfo {
+        VideoInfo {
+            video_id: self.video_id.clone(),
+            title: self.title.clone(),
+            channel: None,
+            item_id: self.item_id.clone(),
+            added_at: None,
+            published_at: None,
+            details: None,
+        }
+    }
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plan {
+    pub fn new() -> Self {
+        Plan {
+            version: PLAN_VERSION,
+            created_at: Utc::now(),
+            playlists: Vec::new(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read the plan {}: {}", path.display(), e))?;
+        let plan: Plan = serde_json::from_str(&content)
+            .map_err(|e| format!("{} is not a valid plan: {}", path.display(), e))?;
+
+        if plan.version != PLAN_VERSION {
+            return Err(format!(
+                "The plan {} has version {}, this playsync applies version {} plans",
+                path.display(),
+                plan.version,
+                PLAN_VERSION
+            )
+            .into());
+        }
+        Ok(plan)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl PlannedPlaylist {
+    /// The planned changes of a playlist, from the report of its dry run.
+    /// Returns `None` when the dry run found nothing to change.
+    pub fn from_dry_run(playlist: &Playlist, report: &SyncReport) -> Option<Self> {
+        if report.added.is_empty() && report.removed.is_empty() {
+            return None;
+        }
+
+        Some(PlannedPlaylist {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            account: playlist.account.clone(),
+            items: report.previous_item_ids.clone(),
+            at_start: playlist.position == Some(InsertPosition::Start),
+            add: report.added.iter().map(PlannedVideo::new).collect(),
+            remove: report.removed.iter().map(PlannedVideo::new).collect(),
+        })
+    }
+
+    /// Whether the playlist still has the items it had when the plan was made
+    pub fn is_current(&self, items: &[VideoInfo]) -> bool {
+        items.iter().map(|item| &item.item_id).eq(self.items.iter())
+    }
+
+    pub fn videos_to_add(&self) -> Vec<VideoInfo> {
+        self.add.iter().map(PlannedVideo::to_video).collect()
+    }
+
+    pub fn videos_to_remove(&self) -> Vec<VideoInfo> {
+        self.remove.iter().map(PlannedVideo::to_video).collect()
+    }
+}
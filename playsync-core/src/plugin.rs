@@ -0,0 +1,321 @@
+use crate::config;
+use crate::youtube::VideoInfo;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use wasmi::{
+    AsContextMut, Caller, Config as EngineConfig, Engine, Extern, Instance, Linker, Memory, Module,
+    Store, TypedFunc,
+};
+
+/// Version of the plugin ABI, which plugins return from their `playsync_abi_version` export
+pub const ABI_VERSION: i32 = 1;
+
+/// Instructions a plugin may run per call, so a stuck plugin fails instead of hanging the sync
+const FUEL_PER_CALL: u64 = 1_000_000_000;
+
+/// Exports of the plugins providing videos and filtering them
+const FETCH_EXPORT: &str = "playsync_fetch";
+const FILTER_EXPORT: &str = "playsync_filter";
+
+/// Directory the plugins are discovered in, as `<name>.wasm` files, next to the configuration
+pub fn plugins_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(config::config_dir()?.join("plugins"))
+}
+
+/// Names of the plugins in the plugins directory, sorted
+pub fn discover() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let dir = plugins_dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+/// A video returned by a provider plugin
+#[derive(Deserialize)]
+struct PluginVideo {
+    video_id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct FetchResponse {
+    videos: Vec<PluginVideo>,
+}
+
+#[derive(Deserialize)]
+struct FilterResponse {
+    keep: HashSet<String>,
+}
+
+/// A WebAssembly plugin, loaded from the plugins directory.
+///
+/// Plugins are core WebAssembly modules exporting their `memory`, `playsync_abi_version`
+/// and `playsync_alloc(len) -> ptr`, and at least one of `playsync_fetch` (a provider of
+/// videos) and `playsync_filter`. Both take a JSON request written in the plugin memory
+/// and return the location of their JSON response, packed as `ptr << 32 | len`.
+pub struct Plugin {
+    name: String,
+    store: Store<()>,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+}
+
+impl Plugin {
+    pub fn load(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = plugins_dir()?.join(format!("{}.wasm", name));
+        let wasm = std::fs::read(&path).map_err(|e| {
+            format!(
+                "Failed to read plugin '{}' ({}): {}",
+                name,
+                path.display(),
+                e
+            )
+        })?;
+
+        let mut engine_config = EngineConfig::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, &wasm[..])
+            .map_err(|e| format!("Plugin '{}' is not a valid module: {}", name, e))?;
+
+        let mut linker = Linker::<()>::new(&engine);
+        linker.func_wrap("playsync", "log", host_log)?;
+        linker.func_wrap("playsync", "http_get", host_http_get)?;
+
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| format!("Failed to start plugin '{}': {}", name, e))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| format!("Plugin '{}' doesn't export its memory", name))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "playsync_alloc")?;
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "playsync_abi_version")?
+            .call(&mut store, ())?;
+        if version != ABI_VERSION {
+            return Err(format!(
+                "Plugin '{}' was built for ABI version {}, playsync supports version {}",
+                name, version, ABI_VERSION
+            )
+            .into());
+        }
+
+        Ok(Plugin {
+            name: name.to_string(),
+            store,
+            instance,
+            memory,
+            alloc,
+        })
+    }
+
+    /// Whether the plugin provides videos for `plugin:` sources
+    pub fn is_provider(&self) -> bool {
+        self.instance.get_func(&self.store, FETCH_EXPORT).is_some()
+    }
+
+    /// Whether the plugin filters the videos added to playlists
+    pub fn is_filter(&self) -> bool {
+        self.instance.get_func(&self.store, FILTER_EXPORT).is_some()
+    }
+
+    /// Call an export with a JSON request, returning its JSON response.
+    /// A response with an `error` field is turned into an error.
+    fn call(&mut self, export: &str, request: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&self.store, export)
+            .map_err(|_| format!("Plugin '{}' doesn't export {}", self.name, export))?;
+
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| e.to_string())?;
+        let input = serde_json::to_vec(request)?;
+        let ptr = write_guest(&mut self.store, &self.alloc, &self.memory, &input)?;
+        let packed = func
+            .call(&mut self.store, (ptr, input.len() as i32))
+            .map_err(|e| format!("Plugin '{}' failed: {}", self.name, e))?;
+
+        let (ptr, len) = unpack(packed);
+        let mut output = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut output)
+            .map_err(|e| e.to_string())?;
+        let response: Value = serde_json::from_slice(&output)
+            .map_err(|e| format!("Plugin '{}' returned invalid JSON: {}", self.name, e))?;
+
+        if let Some(error) = response.get("error").and_then(Value::as_str) {
+            return Err(format!("Plugin '{}': {}", self.name, error).into());
+        }
+        Ok(response)
+    }
+}
+
+/// Fetch the videos of a `plugin:NAME:ARGUMENT` source.
+///
+/// The plugin gets `{"argument": ...}` and returns `{"videos": [...]}`, each with a
+/// `video_id` and optionally a `title`, `channel` and `published_at`.
+pub fn fetch(name: &str, argument: &str) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+    let mut plugin = Plugin::load(name)?;
+    let response: FetchResponse =
+        serde_json::from_value(plugin.call(FETCH_EXPORT, &json!({ "argument": argument }))?)?;
+
+    Ok(response
+        .videos
+        .into_iter()
+        .map(|video| VideoInfo {
+            title: if video.title.is_empty() {
+                video.video_id.clone()
+            } else {
+                video.title
+            },
+            video_id: video.video_id,
+            channel: video.channel,
+            item_id: String::new(),
+            added_at: None,
+            published_at: video.published_at,
+            details: None,
+        })
+        .collect())
+}
+
+/// Keep the videos a filter plugin accepts, in their order.
+///
+/// The plugin gets `{"playlist": ..., "videos": [...]}`, each with its `video_id`, `title`,
+/// `channel`, `duration` (in seconds), `published_at` and `source`, and returns
+/// `{"keep": [...]}` with the IDs of the videos to add.
+pub fn filter(
+    name: &str,
+    playlist_id: &str,
+    videos: Vec<VideoInfo>,
+    sources: &HashMap<String, String>,
+) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+    if videos.is_empty() {
+        return Ok(videos);
+    }
+
+    let items: Vec<Value> = videos
+        .iter()
+        .map(|video| {
+            let details = video.details.as_ref();
+            json!({
+                "video_id": video.video_id,
+                "title": video.title,
+                "channel": video.channel,
+                "duration": details.and_then(|d| d.duration).map(|d| d.as_secs()),
+                "published_at": video.published_at.or(details.and_then(|d| d.published_at)),
+                "source": sources.get(&video.video_id),
+            })
+        })
+        .collect();
+
+    let mut plugin = Plugin::load(name)?;
+    let response: FilterResponse = serde_json::from_value(plugin.call(
+        FILTER_EXPORT,
+        &json!({ "playlist": playlist_id, "videos": items }),
+    )?)?;
+
+    Ok(videos
+        .into_iter()
+        .filter(|video| response.keep.contains(&video.video_id))
+        .collect())
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+/// Copy bytes into memory allocated by the plugin, returning their address
+fn write_guest(
+    mut context: impl AsContextMut,
+    alloc: &TypedFunc<i32, i32>,
+    memory: &Memory,
+    data: &[u8],
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let ptr = alloc.call(&mut context, data.len() as i32)?;
+    memory
+        .write(&mut context, ptr as usize, data)
+        .map_err(|e| e.to_string())?;
+    Ok(ptr)
+}
+
+fn read_guest(caller: &Caller<'_, ()>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let mut buffer = vec![0; len as usize];
+    memory.read(caller, ptr as usize, &mut buffer).ok()?;
+    String::from_utf8(buffer).ok()
+}
+
+/// `playsync.log(ptr, len)`: show a message of the plugin
+fn host_log(caller: Caller<'_, ()>, ptr: i32, len: i32) {
+    if let Some(message) = read_guest(&caller, ptr, len) {
+        let _ = crate::ui::log::info(message);
+    }
+}
+
+/// `playsync.http_get(ptr, len) -> packed`: fetch a URL, returning the body written in the
+/// plugin memory, or 0 when the request failed
+fn host_http_get(mut caller: Caller<'_, ()>, ptr: i32, len: i32) -> i64 {
+    let Some(url) = read_guest(&caller, ptr, len) else {
+        return 0;
+    };
+
+    // Plugins run synchronously, so the request gets its own runtime on another thread
+    let body = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .ok()?;
+                runtime.block_on(async {
+                    let response = reqwest::get(&url).await.ok()?.error_for_status().ok()?;
+                    response.bytes().await.ok()
+                })
+            })
+            .join()
+            .ok()
+            .flatten()
+    });
+    let Some(body) = body else {
+        let _ = crate::ui::log::warning(format!("Plugin request to {} failed", url));
+        return 0;
+    };
+
+    let memory = caller.get_export("memory").and_then(Extern::into_memory);
+    let alloc = caller
+        .get_export("playsync_alloc")
+        .and_then(Extern::into_func)
+        .and_then(|func| func.typed::<i32, i32>(&caller).ok());
+    let (Some(memory), Some(alloc)) = (memory, alloc) else {
+        return 0;
+    };
+
+    match write_guest(&mut caller, &alloc, &memory, &body) {
+        Ok(ptr) => ((ptr as u32 as i64) << 32) | body.len() as u32 as i64,
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,42 @@
+use serde::Serialize;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+/// Events of the syncs running in this process, streamed by `playsync serve`
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    SyncStarted {
+        dry_run: bool,
+    },
+    PlaylistStarted {
+        id: String,
+        title: String,
+    },
+    PlaylistSynced {
+        id: String,
+        title: String,
+        added: usize,
+        removed: usize,
+        failed: usize,
+    },
+    SyncFinished {
+        error: Option<String>,
+    },
+}
+
+static EVENTS: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+
+fn sender() -> &'static broadcast::Sender<Event> {
+    EVENTS.get_or_init(|| broadcast::channel(100).0)
+}
+
+/// Send an event to everyone following the progress, if anyone is
+pub fn publish(event: Event) {
+    let _ = sender().send(event);
+}
+
+/// Follow the events published from now on
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    sender().subscribe()
+}
@@ -0,0 +1,769 @@
+use crate::source::{self, MusicServices, Source};
+use crate::youtube::{
+    PlaylistMetadata, PlaylistPages, PlaylistState, SearchQuery, VideoDetails, VideoInfo,
+    YouTubeClient,
+};
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use futures::future::LocalBoxFuture;
+use std::collections::HashMap;
+use std::future::ready;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// What a [`Provider`] call returns
+pub type ProviderFuture<'a, T> = LocalBoxFuture<'a, Result<T, Box<dyn std::error::Error>>>;
+
+/// Where the sync engine reads and writes playlists.
+///
+/// The engine takes a `&dyn Provider`: [`sync`](crate::sync), the runs of
+/// [`run`](crate::run) and the commands built on them. A [`YouTubeClient`] is the real
+/// thing, and an [`InMemoryProvider`] runs them without YouTube, e.g. in tests.
+///
+/// Calls return a [`LocalBoxFuture`], which isn't `Send`, so neither are the futures of
+/// the engine: they can't be given to `tokio::spawn`. Run them on the current task, or
+/// on a [`LocalSet`](tokio::task::LocalSet) with `spawn_local`, like `playsync serve`
+/// does for the syncs it starts.
+pub trait Provider {
+    /// Whether a playlist exists and can be read
+    fn playlist_exists<'a>(&'a self, playlist_id: &'a str) -> ProviderFuture<'a, bool>;
+
+    /// Create a playlist and return its ID
+    fn create_playlist<'a>(
+        &'a self,
+        title: &'a str,
+        description: &'a str,
+        privacy: &'a str,
+    ) -> ProviderFuture<'a, String>;
+
+    /// The items of a playlist, in playlist order
+    fn get_playlist_items<'a>(&'a self, playlist_id: &'a str)
+    -> ProviderFuture<'a, Vec<VideoInfo>>;
+
+    /// The items of a playlist, showing the progress under `label` if the provider can
+    fn get_playlist_items_with_progress<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        _label: &'a str,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        self.get_playlist_items(playlist_id)
+    }
+
+    /// Read the items of a playlist a page at a time, showing the progress under
+    /// `progress` if the provider can
+    fn playlist_pages<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        progress: Option<&'a str>,
+    ) -> ProviderFuture<'a, Box<dyn Pages + 'a>>;
+
+    /// The videos of a `sync_from` source that isn't a playlist, like `subscriptions` or
+    /// a search, oldest first
+    fn source_videos<'a>(&'a self, source_id: &'a str) -> ProviderFuture<'a, Vec<VideoInfo>>;
+
+    /// Attach their details to the videos that don't have them yet
+    fn enrich_videos<'a>(&'a self, videos: &'a mut [VideoInfo]) -> ProviderFuture<'a, ()>;
+
+    /// Add a video to a playlist, at `position` or at the end
+    fn add_video_to_playlist<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video_id: &'a str,
+        position: Option<u32>,
+    ) -> ProviderFuture<'a, ()>;
+
+    /// Remove an item from its playlist
+    fn remove_playlist_item<'a>(&'a self, item_id: &'a str) -> ProviderFuture<'a, ()>;
+
+    /// Remove items from their playlists, returning how many were removed
+    fn remove_playlist_items<'a>(&'a self, item_ids: &'a [String]) -> ProviderFuture<'a, usize>;
+
+    /// The title and description of playlists, leaving out the ones that can't be read
+    fn get_playlists_metadata<'a>(
+        &'a self,
+        playlist_ids: &'a [&'a str],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistMetadata>>;
+
+    /// Update the title and description of a playlist
+    fn update_playlist_metadata<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        metadata: &'a PlaylistMetadata,
+    ) -> ProviderFuture<'a, ()>;
+
+    /// The title of a playlist
+    fn get_playlist_title<'a>(&'a self, playlist_id: &'a str) -> ProviderFuture<'a, String> {
+        async move {
+            let ids = [playlist_id];
+            self.get_playlists_metadata(&ids)
+                .await?
+                .remove(playlist_id)
+                .map(|metadata| metadata.title)
+                .ok_or_else(|| format!("Playlist {} not found", playlist_id).into())
+        }
+        .boxed_local()
+    }
+
+    /// The item count and privacy status of playlists, leaving out the ones that can't
+    /// be read
+    fn get_playlist_states<'a>(
+        &'a self,
+        playlist_ids: &'a [String],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistState>>;
+
+    /// The videos with these IDs, leaving out the ones that don't exist
+    fn get_videos<'a>(&'a self, video_ids: &'a [String]) -> ProviderFuture<'a, Vec<VideoInfo>>;
+
+    /// Search for videos, most relevant first unless the query orders them otherwise
+    fn search_videos<'a>(&'a self, search: &'a SearchQuery) -> ProviderFuture<'a, Vec<VideoInfo>>;
+
+    /// The uploads of the subscribed channels published on or after `since`, in no
+    /// particular order
+    fn subscription_uploads<'a>(
+        &'a self,
+        since: DateTime<Utc>,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>>;
+
+    /// Move an item of a playlist to a zero-based position, which the other items make
+    /// room for
+    fn move_playlist_item<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video: &'a VideoInfo,
+        position: u32,
+    ) -> ProviderFuture<'a, ()>;
+
+    /// A provider for the playlists of another account, signed in with the OAuth2
+    /// credentials at `oauth2_json`
+    fn for_account<'a>(
+        &'a self,
+        _oauth2_json: &'a str,
+        account: &'a str,
+    ) -> ProviderFuture<'a, Box<dyn Provider>> {
+        ready(Err(format!(
+            "Can't sign in to the account {} here",
+            account
+        )
+        .into()))
+        .boxed_local()
+    }
+
+    /// The API quota used so far, none if the provider has no quota
+    fn quota_used(&self) -> u64 {
+        0
+    }
+
+    /// Whether playlists are only read from a local copy, which can't be changed
+    fn is_offline(&self) -> bool {
+        false
+    }
+
+    /// Read music sources with these credentials
+    fn use_music_services(&self, _services: MusicServices) {}
+
+    /// The credentials music sources are read with
+    fn music_services(&self) -> MusicServices {
+        MusicServices::default()
+    }
+}
+
+/// Items of a playlist read a page at a time, see [`Provider::playlist_pages`]
+pub trait Pages {
+    /// The next page of items, `None` once they were all read
+    fn next_page(&mut self) -> ProviderFuture<'_, Option<Vec<VideoInfo>>>;
+}
+
+// The inherent methods of the same names take priority over the trait's in these calls
+impl Provider for YouTubeClient {
+    fn playlist_exists<'a>(&'a self, playlist_id: &'a str) -> ProviderFuture<'a, bool> {
+        self.playlist_exists(playlist_id).boxed_local()
+    }
+
+    fn create_playlist<'a>(
+        &'a self,
+        title: &'a str,
+        description: &'a str,
+        privacy: &'a str,
+    ) -> ProviderFuture<'a, String> {
+        self.create_playlist(title, description, privacy)
+            .boxed_local()
+    }
+
+    fn get_playlist_items<'a>(
+        &'a self,
+        playlist_id: &'a str,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        self.get_playlist_items(playlist_id).boxed_local()
+    }
+
+    fn get_playlist_items_with_progress<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        label: &'a str,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        self.get_playlist_items_with_progress(playlist_id, label)
+            .boxed_local()
+    }
+
+    fn playlist_pages<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        progress: Option<&'a str>,
+    ) -> ProviderFuture<'a, Box<dyn Pages + 'a>> {
+        async move {
+            let pages = self.playlist_pages(playlist_id, progress).await?;
+            Ok(Box::new(pages) as Box<dyn Pages>)
+        }
+        .boxed_local()
+    }
+
+    fn source_videos<'a>(&'a self, source_id: &'a str) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        async move { source::fetch_videos(self, &Source::parse(source_id)).await }.boxed_local()
+    }
+
+    fn enrich_videos<'a>(&'a self, videos: &'a mut [VideoInfo]) -> ProviderFuture<'a, ()> {
+        self.enrich_videos(videos).boxed_local()
+    }
+
+    fn add_video_to_playlist<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video_id: &'a str,
+        position: Option<u32>,
+    ) -> ProviderFuture<'a, ()> {
+        self.add_video_to_playlist(playlist_id, video_id, position)
+            .boxed_local()
+    }
+
+    fn remove_playlist_item<'a>(&'a self, item_id: &'a str) -> ProviderFuture<'a, ()> {
+        self.remove_playlist_item(item_id).boxed_local()
+    }
+
+    fn remove_playlist_items<'a>(&'a self, item_ids: &'a [String]) -> ProviderFuture<'a, usize> {
+        self.remove_playlist_items(item_ids).boxed_local()
+    }
+
+    fn get_playlists_metadata<'a>(
+        &'a self,
+        playlist_ids: &'a [&'a str],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistMetadata>> {
+        self.get_playlists_metadata(playlist_ids).boxed_local()
+    }
+
+    fn update_playlist_metadata<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        metadata: &'a PlaylistMetadata,
+    ) -> ProviderFuture<'a, ()> {
+        self.update_playlist_metadata(playlist_id, metadata)
+            .boxed_local()
+    }
+
+    fn get_playlist_title<'a>(&'a self, playlist_id: &'a str) -> ProviderFuture<'a, String> {
+        self.get_playlist_title(playlist_id).boxed_local()
+    }
+
+    fn get_playlist_states<'a>(
+        &'a self,
+        playlist_ids: &'a [String],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistState>> {
+        self.get_playlist_states(playlist_ids).boxed_local()
+    }
+
+    fn get_videos<'a>(&'a self, video_ids: &'a [String]) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        self.get_videos(video_ids).boxed_local()
+    }
+
+    fn search_videos<'a>(&'a self, search: &'a SearchQuery) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        self.search_videos(search).boxed_local()
+    }
+
+    fn subscription_uploads<'a>(
+        &'a self,
+        since: DateTime<Utc>,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        async move {
+            let channel_ids = self.get_subscribed_channels().await?;
+            let uploads_ids = self.get_uploads_playlists(&channel_ids).await?;
+
+            Ok(self
+                .concurrently(&uploads_ids, |uploads_id| {
+                    self.get_recent_playlist_items(uploads_id, since)
+                })
+                .await?
+                .into_iter()
+                .flatten()
+                .collect())
+        }
+        .boxed_local()
+    }
+
+    fn move_playlist_item<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video: &'a VideoInfo,
+        position: u32,
+    ) -> ProviderFuture<'a, ()> {
+        self.move_playlist_item(playlist_id, video, position)
+            .boxed_local()
+    }
+
+    fn for_account<'a>(
+        &'a self,
+        oauth2_json: &'a str,
+        account: &'a str,
+    ) -> ProviderFuture<'a, Box<dyn Provider>> {
+        async move {
+            let client = YouTubeClient::new_for_account(oauth2_json, account).await?;
+            Ok(Box::new(client) as Box<dyn Provider>)
+        }
+        .boxed_local()
+    }
+
+    fn quota_used(&self) -> u64 {
+        self.quota_used()
+    }
+
+    fn is_offline(&self) -> bool {
+        self.is_offline()
+    }
+
+    fn use_music_services(&self, services: MusicServices) {
+        self.use_music_services(services);
+    }
+
+    fn music_services(&self) -> MusicServices {
+        self.music_services()
+    }
+}
+
+impl Pages for PlaylistPages<'_> {
+    fn next_page(&mut self) -> ProviderFuture<'_, Option<Vec<VideoInfo>>> {
+        self.next_page().boxed_local()
+    }
+}
+
+/// Number of items per page of [`InMemoryProvider::playlist_pages`], like on YouTube
+const PAGE_SIZE: usize = 50;
+
+/// A [`Provider`] keeping its playlists in memory, to run the sync engine without
+/// YouTube, e.g. in tests. Playlists and sources are set up with the `with_` methods,
+/// and read back after a sync with [`playlist`](Self::playlist).
+///
+/// ```
+/// use playsync_core::config::Playlist;
+/// use playsync_core::provider::InMemoryProvider;
+/// use playsync_core::sync;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let provider = InMemoryProvider::new()
+///     .with_playlist("target", "Target", vec![InMemoryProvider::video("a", "A")])
+///     .with_playlist(
+///         "source",
+///         "Source",
+///         vec![InMemoryProvider::video("a", "A"), InMemoryProvider::video("b", "B")],
+///     );
+/// let target = Playlist {
+///     id: "target".to_string(),
+///     title: "Target".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let report = sync::sync_playlist(&provider, &target, &["source".to_string()], false)
+///     .await
+///     .unwrap();
+///
+/// assert_eq!(report.added.len(), 1);
+/// let videos: Vec<String> = provider
+///     .playlist("target")
+///     .unwrap()
+///     .into_iter()
+///     .map(|video| video.video_id)
+///     .collect();
+/// assert_eq!(videos, ["a", "b"]);
+/// # });
+/// ```
+#[derive(Default)]
+pub struct InMemoryProvider {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    playlists: HashMap<String, MemoryPlaylist>,
+    /// Videos of the sources that aren't playlists, by `sync_from` entry
+    sources: HashMap<String, Vec<VideoInfo>>,
+    /// Every video given, so videos added by ID get their title and channel
+    videos: HashMap<String, VideoInfo>,
+    details: HashMap<String, VideoDetails>,
+    /// Number of IDs handed out, to make new ones
+    next_id: usize,
+}
+
+struct MemoryPlaylist {
+    metadata: PlaylistMetadata,
+    items: Vec<VideoInfo>,
+}
+
+impl State {
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+
+    fn remember(&mut self, video: &VideoInfo) {
+        self.videos
+            .entry(video.video_id.clone())
+            .or_insert_with(|| VideoInfo {
+                item_id: String::new(),
+                added_at: None,
+                ..video.clone()
+            });
+    }
+
+    fn remove_item(&mut self, item_id: &str) -> bool {
+        self.playlists.values_mut().any(|playlist| {
+            let count = playlist.items.len();
+            playlist.items.retain(|item| item.item_id != item_id);
+            playlist.items.len() < count
+        })
+    }
+}
+
+impl InMemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A video with just an ID and a title, to set up playlists and sources
+    pub fn video(video_id: &str, title: &str) -> VideoInfo {
+        VideoInfo {
+            video_id: video_id.to_string(),
+            title: title.to_string(),
+            channel: None,
+            item_id: String::new(),
+            added_at: None,
+            published_at: None,
+            details: None,
+        }
+    }
+
+    /// Add a playlist holding these videos. Videos without an item ID are given one.
+    pub fn with_playlist(self, playlist_id: &str, title: &str, videos: Vec<VideoInfo>) -> Self {
+        {
+            let mut state = self.state();
+            let items = videos
+                .into_iter()
+                .map(|mut video| {
+                    state.remember(&video);
+                    if video.item_id.is_empty() {
+                        video.item_id = state.new_id("item");
+                    }
+                    video
+                })
+                .collect();
+            state.playlists.insert(
+                playlist_id.to_string(),
+                MemoryPlaylist {
+                    metadata: PlaylistMetadata {
+                        title: title.to_string(),
+                        description: String::new(),
+                    },
+                    items,
+                },
+            );
+        }
+        self
+    }
+
+    /// Give the videos of a `sync_from` entry that isn't a playlist, like
+    /// `subscriptions` or `search:...`
+    pub fn with_source(self, source_id: &str, videos: Vec<VideoInfo>) -> Self {
+        {
+            let mut state = self.state();
+            for video in &videos {
+                state.remember(video);
+            }
+            state.sources.insert(source_id.to_string(), videos);
+        }
+        self
+    }
+
+    /// Give the details of a video, attached by [`Provider::enrich_videos`]. Videos
+    /// without details get ones with their title and channel, and no duration.
+    pub fn with_details(self, video_id: &str, details: VideoDetails) -> Self {
+        self.state().details.insert(video_id.to_string(), details);
+        self
+    }
+
+    /// The items of a playlist, `None` if there is no such playlist
+    pub fn playlist(&self, playlist_id: &str) -> Option<Vec<VideoInfo>> {
+        let state = self.state();
+        Some(state.playlists.get(playlist_id)?.items.clone())
+    }
+
+    /// The title and description of a playlist, `None` if there is no such playlist
+    pub fn metadata(&self, playlist_id: &str) -> Option<PlaylistMetadata> {
+        let state = self.state();
+        Some(state.playlists.get(playlist_id)?.metadata.clone())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The state stays consistent even if a caller panicked
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn items(&self, playlist_id: &str) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        self.playlist(playlist_id)
+            .ok_or_else(|| format!("Playlist {} not found", playlist_id).into())
+    }
+}
+
+impl Provider for InMemoryProvider {
+    fn playlist_exists<'a>(&'a self, playlist_id: &'a str) -> ProviderFuture<'a, bool> {
+        let exists = self.state().playlists.contains_key(playlist_id);
+        ready(Ok(exists)).boxed_local()
+    }
+
+    fn create_playlist<'a>(
+        &'a self,
+        title: &'a str,
+        description: &'a str,
+        _privacy: &'a str,
+    ) -> ProviderFuture<'a, String> {
+        let mut state = self.state();
+        let playlist_id = state.new_id("playlist");
+        state.playlists.insert(
+            playlist_id.clone(),
+            MemoryPlaylist {
+                metadata: PlaylistMetadata {
+                    title: title.to_string(),
+                    description: description.to_string(),
+                },
+                items: Vec::new(),
+            },
+        );
+        ready(Ok(playlist_id)).boxed_local()
+    }
+
+    fn get_playlist_items<'a>(
+        &'a self,
+        playlist_id: &'a str,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        ready(self.items(playlist_id)).boxed_local()
+    }
+
+    fn playlist_pages<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        _progress: Option<&'a str>,
+    ) -> ProviderFuture<'a, Box<dyn Pages + 'a>> {
+        let pages = self.items(playlist_id).map(|items| {
+            let pages: Vec<Vec<VideoInfo>> =
+                items.chunks(PAGE_SIZE).map(<[VideoInfo]>::to_vec).collect();
+            Box::new(MemoryPages(pages.into_iter())) as Box<dyn Pages>
+        });
+        ready(pages).boxed_local()
+    }
+
+    fn source_videos<'a>(&'a self, source_id: &'a str) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        let videos = match Source::parse(source_id).playlist_id() {
+            Some(playlist_id) => self.items(playlist_id),
+            None => self
+                .state()
+                .sources
+                .get(source_id)
+                .cloned()
+                .ok_or_else(|| format!("Source {} not found", source_id).into()),
+        };
+        ready(videos).boxed_local()
+    }
+
+    fn enrich_videos<'a>(&'a self, videos: &'a mut [VideoInfo]) -> ProviderFuture<'a, ()> {
+        let state = self.state();
+        for video in videos.iter_mut().filter(|video| video.details.is_none()) {
+            video.details = Some(state.details.get(&video.video_id).cloned().unwrap_or_else(
+                || VideoDetails {
+                    title: video.title.clone(),
+                    channel: video.channel.clone(),
+                    published_at: video.published_at,
+                    ..Default::default()
+                },
+            ));
+        }
+        ready(Ok(())).boxed_local()
+    }
+
+    fn add_video_to_playlist<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video_id: &'a str,
+        position: Option<u32>,
+    ) -> ProviderFuture<'a, ()> {
+        let mut state = self.state();
+        let mut video = state
+            .videos
+            .get(video_id)
+            .cloned()
+            .unwrap_or_else(|| Self::video(video_id, video_id));
+        video.item_id = state.new_id("item");
+        video.added_at = Some(Utc::now());
+
+        let result = match state.playlists.get_mut(playlist_id) {
+            Some(playlist) => {
+                let position = position.map_or(playlist.items.len(), |position| {
+                    (position as usize).min(playlist.items.len())
+                });
+                playlist.items.insert(position, video);
+                Ok(())
+            }
+            None => Err(format!("Playlist {} not found", playlist_id).into()),
+        };
+        ready(result).boxed_local()
+    }
+
+    fn remove_playlist_item<'a>(&'a self, item_id: &'a str) -> ProviderFuture<'a, ()> {
+        let result = if self.state().remove_item(item_id) {
+            Ok(())
+        } else {
+            Err(format!("Playlist item {} not found", item_id).into())
+        };
+        ready(result).boxed_local()
+    }
+
+    fn remove_playlist_items<'a>(&'a self, item_ids: &'a [String]) -> ProviderFuture<'a, usize> {
+        let mut state = self.state();
+        let removed = item_ids
+            .iter()
+            .filter(|item_id| state.remove_item(item_id))
+            .count();
+        ready(Ok(removed)).boxed_local()
+    }
+
+    fn get_playlists_metadata<'a>(
+        &'a self,
+        playlist_ids: &'a [&'a str],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistMetadata>> {
+        let state = self.state();
+        let metadata = playlist_ids
+            .iter()
+            .filter_map(|id| {
+                let playlist = state.playlists.get(*id)?;
+                Some((id.to_string(), playlist.metadata.clone()))
+            })
+            .collect();
+        ready(Ok(metadata)).boxed_local()
+    }
+
+    fn update_playlist_metadata<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        metadata: &'a PlaylistMetadata,
+    ) -> ProviderFuture<'a, ()> {
+        let result = match self.state().playlists.get_mut(playlist_id) {
+            Some(playlist) => {
+                playlist.metadata = metadata.clone();
+                Ok(())
+            }
+            None => Err(format!("Playlist {} not found", playlist_id).into()),
+        };
+        ready(result).boxed_local()
+    }
+
+    fn get_playlist_states<'a>(
+        &'a self,
+        playlist_ids: &'a [String],
+    ) -> ProviderFuture<'a, HashMap<String, PlaylistState>> {
+        let state = self.state();
+        let states = playlist_ids
+            .iter()
+            .filter_map(|id| {
+                let playlist = state.playlists.get(id)?;
+                let state = PlaylistState {
+                    item_count: playlist.items.len() as u32,
+                    privacy: "private".to_string(),
+                };
+                Some((id.clone(), state))
+            })
+            .collect();
+        ready(Ok(states)).boxed_local()
+    }
+
+    fn get_videos<'a>(&'a self, video_ids: &'a [String]) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        let state = self.state();
+        let videos = video_ids
+            .iter()
+            .filter_map(|id| state.videos.get(id).cloned())
+            .collect();
+        ready(Ok(videos)).boxed_local()
+    }
+
+    /// Videos given for any playlist or source whose title holds the search terms
+    fn search_videos<'a>(&'a self, search: &'a SearchQuery) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        let terms = search.query.to_lowercase();
+        let mut videos: Vec<VideoInfo> = self
+            .state()
+            .videos
+            .values()
+            .filter(|video| video.title.to_lowercase().contains(&terms))
+            .cloned()
+            .collect();
+        videos.sort_by(|a, b| a.video_id.cmp(&b.video_id));
+        videos.truncate(search.max_results as usize);
+        ready(Ok(videos)).boxed_local()
+    }
+
+    /// Videos given for the `subscriptions` sources, published on or after `since` or
+    /// without a date
+    fn subscription_uploads<'a>(
+        &'a self,
+        since: DateTime<Utc>,
+    ) -> ProviderFuture<'a, Vec<VideoInfo>> {
+        let videos = self
+            .state()
+            .sources
+            .iter()
+            .filter(|(source_id, _)| {
+                matches!(Source::parse(source_id), Source::Subscriptions { .. })
+            })
+            .flat_map(|(_, videos)| videos)
+            .filter(|video| video.published_at.is_none_or(|date| date >= since))
+            .cloned()
+            .collect();
+        ready(Ok(videos)).boxed_local()
+    }
+
+    fn move_playlist_item<'a>(
+        &'a self,
+        playlist_id: &'a str,
+        video: &'a VideoInfo,
+        position: u32,
+    ) -> ProviderFuture<'a, ()> {
+        let mut state = self.state();
+        let result = match state.playlists.get_mut(playlist_id) {
+            Some(playlist) => match playlist
+                .items
+                .iter()
+                .position(|item| item.item_id == video.item_id)
+            {
+                Some(index) => {
+                    let item = playlist.items.remove(index);
+                    let position = (position as usize).min(playlist.items.len());
+                    playlist.items.insert(position, item);
+                    Ok(())
+                }
+                None => Err(format!("Playlist item {} not found", video.item_id).into()),
+            },
+            None => Err(format!("Playlist {} not found", playlist_id).into()),
+        };
+        ready(result).boxed_local()
+    }
+}
+
+/// Pages of an [`InMemoryProvider`] playlist, copied when they were asked for
+struct MemoryPages(std::vec::IntoIter<Vec<VideoInfo>>);
+
+impl Pages for MemoryPages {
+    fn next_page(&mut self) -> ProviderFuture<'_, Option<Vec<VideoInfo>>> {
+        ready(Ok(self.0.next())).boxed_local()
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Requests per second sent to YouTube when `rate_limit.requests_per_second` is unset
+const DEFAULT_REQUESTS_PER_SECOND: u32 = 10;
+/// Writes per minute sent to YouTube when `rate_limit.writes_per_minute` is unset
+const DEFAULT_WRITES_PER_MINUTE: u32 = 120;
+
+/// How fast requests are sent to YouTube, in the `[rate_limit]` section
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Requests of any kind per second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+
+    /// Requests changing playlists (adding, removing, moving videos, editing playlists)
+    /// per minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub writes_per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn is_empty(&self) -> bool {
+        self.requests_per_second.is_none() && self.writes_per_minute.is_none()
+    }
+}
+
+/// Token buckets shared by every YouTube client and task of the process, so requests
+/// made in parallel can't go over the limits together
+struct Limiter {
+    requests: TokenBucket,
+    writes: TokenBucket,
+}
+
+static LIMITER: OnceLock<Limiter> = OnceLock::new();
+
+/// Apply the rate limits of the configuration for the rest of the run. Without it, or
+/// after the first request, the defaults are used.
+pub fn configure(settings: &RateLimit) {
+    let _ = LIMITER.set(Limiter::new(settings));
+}
+
+impl Limiter {
+    fn new(settings: &RateLimit) -> Self {
+        // A zero limit would block every request, `config validate` reports it
+        let requests_per_second = settings
+            .requests_per_second
+            .filter(|limit| *limit > 0)
+            .unwrap_or(DEFAULT_REQUESTS_PER_SECOND);
+        let writes_per_minute = settings
+            .writes_per_minute
+            .filter(|limit| *limit > 0)
+            .unwrap_or(DEFAULT_WRITES_PER_MINUTE);
+
+        Limiter {
+            requests: TokenBucket::new(requests_per_second as f64),
+            writes: TokenBucket::new(writes_per_minute as f64 / 60.0),
+        }
+    }
+}
+
+fn limiter() -> &'static Limiter {
+    LIMITER.get_or_init(|| Limiter::new(&RateLimit::default()))
+}
+
+/// Wait until a request may be sent
+pub async fn request() {
+    limiter().requests.take().await;
+}
+
+/// Wait until a request changing a playlist may be sent. It's a request too, so
+/// [`request`] must be awaited as well.
+pub async fn write() {
+    limiter().writes.take().await;
+}
+
+/// Tokens added at a steady rate up to a burst of a second's worth, each request
+/// taking one. Waiting requests are served in turn.
+struct TokenBucket {
+    per_second: f64,
+    capacity: f64,
+    state: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_second: f64) -> Self {
+        let capacity = per_second.max(1.0);
+        TokenBucket {
+            per_second,
+            capacity,
+            state: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    async fn take(&self) {
+        // Held while waiting, so the requests behind wait their turn
+        let mut bucket = self.state.lock().await;
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return;
+        }
+
+        let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second);
+        tokio::time::sleep(wait).await;
+        bucket.tokens = 0.0;
+        bucket.refilled_at = now + wait;
+    }
+}
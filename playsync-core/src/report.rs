@@ -0,0 +1,349 @@
+use crate::sync::{SkipCode, SyncReport};
+use crate::youtube::{self, VideoInfo};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Format of the sync reports
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    /// One row per playlist and per video, for spreadsheets
+    Csv,
+    /// Tables with links, for GitHub issues, wikis and chat tools
+    Markdown,
+}
+
+/// Where the report of each sync run is written, in a `reports` table
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reports {
+    /// Directory of the reports, with one file per run
+    pub dir: PathBuf,
+
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// The report of a sync run
+#[derive(Serialize)]
+pub struct RunReport {
+    pub timestamp: DateTime<Utc>,
+    pub dry_run: bool,
+    /// Estimated API quota units used by the run
+    pub quota_used: u64,
+    /// The error that aborted the run, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub playlists: Vec<PlaylistReport>,
+}
+
+/// The result of a run for a single target playlist
+#[derive(Serialize)]
+pub struct PlaylistReport {
+    pub id: String,
+    pub title: String,
+    pub added: Vec<ReportedVideo>,
+    pub removed: Vec<ReportedVideo>,
+    /// Videos of the sources left out, with the reason
+    pub skipped: Vec<ReportedVideo>,
+    /// Videos that could not be added or removed, with the error
+    pub failed: Vec<ReportedVideo>,
+    pub quota_used: u64,
+    pub duration_seconds: f64,
+    /// The error that stopped the sync of the playlist, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReportedVideo {
+    pub video_id: String,
+    pub title: String,
+    /// Why a skipped video was left out, as a stable code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<SkipCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ReportedVideo {
+    fn new(video: &VideoInfo, reason: Option<&str>) -> Self {
+        ReportedVideo {
+            video_id: video.video_id.clone(),
+            title: video.title.clone(),
+            code: None,
+            reason: reason.map(str::to_string),
+        }
+    }
+}
+
+impl PlaylistReport {
+    fn new(id: &str, title: &str, quota_used: u64, duration: Duration) -> Self {
+        PlaylistReport {
+            id: id.to_string(),
+            title: title.to_string(),
+            added: Vec::new(),
+            removed: Vec::new(),
+            skipped: Vec::new(),
+            failed: Vec::new(),
+            quota_used,
+            // Milliseconds are enough
+            duration_seconds: duration.as_millis() as f64 / 1000.0,
+            error: None,
+        }
+    }
+
+    pub fn synced(
+        id: &str,
+        title: &str,
+        report: &SyncReport,
+        quota_used: u64,
+        duration: Duration,
+    ) -> Self {
+        PlaylistReport {
+            added: report
+                .added
+                .iter()
+                .map(|video| ReportedVideo::new(video, None))
+                .collect(),
+            removed: report
+                .removed
+                .iter()
+                .map(|video| ReportedVideo::new(video, None))
+                .collect(),
+            skipped: report
+                .skipped
+                .iter()
+                .map(|(video, reason)| ReportedVideo {
+                    code: Some(reason.code),
+                    ..ReportedVideo::new(video, Some(&reason.message))
+                })
+                .collect(),
+            failed: report
+                .failed
+                .iter()
+                .map(|(video, error)| ReportedVideo::new(video, Some(error)))
+                .collect(),
+            ..PlaylistReport::new(id, title, quota_used, duration)
+        }
+    }
+
+    pub fn failed(id: &str, title: &str, error: &str, quota_used: u64, duration: Duration) -> Self {
+        PlaylistReport {
+            error: Some(error.to_string()),
+            ..PlaylistReport::new(id, title, quota_used, duration)
+        }
+    }
+}
+
+/// Quote a CSV field when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a run as CSV: a `playlist` row per playlist, with its quota, duration and
+/// error, followed by an `added`, `removed`, `skipped` or `failed` row per video.
+/// Skipped videos have the code of their reason.
+fn to_csv(run: &RunReport) -> String {
+    let mut csv = String::from(
+        "playlist_id,playlist_title,action,video_id,title,code,reason,quota_used,duration_seconds\n",
+    );
+    let mut row = |fields: [&str; 9]| {
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    };
+
+    for playlist in &run.playlists {
+        row([
+            &playlist.id,
+            &playlist.title,
+            "playlist",
+            "",
+            "",
+            "",
+            playlist.error.as_deref().unwrap_or_default(),
+            &playlist.quota_used.to_string(),
+            &playlist.duration_seconds.to_string(),
+        ]);
+
+        for (action, videos) in [
+            ("added", &playlist.added),
+            ("removed", &playlist.removed),
+            ("skipped", &playlist.skipped),
+            ("failed", &playlist.failed),
+        ] {
+            for video in videos {
+                row([
+                    &playlist.id,
+                    &playlist.title,
+                    action,
+                    &video.video_id,
+                    &video.title,
+                    video.code.map(SkipCode::as_str).unwrap_or_default(),
+                    video.reason.as_deref().unwrap_or_default(),
+                    "",
+                    "",
+                ]);
+            }
+        }
+    }
+
+    csv
+}
+
+/// Escape the characters Markdown would interpret in a table cell or a link text
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '|' | '[' | ']' | '*' | '_' | '`' | '<' | '>' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn markdown_link(video: &ReportedVideo) -> String {
+    format!(
+        "[{}]({})",
+        markdown_text(&video.title),
+        youtube::video_url(&video.video_id)
+    )
+}
+
+/// Render the videos of a playlist as Markdown: a `##` heading linking to the playlist,
+/// then a table per kind of change
+pub fn playlist_markdown(playlist: &PlaylistReport) -> String {
+    let mut markdown = format!(
+        "## [{}]({})\n",
+        markdown_text(&playlist.title),
+        youtube::playlist_url(&playlist.id)
+    );
+
+    if let Some(error) = &playlist.error {
+        markdown.push_str(&format!("\n**Error:** {}\n", markdown_text(error)));
+    }
+    if playlist.added.is_empty()
+        && playlist.removed.is_empty()
+        && playlist.skipped.is_empty()
+        && playlist.failed.is_empty()
+        && playlist.error.is_none()
+    {
+        markdown.push_str("\nNo changes.\n");
+    }
+
+    for (heading, videos) in [("Added", &playlist.added), ("Removed", &playlist.removed)] {
+        if videos.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!(
+            "\n### {} ({})\n\n| Video |\n| --- |\n",
+            heading,
+            videos.len()
+        ));
+        for video in videos {
+            markdown.push_str(&format!("| {} |\n", markdown_link(video)));
+        }
+    }
+
+    for (heading, column, videos) in [
+        ("Failed", "Error", &playlist.failed),
+        ("Skipped", "Reason", &playlist.skipped),
+    ] {
+        if videos.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!(
+            "\n### {} ({})\n\n| Video | {} |\n| --- | --- |\n",
+            heading,
+            videos.len(),
+            column
+        ));
+        for video in videos {
+            let code = video
+                .code
+                .map(|code| format!("`{}` ", code.as_str()))
+                .unwrap_or_default();
+            markdown.push_str(&format!(
+                "| {} | {}{} |\n",
+                markdown_link(video),
+                code,
+                markdown_text(video.reason.as_deref().unwrap_or_default())
+            ));
+        }
+    }
+
+    markdown
+}
+
+/// Render a run as Markdown: a summary table of the playlists, then their changes
+pub fn to_markdown(run: &RunReport) -> String {
+    let mut markdown = format!(
+        "# Sync report, {}{}\n\n",
+        run.timestamp.format("%Y-%m-%d %H:%M UTC"),
+        if run.dry_run { " (dry run)" } else { "" }
+    );
+    if let Some(error) = &run.error {
+        markdown.push_str(&format!("**The run failed:** {}\n\n", markdown_text(error)));
+    }
+
+    markdown.push_str(
+        "| Playlist | Added | Removed | Skipped | Failed | Quota | Duration |\n\
+         | --- | ---: | ---: | ---: | ---: | ---: | ---: |\n",
+    );
+    for playlist in &run.playlists {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {}s |\n",
+            markdown_text(&playlist.title),
+            playlist.added.len(),
+            playlist.removed.len(),
+            playlist.skipped.len(),
+            playlist.failed.len(),
+            playlist.quota_used,
+            playlist.duration_seconds
+        ));
+    }
+    markdown.push_str(&format!(
+        "\nQuota used by the run: {} units\n",
+        run.quota_used
+    ));
+
+    for playlist in &run.playlists {
+        markdown.push('\n');
+        markdown.push_str(&playlist_markdown(playlist));
+    }
+
+    markdown
+}
+
+/// Write the report of a run to the reports directory, returning its path.
+/// Reports are named after the time of the run, e.g. `sync-20250601T020000Z.json`.
+pub fn write(reports: &Reports, run: &RunReport) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let (extension, content) = match reports.format {
+        ReportFormat::Json => ("json", serde_json::to_string_pretty(run)?),
+        ReportFormat::Csv => ("csv", to_csv(run)),
+        ReportFormat::Markdown => ("md", to_markdown(run)),
+    };
+    let path = reports.dir.join(format!(
+        "sync-{}.{}",
+        run.timestamp.format("%Y%m%dT%H%M%SZ"),
+        extension
+    ));
+
+    std::fs::create_dir_all(&reports.dir)?;
+    std::fs::write(&path, content)?;
+
+    Ok(path)
+}
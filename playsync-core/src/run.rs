@@ -0,0 +1,928 @@
+use crate::config::{Config, Playlist};
+use crate::plan::{Plan, PlannedPlaylist};
+use crate::provider::Provider;
+use crate::ui::{self, log};
+use crate::{
+    download, export, history, hooks, metrics, notify, output, progress, report, schedule, sync,
+    websub,
+};
+use chrono::{DateTime, Local, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Which configured playlists a run syncs
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Only these playlists, or all of them if empty
+    pub playlist_ids: Vec<String>,
+    /// Playlists left out
+    pub exclude: Vec<String>,
+    /// Only the playlists tagged with this group
+    pub group: Option<String>,
+}
+
+impl Selection {
+    pub fn matches(&self, playlist: &Playlist) -> bool {
+        (self.playlist_ids.is_empty() || self.playlist_ids.contains(&playlist.id))
+            && !self.exclude.contains(&playlist.id)
+            && self.group.as_ref().is_none_or(|g| playlist.has_tag(g))
+    }
+}
+
+/// How a command changing playlists outside of syncs ended, which the command line words
+/// its last message after
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome<T = ()> {
+    /// The changes were made
+    Done(T),
+    /// The changes were only shown, on a dry run
+    Previewed,
+    /// There was nothing to change
+    Unchanged,
+}
+
+/// Sync the selected playlists of the configuration one after the other, stopping at the
+/// first one that fails, then record and report the run.
+///
+/// The configuration is written back when a missing target is created or a title follows
+/// its sources. Playlists of other accounts are synced with clients signed in when first
+/// needed. With an offline client, the run is a preview of the cache: it isn't recorded,
+/// reported or notified. With `plan`, the changes of the dry run are written to that file,
+/// to be made later with [`apply`].
+pub async fn sync(
+    cfg: &mut Config,
+    client: &dyn Provider,
+    selection: &Selection,
+    dry_run: bool,
+    plan: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Playlists are tracked by index, since creating a missing target changes its ID
+    let playlists_to_sync: Vec<usize> = (0..cfg.playlists.len())
+        .filter(|&i| selection.matches(&cfg.playlists[i]))
+        .collect();
+    if playlists_to_sync.is_empty() {
+        return Ok(());
+    }
+
+    let mut entry = history::HistoryEntry::new("sync", dry_run);
+    let run_started = Instant::now();
+    let mut result = Ok(());
+    // The client may be shared by several syncs in daemon mode
+    let quota_before = client.quota_used();
+    client.use_music_services(cfg.music_services());
+
+    // Clients of the other accounts playlists belong to, signed in when first needed
+    let mut account_clients: HashMap<String, Box<dyn Provider>> = HashMap::new();
+    let mut playlist_reports = Vec::new();
+    let mut planned = Plan::new();
+
+    for index in playlists_to_sync {
+        let mut playlist = cfg.with_defaults(&cfg.playlists[index]);
+        progress::publish(progress::Event::PlaylistStarted {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+        });
+
+        // Hooks only run when the sync changes something
+        let hooks = playlist
+            .hooks
+            .clone()
+            .filter(|_| !dry_run)
+            .unwrap_or_default();
+        if let Some(command) = &hooks.pre_sync
+            && let Err(e) = hooks::run(command, &playlist, hooks::Outcome::Pending).await
+        {
+            log::warning(format!(
+                "Skipping '{}', its pre_sync hook failed: {}",
+                playlist.title, e
+            ))?;
+            continue;
+        }
+
+        // Offline, every playlist is read from the same cache
+        if let Some(account) = playlist.account.as_ref().filter(|_| !client.is_offline())
+            && !account_clients.contains_key(account)
+        {
+            let account_client = account_client(cfg, client, account).await?;
+            account_clients.insert(account.clone(), account_client);
+        }
+        let client = playlist
+            .account
+            .as_ref()
+            .and_then(|account| account_clients.get(account))
+            .map(|account_client| account_client.as_ref())
+            .unwrap_or(client);
+        let offline = client.is_offline();
+        let started = Instant::now();
+        let playlist_quota_before = client.quota_used();
+
+        let mut target_exists = true;
+        if playlist.sync_from.is_some() {
+            match sync::ensure_target(client, &playlist, dry_run).await {
+                Ok(sync::TargetState::Ready) => {}
+                Ok(sync::TargetState::Created(new_id)) => {
+                    // Only the change is saved, onto the configuration as it is now
+                    Config::update(|file| {
+                        file.replace_playlist_id(&playlist.id, &new_id);
+                        Ok(())
+                    })?;
+                    cfg.replace_playlist_id(&playlist.id, &new_id);
+                    playlist = cfg.with_defaults(&cfg.playlists[index]);
+                }
+                Ok(sync::TargetState::Missing) => continue,
+                Ok(sync::TargetState::ToCreate) => target_exists = false,
+                Err(e) => {
+                    playlist_reports.push(report::PlaylistReport::failed(
+                        &playlist.id,
+                        &playlist.title,
+                        &e.to_string(),
+                        client.quota_used() - playlist_quota_before,
+                        started.elapsed(),
+                    ));
+                    entry.error = Some(e.to_string());
+                    run_failure_hook(&hooks, &playlist, &e.to_string()).await?;
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        if let Some(sync_from) = &playlist.sync_from {
+            let synced = if target_exists {
+                sync::sync_playlist(client, &playlist, sync_from, dry_run).await
+            } else {
+                sync::preview_new_playlist(client, &playlist, sync_from).await
+            };
+            match synced {
+                Ok(report) => {
+                    let mut record = history::PlaylistRecord::new(&playlist.id, &playlist.title);
+                    record.added = report.added.len();
+                    record.removed = report.removed.len();
+                    record.failed = report.failed.len();
+                    record.skipped = report.skipped.len();
+                    record.item_count = Some(report.item_count);
+                    progress::publish(progress::Event::PlaylistSynced {
+                        id: record.id.clone(),
+                        title: record.title.clone(),
+                        added: record.added,
+                        removed: record.removed,
+                        failed: record.failed,
+                    });
+                    entry.playlists.push(record);
+
+                    // The metadata of the sources isn't cached, and a playlist to create
+                    // has none yet
+                    let metadata = if offline || !target_exists {
+                        Ok(None)
+                    } else {
+                        sync::sync_metadata(client, &playlist, sync_from, dry_run).await
+                    };
+                    match metadata {
+                        Ok(Some(new_title)) => {
+                            Config::update(|file| {
+                                if let Some(saved) =
+                                    file.playlists.iter_mut().find(|p| p.id == playlist.id)
+                                {
+                                    saved.title = new_title.clone();
+                                }
+                                Ok(())
+                            })?;
+                            cfg.playlists[index].title = new_title;
+                        }
+                        Ok(None) => {}
+                        Err(e) => log::warning(format!(
+                            "Failed to update the metadata of '{}': {}",
+                            playlist.title, e
+                        ))?,
+                    }
+
+                    if let Some(path) = playlist.download_archive.as_ref().filter(|_| !dry_run)
+                        && let Err(e) = export::write_download_archive(path, &report.videos)
+                    {
+                        log::warning(format!(
+                            "Failed to write the download archive of '{}': {}",
+                            playlist.title, e
+                        ))?;
+                    }
+
+                    if let Some(dir) = cfg.m3u_dir.as_ref().filter(|_| !dry_run)
+                        && let Err(e) = export::write_m3u(dir, &playlist.title, &report.videos)
+                    {
+                        log::warning(format!(
+                            "Failed to write the .m3u file of '{}': {}",
+                            playlist.title, e
+                        ))?;
+                    }
+
+                    if let Some(dir) = &playlist.download_dir {
+                        download::mirror_playlist(&playlist, dir, &report.videos, dry_run).await?;
+                    }
+
+                    if let Some(command) = &hooks.post_sync
+                        && let Err(e) =
+                            hooks::run(command, &playlist, hooks::Outcome::Synced(&report)).await
+                    {
+                        log::warning(format!(
+                            "The post_sync hook of '{}' failed: {}",
+                            playlist.title, e
+                        ))?;
+                    }
+
+                    playlist_reports.push(report::PlaylistReport::synced(
+                        &playlist.id,
+                        &playlist.title,
+                        &report,
+                        client.quota_used() - playlist_quota_before,
+                        started.elapsed(),
+                    ));
+                    if plan.is_some() {
+                        planned
+                            .playlists
+                            .extend(PlannedPlaylist::from_dry_run(&playlist, &report));
+                    }
+                }
+                // Playlists missing from the cache don't stop the preview of the others
+                Err(e) if offline => {
+                    log::warning(format!("Skipping '{}': {}", playlist.title, e))?;
+                }
+                Err(e) => {
+                    playlist_reports.push(report::PlaylistReport::failed(
+                        &playlist.id,
+                        &playlist.title,
+                        &e.to_string(),
+                        client.quota_used() - playlist_quota_before,
+                        started.elapsed(),
+                    ));
+                    entry.error = Some(e.to_string());
+                    run_failure_hook(&hooks, &playlist, &e.to_string()).await?;
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+    }
+
+    entry.quota_used = client.quota_used() - quota_before
+        + account_clients
+            .values()
+            .map(|account_client| account_client.quota_used())
+            .sum::<u64>();
+    if client.is_offline() {
+        // A preview of cached data isn't a run: it isn't recorded, reported or notified
+        log::info(entry.summary_line(run_started.elapsed()))?;
+        output::print(&entry)?;
+    } else {
+        finish(cfg, &entry, playlist_reports, run_started.elapsed()).await?;
+    }
+    result?;
+
+    if let Some(path) = plan {
+        planned.write(path)?;
+        log::success(format!(
+            "Plan of {} playlist(s) written to {}. Make its changes with `playsync sync --apply {}`",
+            planned.playlists.len(),
+            path.display(),
+            path.display()
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// A client signed in to another Google account, for its playlists
+async fn account_client(
+    cfg: &Config,
+    client: &dyn Provider,
+    account: &str,
+) -> Result<Box<dyn Provider>, Box<dyn std::error::Error>> {
+    let oauth2_json = cfg
+        .oauth2_json
+        .as_ref()
+        .ok_or("OAuth2 JSON path is not set")?;
+    let client = client.for_account(oauth2_json, account).await?;
+    client.use_music_services(cfg.music_services());
+
+    Ok(client)
+}
+
+/// Run the `on_failure` hook of a playlist whose sync errored out, if it has one
+async fn run_failure_hook(
+    hooks: &hooks::Hooks,
+    playlist: &Playlist,
+    error: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(command) = &hooks.on_failure
+        && let Err(e) = hooks::run(command, playlist, hooks::Outcome::Failed(error)).await
+    {
+        log::warning(format!(
+            "The on_failure hook of '{}' failed: {}",
+            playlist.title, e
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Error of [`apply`] when a playlist changed since the plan was made: nothing was changed
+pub struct PlanRefused {
+    pub title: String,
+    pub id: String,
+    /// When the plan was made
+    pub created_at: DateTime<Utc>,
+}
+
+impl fmt::Display for PlanRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' (ID: {}) changed since the plan was made on {}. Make a new plan with `playsync sync --plan`.",
+            self.title,
+            self.id,
+            self.created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+// Printed like the plain string errors when main returns
+impl fmt::Debug for PlanRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl std::error::Error for PlanRefused {}
+
+/// Make the changes of a plan written by [`sync()`], and nothing else, then record and
+/// report the run.
+///
+/// Every playlist of the plan is checked first, so a plan is refused with [`PlanRefused`]
+/// without changing anything if one of its playlists changed since it was made.
+pub async fn apply(
+    cfg: &Config,
+    client: &dyn Provider,
+    plan: &Plan,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut account_clients: HashMap<String, Box<dyn Provider>> = HashMap::new();
+    for account in plan.playlists.iter().filter_map(|p| p.account.as_ref()) {
+        if !account_clients.contains_key(account) {
+            let account_client = account_client(cfg, client, account).await?;
+            account_clients.insert(account.clone(), account_client);
+        }
+    }
+    let client_for = |planned: &PlannedPlaylist| {
+        planned
+            .account
+            .as_ref()
+            .and_then(|account| account_clients.get(account))
+            .map(|account_client| account_client.as_ref())
+            .unwrap_or(client)
+    };
+
+    let sp = ui::spinner();
+    sp.start("Checking the playlists of the plan");
+    for planned in &plan.playlists {
+        let items = client_for(planned).get_playlist_items(&planned.id).await?;
+        if !planned.is_current(&items) {
+            sp.error(&format!("'{}' changed", planned.title));
+            return Err(Box::new(PlanRefused {
+                title: planned.title.clone(),
+                id: planned.id.clone(),
+                created_at: plan.created_at,
+            }));
+        }
+    }
+    sp.stop("The playlists didn't change since the plan was made");
+
+    let mut entry = history::HistoryEntry::new("sync", false);
+    let run_started = Instant::now();
+    let mut result = Ok(());
+    let quota_before = client.quota_used();
+    let mut playlist_reports = Vec::new();
+
+    for planned in &plan.playlists {
+        let client = client_for(planned);
+        let started = Instant::now();
+        let playlist_quota_before = client.quota_used();
+        let options = sync::WriteOptions {
+            at_start: planned.at_start,
+            delay: cfg
+                .playlists
+                .iter()
+                .find(|p| p.id == planned.id)
+                .map(|p| cfg.with_defaults(p))
+                .and_then(|p| p.write_delay_ms)
+                .map(Duration::from_millis),
+        };
+
+        let mut report = sync::SyncReport {
+            item_count: planned.items.len(),
+            ..Default::default()
+        };
+        let applied = async {
+            sync::add_videos_with(
+                client,
+                &planned.id,
+                planned.videos_to_add(),
+                &mut report,
+                options,
+            )
+            .await?;
+            sync::remove_videos(client, &planned.id, planned.videos_to_remove(), &mut report).await
+        }
+        .await;
+
+        let mut record = history::PlaylistRecord::new(&planned.id, &planned.title);
+        record.added = report.added.len();
+        record.removed = report.removed.len();
+        record.failed = report.failed.len();
+        record.item_count = Some(report.item_count);
+        entry.playlists.push(record);
+        log::info(format!(
+            "{}: +{} -{} ✗{}",
+            planned.title,
+            report.added.len(),
+            report.removed.len(),
+            report.failed.len()
+        ))?;
+
+        let mut playlist_report = report::PlaylistReport::synced(
+            &planned.id,
+            &planned.title,
+            &report,
+            client.quota_used() - playlist_quota_before,
+            started.elapsed(),
+        );
+        if let Err(e) = &applied {
+            playlist_report.error = Some(e.to_string());
+        }
+        playlist_reports.push(playlist_report);
+
+        if let Err(e) = applied {
+            entry.error = Some(e.to_string());
+            result = Err(e);
+            break;
+        }
+    }
+
+    entry.quota_used = client.quota_used() - quota_before
+        + account_clients
+            .values()
+            .map(|account_client| account_client.quota_used())
+            .sum::<u64>();
+    finish(cfg, &entry, playlist_reports, run_started.elapsed()).await?;
+    result
+}
+
+/// Record a finished sync run in the history and the metrics, log its summary line,
+/// write its report and send the notifications
+async fn finish(
+    cfg: &Config,
+    entry: &history::HistoryEntry,
+    playlists: Vec<report::PlaylistReport>,
+    duration: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    metrics::record(entry);
+    history::record(entry)?;
+    let summary = entry.summary_line(duration);
+    log::info(&summary)?;
+
+    let run = report::RunReport {
+        timestamp: entry.timestamp,
+        dry_run: entry.dry_run,
+        quota_used: entry.quota_used,
+        error: entry.error.clone(),
+        playlists,
+    };
+    if let Some(reports) = &cfg.reports
+        && let Err(e) = report::write(reports, &run)
+    {
+        log::warning(format!("Failed to write the sync report: {}", e))?;
+    }
+    if output::is_github_actions()
+        && let Err(e) = output::github_actions(&run, &summary)
+    {
+        log::warning(format!("Failed to write the job summary: {}", e))?;
+    }
+
+    for error in notify::send(&cfg.notifications, entry).await {
+        log::warning(error)?;
+    }
+    output::print(entry)?;
+
+    Ok(())
+}
+
+/// Delay before the first retry of a playlist whose sync failed, doubled by each failure
+/// in a row
+const FIRST_RETRY_DELAY: chrono::Duration = chrono::Duration::minutes(1);
+
+/// Longest delay between the retries of a failing playlist
+const MAX_RETRY_DELAY: chrono::Duration = chrono::Duration::hours(1);
+
+/// How long to wait before retrying a playlist whose sync failed `attempts` times in a row
+fn retry_delay(attempts: u32) -> chrono::Duration {
+    // Doubling more than 6 times is past the longest delay anyway
+    let doublings = attempts.saturating_sub(1).min(6);
+    (FIRST_RETRY_DELAY * (1 << doublings)).min(MAX_RETRY_DELAY)
+}
+
+/// Settings of [`daemon`]
+#[derive(Debug, Clone)]
+pub struct DaemonOptions {
+    /// Minutes between the syncs of the playlists without a schedule
+    pub interval: u32,
+    /// Only preview the syncs, also done with the `dry_run` default of the configuration
+    pub dry_run: bool,
+    /// Public URL of the WebSub callback, to also sync when a source channel uploads
+    pub websub_url: Option<String>,
+    /// Port the WebSub callback listens on
+    pub websub_port: u16,
+    /// Where Prometheus metrics are served, if they are
+    pub metrics_bind: Option<SocketAddr>,
+}
+
+/// Sync playlists as they come due, until `stop` completes.
+///
+/// Each due playlist is synced on its own. One that fails is retried after a minute,
+/// then after twice as long with each failure in a row, up to an hour, unless its next
+/// run comes first. The configuration is read again before each round, so playlists and
+/// schedules can be changed without restarting the daemon. With `websub_url`, playlists are also synced
+/// when the WebSub hub announces a new upload of one of their source channels, and
+/// Prometheus metrics are served on `metrics_bind`. Notifications are batched into
+/// digests, sent as they come due.
+pub async fn daemon(
+    client: &dyn Provider,
+    options: &DaemonOptions,
+    stop: impl Future<Output = ()>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    notify::batch_into_digest();
+
+    if let Some(address) = options.metrics_bind {
+        log::info(format!("Serving metrics on http://{}/metrics", address))?;
+        tokio::spawn(async move {
+            if let Err(e) = metrics::listen(address).await {
+                let _ = log::error(format!("The metrics listener stopped: {}", e));
+            }
+        });
+    }
+
+    let interval = chrono::Duration::minutes(options.interval.into());
+
+    // Next run of each playlist, with the schedule and interval it was computed from
+    let mut next_runs: HashMap<String, (schedule::Cadence, DateTime<Local>)> = HashMap::new();
+    // Invalid schedules already reported, so they're not logged every round
+    let mut invalid: HashMap<String, String> = HashMap::new();
+    // Syncs that failed in a row for each playlist, which its retries back off with
+    let mut failures: HashMap<String, u32> = HashMap::new();
+
+    // Whether due playlists are waiting for a sync window, and the last invalid
+    // `sync_windows` error reported, so they're only logged once
+    let mut waiting = false;
+    let mut windows_error: Option<String> = None;
+
+    // Channels with a new upload announced since the last round
+    let mut pushed: HashSet<String> = HashSet::new();
+    // When to renew the subscription to each channel
+    let mut renewals: HashMap<String, DateTime<Local>> = HashMap::new();
+    let subscriptions = options
+        .websub_url
+        .as_deref()
+        .map(|url| Arc::new(websub::Subscriptions::new(url)));
+    let mut notifications = match (&options.websub_url, &subscriptions) {
+        (Some(url), Some(subscriptions)) => {
+            // The hub calls back on the path of the public URL
+            let path = reqwest::Url::parse(url)
+                .map_err(|e| format!("Invalid WebSub URL {}: {}", url, e))?
+                .path()
+                .to_string();
+            let (sender, receiver) = tokio::sync::mpsc::channel(100);
+            let port = options.websub_port;
+            let subscriptions = subscriptions.clone();
+            tokio::spawn(async move {
+                if let Err(e) = websub::listen(port, path, subscriptions, sender).await {
+                    let _ = log::error(format!("The WebSub listener stopped: {}", e));
+                }
+            });
+            Some(receiver)
+        }
+        _ => None,
+    };
+
+    tokio::pin!(stop);
+    loop {
+        let now = Local::now();
+        let mut wake_up = now + chrono::Duration::seconds(60);
+
+        match Config::read() {
+            Err(e) => log::error(format!("Failed to read the configuration: {}", e))?,
+            Ok(mut cfg) => {
+                next_runs.retain(|id, _| cfg.playlists.iter().any(|p| &p.id == id));
+                failures.retain(|id, _| cfg.playlists.iter().any(|p| &p.id == id));
+
+                if let Some(subscriptions) = &subscriptions {
+                    let channels: HashSet<String> = cfg
+                        .playlists
+                        .iter()
+                        .flat_map(websub::source_channels)
+                        .collect();
+                    for channel_id in channels {
+                        if renewals.get(&channel_id).is_some_and(|at| *at > now) {
+                            continue;
+                        }
+                        // Renewed a day before the lease ends, or retried in an hour
+                        let renewal = match subscriptions.subscribe(&channel_id).await {
+                            Ok(()) => {
+                                now + chrono::Duration::seconds(websub::LEASE_SECONDS)
+                                    - chrono::Duration::days(1)
+                            }
+                            Err(e) => {
+                                log::warning(e.to_string())?;
+                                now + chrono::Duration::hours(1)
+                            }
+                        };
+                        renewals.insert(channel_id, renewal);
+                    }
+                }
+
+                let mut due = Vec::new();
+                for playlist in &cfg.playlists {
+                    let next = match next_runs.get(&playlist.id) {
+                        Some((cadence, next)) if *cadence == schedule::cadence(playlist) => *next,
+                        _ => {
+                            // Playlists without a schedule sync right away when first seen
+                            let first = match playlist.schedule {
+                                Some(_) => schedule::next_run(playlist, now, interval),
+                                None => Ok(now),
+                            };
+                            match first {
+                                Ok(next) => {
+                                    invalid.remove(&playlist.id);
+                                    next_runs.insert(
+                                        playlist.id.clone(),
+                                        (schedule::cadence(playlist), next),
+                                    );
+                                    if next > now {
+                                        log::info(format!(
+                                            "Next sync of \"{}\" at {}",
+                                            playlist.title,
+                                            next.format("%Y-%m-%d %H:%M")
+                                        ))?;
+                                    }
+                                    next
+                                }
+                                Err(e) => {
+                                    let message = e.to_string();
+                                    if invalid.get(&playlist.id) != Some(&message) {
+                                        log::error(format!(
+                                            "\"{}\" is not synced: {}",
+                                            playlist.title, message
+                                        ))?;
+                                        invalid.insert(playlist.id.clone(), message);
+                                    }
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+
+                    if next <= now
+                        || websub::source_channels(playlist)
+                            .iter()
+                            .any(|channel_id| pushed.contains(channel_id))
+                    {
+                        due.push(playlist.id.clone());
+                    } else {
+                        wake_up = wake_up.min(next);
+                    }
+                }
+
+                let open = match schedule::parse_windows(&cfg.sync_windows) {
+                    Ok(windows) => {
+                        windows_error = None;
+                        let open = schedule::in_windows(&windows, now);
+                        if !open && !due.is_empty() && !waiting {
+                            if let Some(opens) = schedule::next_window(&windows, now) {
+                                log::info(format!(
+                                    "Outside the sync windows, waiting until {}",
+                                    opens.format("%H:%M")
+                                ))?;
+                            }
+                            waiting = true;
+                        }
+                        open
+                    }
+                    Err(e) => {
+                        // Nothing syncs until the windows are fixed, to stay off-peak
+                        let message = e.to_string();
+                        if windows_error.as_ref() != Some(&message) {
+                            log::error(format!("Not syncing: {}", message))?;
+                            windows_error = Some(message);
+                        }
+                        false
+                    }
+                };
+                if open {
+                    waiting = false;
+                    pushed.clear();
+                } else {
+                    // Due playlists and announced uploads wait for the next window
+                    due.clear();
+                }
+
+                // Each playlist syncs on its own, so a failing one doesn't hold back the
+                // others, and is retried sooner than its next run
+                let dry_run = options.dry_run || cfg.defaults.dry_run;
+                for id in due {
+                    // Created targets change their ID, so the playlist is taken from the
+                    // configuration as it was before the sync
+                    let Some(playlist) = cfg.playlists.iter().find(|p| p.id == id).cloned() else {
+                        continue;
+                    };
+                    let selection = Selection {
+                        playlist_ids: vec![id.clone()],
+                        ..Default::default()
+                    };
+                    let result = sync(&mut cfg, client, &selection, dry_run, None).await;
+
+                    let now = Local::now();
+                    // The schedule was valid when the playlist came due
+                    let Ok(next) = schedule::next_run(&playlist, now, interval) else {
+                        continue;
+                    };
+                    let next = match result {
+                        Ok(()) => {
+                            failures.remove(&id);
+                            next
+                        }
+                        Err(e) => {
+                            let attempts = failures.entry(id.clone()).or_default();
+                            *attempts += 1;
+                            let retry = now + retry_delay(*attempts);
+                            log::error(format!(
+                                "Sync of \"{}\" failed ({} in a row): {}",
+                                playlist.title, attempts, e
+                            ))?;
+                            next.min(retry)
+                        }
+                    };
+                    next_runs.insert(id, (schedule::cadence(&playlist), next));
+                    wake_up = wake_up.min(next);
+                    log::info(format!(
+                        "Next sync of \"{}\" at {}",
+                        playlist.title,
+                        next.format("%Y-%m-%d %H:%M")
+                    ))?;
+                }
+
+                for error in notify::send_digest(&cfg.notifications).await {
+                    log::warning(error)?;
+                }
+            }
+        }
+
+        // Wake up at least every minute to pick up configuration changes
+        let sleep = (wake_up - Local::now())
+            .to_std()
+            .unwrap_or_default()
+            .min(Duration::from_secs(60));
+        tokio::select! {
+            _ = tokio::time::sleep(sleep) => {}
+            Some(channel_id) = async {
+                match notifications.as_mut() {
+                    Some(receiver) => receiver.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                log::info(format!("New upload announced on channel {}", channel_id))?;
+                pushed.insert(channel_id);
+            }
+            _ = &mut stop => break,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::provider::InMemoryProvider;
+    use std::sync::OnceLock;
+    use tempfile::TempDir;
+
+    /// Held by the tests reading and writing the configuration and history, which they
+    /// share
+    static FILES: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    /// Write a configuration syncing these playlists from one source each, in a
+    /// directory of its own since the configuration path can only be set once
+    fn write_config(playlists: &[(&str, &str)]) -> Config {
+        static DIR: OnceLock<TempDir> = OnceLock::new();
+        let dir = DIR.get_or_init(|| tempfile::tempdir().unwrap());
+        config::set_config_path(dir.path().join("config.toml")).unwrap();
+
+        let mut cfg = Config::default();
+        for (id, source) in playlists {
+            cfg.add_playlist(Playlist {
+                id: id.to_string(),
+                title: id.to_uppercase(),
+                sync_from: Some(vec![source.to_string()]),
+                ..Default::default()
+            });
+        }
+        cfg.write(&Config::lock().unwrap()).unwrap();
+        cfg
+    }
+
+    fn video_ids(provider: &InMemoryProvider, playlist_id: &str) -> Vec<String> {
+        provider
+            .playlist(playlist_id)
+            .unwrap()
+            .into_iter()
+            .map(|video| video.video_id)
+            .collect()
+    }
+
+    #[test]
+    fn retries_back_off_up_to_an_hour() {
+        assert_eq!(retry_delay(1), chrono::Duration::minutes(1));
+        assert_eq!(retry_delay(2), chrono::Duration::minutes(2));
+        assert_eq!(retry_delay(4), chrono::Duration::minutes(8));
+        assert_eq!(retry_delay(7), chrono::Duration::hours(1));
+        assert_eq!(retry_delay(u32::MAX), chrono::Duration::hours(1));
+    }
+
+    #[tokio::test]
+    async fn sync_records_the_run_in_the_history() {
+        let _files = FILES.lock().await;
+        let mut cfg = write_config(&[("target", "source")]);
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![])
+            .with_playlist(
+                "source",
+                "Source",
+                vec![
+                    InMemoryProvider::video("a", "A"),
+                    InMemoryProvider::video("b", "B"),
+                ],
+            );
+
+        sync(&mut cfg, &provider, &Selection::default(), false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["a", "b"]);
+        let entry = history::read_all().unwrap().pop().unwrap();
+        assert_eq!(entry.command, "sync");
+        assert_eq!(entry.error, None);
+        assert_eq!(entry.playlists.len(), 1);
+        assert_eq!(entry.playlists[0].added, 2);
+    }
+
+    #[tokio::test]
+    async fn sync_stops_at_the_first_failing_playlist() {
+        let _files = FILES.lock().await;
+        let mut cfg = write_config(&[("broken", "missing"), ("target", "source")]);
+        let provider = InMemoryProvider::new()
+            .with_playlist("broken", "Broken", vec![])
+            .with_playlist("target", "Target", vec![])
+            .with_playlist("source", "Source", vec![InMemoryProvider::video("a", "A")]);
+
+        let result = sync(&mut cfg, &provider, &Selection::default(), false, None).await;
+
+        assert!(result.is_err());
+        assert!(video_ids(&provider, "target").is_empty());
+        let entry = history::read_all().unwrap().pop().unwrap();
+        assert!(entry.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn daemon_syncs_the_playlists_after_a_failing_one() {
+        let _files = FILES.lock().await;
+        write_config(&[("broken", "missing"), ("target", "source")]);
+        let provider = InMemoryProvider::new()
+            .with_playlist("broken", "Broken", vec![])
+            .with_playlist("target", "Target", vec![])
+            .with_playlist("source", "Source", vec![InMemoryProvider::video("a", "A")]);
+        let options = DaemonOptions {
+            interval: 60,
+            dry_run: false,
+            websub_url: None,
+            websub_port: 0,
+            metrics_bind: None,
+        };
+
+        // Stopped after its first round, which syncs every playlist without a schedule
+        daemon(&provider, &options, async {}).await.unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["a"]);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::config::Playlist;
+use crate::exit;
+use chrono::{DateTime, Duration, Local, NaiveTime};
+use croner::Cron;
+
+/// Parse the cron expression of a playlist schedule, e.g. `0 7 * * *`
+pub fn parse(expression: &str) -> Result<Cron, Box<dyn std::error::Error>> {
+    Cron::new(expression)
+        .parse()
+        .map_err(|e| format!("Invalid schedule `{}`: {}", expression, e).into())
+}
+
+/// Parse the refresh interval of a playlist: a number of minutes (`15m`), hours (`6h`)
+/// or days (`1d`)
+pub fn parse_interval(value: &str) -> Result<Duration, Box<dyn std::error::Error>> {
+    let invalid = || {
+        exit::failure(
+            exit::Code::Config,
+            format!(
+                "Invalid interval `{}`, expected e.g. `15m`, `6h` or `1d`",
+                value
+            ),
+        )
+    };
+    let value = value.trim();
+    let unit = value.chars().last().ok_or_else(invalid)?;
+    let count: i64 = value[..value.len() - unit.len_utf8()]
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    if count <= 0 {
+        return Err(invalid());
+    }
+
+    let duration = match unit {
+        'm' => Duration::try_minutes(count),
+        'h' => Duration::try_hours(count),
+        'd' => Duration::try_days(count),
+        _ => return Err(invalid()),
+    };
+    duration.ok_or_else(|| {
+        exit::failure(
+            exit::Code::Config,
+            format!("Interval `{}` is too long", value),
+        )
+    })
+}
+
+/// The `schedule` and `interval` of a playlist, which its next runs are computed from
+pub type Cadence = (Option<String>, Option<String>);
+
+/// What the next runs of a playlist are computed from, to notice when it changes
+pub fn cadence(playlist: &Playlist) -> Cadence {
+    (playlist.schedule.clone(), playlist.interval.clone())
+}
+
+/// When the daemon should sync a playlist next, after `after`.
+///
+/// Playlists with a `schedule` sync at its next occurrence, the others every `interval`
+/// of their own, or every `default_interval`.
+pub fn next_run(
+    playlist: &Playlist,
+    after: DateTime<Local>,
+    default_interval: Duration,
+) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
+    match (&playlist.schedule, &playlist.interval) {
+        (Some(expression), _) => Ok(parse(expression)?
+            .find_next_occurrence(&after, false)
+            .map_err(|e| format!("No next run for schedule `{}`: {}", expression, e))?),
+        (None, Some(interval)) => later(after, parse_interval(interval)?),
+        (None, None) => later(after, default_interval),
+    }
+}
+
+/// `after` plus `interval`, failing instead of overflowing past the dates chrono supports
+fn later(
+    after: DateTime<Local>,
+    interval: Duration,
+) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
+    after.checked_add_signed(interval).ok_or_else(|| {
+        exit::failure(
+            exit::Code::Config,
+            "The next run is too far away, use a shorter interval",
+        )
+    })
+}
+
+/// A time of day range syncs are allowed in, written `02:00-06:00`.
+/// It may cross midnight, e.g. `22:00-02:00`.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn parse(value: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let invalid = || {
+            format!(
+                "Invalid sync window `{}`, expected e.g. `02:00-06:00`",
+                value
+            )
+        };
+        let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+        let parse_time =
+            |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+
+        Ok(TimeWindow {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Parse the `sync_windows` of the configuration
+pub fn parse_windows(windows: &[String]) -> Result<Vec<TimeWindow>, Box<dyn std::error::Error>> {
+    windows
+        .iter()
+        .map(|window| TimeWindow::parse(window))
+        .collect()
+}
+
+/// Whether syncs are allowed at `time`. Without windows, they always are.
+pub fn in_windows(windows: &[TimeWindow], time: DateTime<Local>) -> bool {
+    windows.is_empty() || windows.iter().any(|window| window.contains(time.time()))
+}
+
+/// When the next window opens after `time`, if there are windows
+pub fn next_window(windows: &[TimeWindow], time: DateTime<Local>) -> Option<DateTime<Local>> {
+    windows
+        .iter()
+        .filter_map(|window| {
+            let today = time.date_naive().and_time(window.start);
+            let start = if today > time.naive_local() {
+                today
+            } else {
+                today.checked_add_signed(Duration::days(1))?
+            };
+            // Times skipped by a daylight saving change have no local equivalent
+            start.and_local_timezone(Local).earliest()
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(parse_interval("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_interval(" 6h ").unwrap(), Duration::hours(6));
+        assert!(parse_interval("0d").is_err());
+        assert!(parse_interval("6w").is_err());
+    }
+
+    #[test]
+    fn rejects_intervals_too_long_instead_of_panicking() {
+        let error = parse_interval(&format!("{}d", i64::MAX)).unwrap_err();
+        assert_eq!(exit::code_for(error.as_ref()), exit::Code::Config);
+
+        let playlist = Playlist {
+            interval: Some("100000000d".to_string()),
+            ..Default::default()
+        };
+        assert!(next_run(&playlist, Local::now(), Duration::hours(1)).is_err());
+    }
+}
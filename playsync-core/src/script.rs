@@ -0,0 +1,127 @@
+use crate::youtube::VideoInfo;
+use rhai::{AST, Dynamic, Engine, Map, Scope};
+use std::path::{Path, PathBuf};
+
+/// Limit of operations per item, so a script stuck in a loop fails instead of hanging the sync
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What a playlist script decided for a candidate video
+pub enum Verdict {
+    Keep,
+    Skip,
+    /// Add another video instead, e.g. the official upload of a song
+    Replace(Box<VideoInfo>),
+}
+
+/// A Rhai script deciding which videos are added to a playlist, from its `script` setting
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    path: PathBuf,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| format!("Failed to load the script {}: {}", path.display(), e))?;
+
+        Ok(Script {
+            engine,
+            ast,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Run the script for a video, found in the `source` entry of `sync_from`.
+    ///
+    /// The script sees the video as `item`, with its `video_id`, `title`, `channel`,
+    /// `duration` (in seconds), `published_at` and `source`, and returns `true` or `"keep"`
+    /// to add it, `false` or `"skip"` to leave it out, or a map with another `video_id`
+    /// (and optionally `title`) to add instead.
+    pub fn verdict(
+        &self,
+        video: &VideoInfo,
+        source: &str,
+    ) -> Result<Verdict, Box<dyn std::error::Error>> {
+        let details = video.details.as_ref();
+        let optional = |value: Option<Dynamic>| value.unwrap_or(Dynamic::UNIT);
+
+        let mut item = Map::new();
+        item.insert("video_id".into(), video.video_id.clone().into());
+        item.insert("title".into(), video.title.clone().into());
+        item.insert(
+            "channel".into(),
+            optional(video.channel.clone().map(Dynamic::from)),
+        );
+        item.insert(
+            "duration".into(),
+            optional(
+                details
+                    .and_then(|d| d.duration)
+                    .map(|duration| Dynamic::from(duration.as_secs() as i64)),
+            ),
+        );
+        item.insert(
+            "published_at".into(),
+            optional(
+                video
+                    .published_at
+                    .or(details.and_then(|d| d.published_at))
+                    .map(|time| Dynamic::from(time.to_rfc3339())),
+            ),
+        );
+        item.insert("source".into(), source.to_string().into());
+
+        let mut scope = Scope::new();
+        scope.push("item", item);
+
+        let failed = |e: &dyn std::fmt::Display| {
+            format!(
+                "The script {} failed on '{}': {}",
+                self.path.display(),
+                video.title,
+                e
+            )
+        };
+        let result: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| failed(&e))?;
+
+        if let Some(keep) = result.clone().try_cast::<bool>() {
+            return Ok(if keep { Verdict::Keep } else { Verdict::Skip });
+        }
+        if let Ok(text) = result.clone().into_string() {
+            return match text.as_str() {
+                "keep" => Ok(Verdict::Keep),
+                "skip" => Ok(Verdict::Skip),
+                _ => Err(failed(&format!("unknown verdict `{}`", text)).into()),
+            };
+        }
+        if let Some(replacement) = result.try_cast::<Map>() {
+            let field = |name: &str| {
+                replacement
+                    .get(name)
+                    .and_then(|value| value.clone().into_string().ok())
+            };
+            let video_id =
+                field("video_id").ok_or_else(|| failed(&"the returned map has no `video_id`"))?;
+
+            return Ok(Verdict::Replace(Box::new(VideoInfo {
+                title: field("title").unwrap_or_else(|| video.title.clone()),
+                video_id,
+                channel: None,
+                item_id: String::new(),
+                added_at: None,
+                published_at: None,
+                details: None,
+            })));
+        }
+
+        Err(failed(&"expected true, false, \"keep\", \"skip\" or a map").into())
+    }
+}
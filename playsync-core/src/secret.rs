@@ -0,0 +1,98 @@
+use crate::exit;
+use crate::i18n::tr;
+use age::secrecy::SecretString;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::sync::OnceLock;
+
+/// Prefix of encrypted values in the configuration file
+const PREFIX: &str = "age:";
+
+/// Passphrase entered during this run, so it's only asked once
+static PASSPHRASE: OnceLock<SecretString> = OnceLock::new();
+
+/// Whether a configuration value is encrypted
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// Encrypt a value with a passphrase, as an `age:` string to store in the configuration.
+///
+/// The value is encrypted with age's passphrase (scrypt) mode, base64-encoded.
+pub fn encrypt(
+    value: &str,
+    passphrase: &SecretString,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let recipient = age::scrypt::Recipient::new(passphrase.clone());
+    let encrypted = age::encrypt(&recipient, value.as_bytes())?;
+
+    Ok(format!("{}{}", PREFIX, STANDARD.encode(encrypted)))
+}
+
+/// Decrypt an `age:` value of the configuration
+pub fn decrypt(
+    value: &str,
+    passphrase: &SecretString,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let encoded = value
+        .strip_prefix(PREFIX)
+        .ok_or("The value is not encrypted")?;
+    let identity = age::scrypt::Identity::new(passphrase.clone());
+    let decrypted = age::decrypt(&identity, &STANDARD.decode(encoded)?)
+        .map_err(|e| format!("Failed to decrypt a secret, check the passphrase: {}", e))?;
+
+    Ok(String::from_utf8(decrypted)?)
+}
+
+/// The plain value of a secret setting.
+///
+/// Encrypted values are decrypted with the passphrase, which is asked the first time
+/// one is needed. Values stored in plain text are returned as they are.
+pub fn reveal(value: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+
+    decrypt(value, &passphrase(false)?)
+}
+
+/// Get the passphrase protecting the secrets of the configuration.
+///
+/// It is read from the `PLAYSYNC_PASSPHRASE` environment variable when set (e.g. for
+/// scheduled syncs), and asked otherwise. With `confirm`, it has to be typed twice.
+pub fn passphrase(confirm: bool) -> Result<SecretString, Box<dyn std::error::Error>> {
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = match std::env::var("PLAYSYNC_PASSPHRASE") {
+        Ok(value) if !value.is_empty() => value,
+        _ if crate::output::assume_yes() => {
+            return Err(exit::failure(
+                exit::Code::Config,
+                "Set PLAYSYNC_PASSPHRASE to decrypt the secrets with --yes",
+            ));
+        }
+        _ => {
+            let value = crate::ui::get().password(&tr("passphrase-prompt"))?;
+
+            if confirm {
+                let again = crate::ui::get().password(&tr("passphrase-confirm"))?;
+                if again != value {
+                    return Err("The passphrases don't match".into());
+                }
+            }
+
+            value
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Err("The passphrase is empty".into());
+    }
+
+    let passphrase = SecretString::from(passphrase);
+    let _ = PASSPHRASE.set(passphrase.clone());
+
+    Ok(passphrase)
+}
@@ -0,0 +1,263 @@
+use crate::history::{self, PlaylistRecord};
+use crate::provider::Provider;
+use crate::run::Outcome;
+use crate::ui::{self, log};
+use crate::youtube::VideoInfo;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+
+/// Maximum number of times the order of a playlist is checked and fixed by [`apply_order`]
+const MAX_PASSES: usize = 3;
+
+/// Order in which playlist items can be sorted
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum SortKey {
+    /// Sort by video title
+    Title,
+    /// Sort by channel name, then by title
+    Channel,
+    /// Sort by video publish date, oldest first
+    Published,
+    /// Sort by the date the video was added to the playlist, oldest first
+    Added,
+    /// Sort by video duration, shortest first
+    Duration,
+}
+
+impl SortKey {
+    /// Whether sorting by this key requires the video details to be fetched first
+    pub fn needs_details(self) -> bool {
+        self == SortKey::Duration
+    }
+}
+
+/// Sort videos in place by the given key, ignoring case.
+///
+/// Videos with an unknown date or duration are placed last. The sort is stable,
+/// so videos with equal keys keep their current relative order.
+pub fn sort_videos(videos: &mut [VideoInfo], key: SortKey) {
+    match key {
+        SortKey::Title => videos.sort_by_cached_key(|video| video.title.to_lowercase()),
+        SortKey::Channel => videos.sort_by_cached_key(|video| {
+            (
+                video.channel.as_deref().unwrap_or_default().to_lowercase(),
+                video.title.to_lowercase(),
+            )
+        }),
+        SortKey::Published => {
+            videos.sort_by_key(|video| (video.published_at.is_none(), video.published_at))
+        }
+        SortKey::Added => videos.sort_by_key(|video| (video.added_at.is_none(), video.added_at)),
+        SortKey::Duration => {
+            videos.sort_by_key(|video| (video.duration().is_none(), video.duration()))
+        }
+    }
+}
+
+/// Compute the position updates needed to turn the current order into the desired one.
+///
+/// Both slices hold playlist item IDs. Items are placed front to back, and items that
+/// are already at the right position are left alone, so an almost sorted playlist only
+/// needs a few updates.
+pub fn plan_moves(current: &[String], desired: &[String]) -> Vec<(String, usize)> {
+    let mut current = current.to_vec();
+    let mut moves = Vec::new();
+
+    for (position, item_id) in desired.iter().enumerate() {
+        if current.get(position) == Some(item_id) {
+            continue;
+        }
+
+        if let Some(from) = current.iter().position(|id| id == item_id) {
+            let item = current.remove(from);
+            current.insert(position, item);
+            moves.push((item_id.clone(), position));
+        }
+    }
+
+    moves
+}
+
+/// Reorder a playlist so its items follow `desired` (playlist item IDs).
+///
+/// On large playlists the API applies position updates with a delay, so the order
+/// reported right after a batch of moves can be stale or slightly off. After each
+/// pass, the playlist is fetched again and the remaining differences are fixed, up
+/// to a few passes. Items added or removed in the meantime are left where they are.
+///
+/// `videos` is the current content of the playlist. Returns the number of items that
+/// failed to move in the last pass.
+pub async fn apply_order(
+    provider: &dyn Provider,
+    playlist_id: &str,
+    mut videos: Vec<VideoInfo>,
+    desired: &[String],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    for pass in 0..MAX_PASSES {
+        if pass > 0 {
+            videos = provider.get_playlist_items(playlist_id).await?;
+        }
+
+        let current: Vec<String> = videos.iter().map(|v| v.item_id.clone()).collect();
+
+        // Keep unknown items (added since the order was computed) at the end
+        let mut target: Vec<String> = desired
+            .iter()
+            .filter(|id| current.contains(id))
+            .cloned()
+            .collect();
+        target.extend(current.iter().filter(|id| !desired.contains(id)).cloned());
+
+        let moves = plan_moves(&current, &target);
+        if moves.is_empty() {
+            return Ok(0);
+        }
+
+        let last_position = current.len().saturating_sub(1);
+        let mut failed = 0;
+
+        for (item_id, position) in &moves {
+            let Some(video) = videos.iter().find(|v| v.item_id == *item_id) else {
+                continue;
+            };
+
+            if let Err(e) = provider
+                .move_playlist_item(playlist_id, video, (*position).min(last_position) as u32)
+                .await
+            {
+                failed += 1;
+                log::warning(format!("Failed to move '{}': {}", video.title, e))?;
+            }
+        }
+
+        // Retrying failed moves would only burn quota on the same errors
+        if failed > 0 || pass + 1 == MAX_PASSES {
+            return Ok(failed);
+        }
+    }
+
+    Ok(0)
+}
+
+/// Sort a playlist by `by`, like `playsync sort`, or preview the new order on a dry run
+pub async fn sort_playlist(
+    client: &dyn Provider,
+    playlist_id: &str,
+    by: SortKey,
+    reverse: bool,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Fetching playlist items");
+    let title = client.get_playlist_title(playlist_id).await?;
+    let mut videos = client.get_playlist_items(playlist_id).await?;
+    if by.needs_details() {
+        client.enrich_videos(&mut videos).await?;
+    }
+    sp.stop(&format!("Found {} videos in '{}'", videos.len(), title));
+
+    let mut sorted = videos.clone();
+    sort_videos(&mut sorted, by);
+    if reverse {
+        sorted.reverse();
+    }
+
+    reorder(
+        client,
+        playlist_id,
+        &title,
+        &videos,
+        &sorted,
+        dry_run,
+        "sort",
+    )
+    .await
+}
+
+/// Shuffle a playlist, like `playsync shuffle`, the same way each time for the same
+/// `seed`, or preview the new order on a dry run
+pub async fn shuffle_playlist(
+    client: &dyn Provider,
+    playlist_id: &str,
+    seed: Option<u64>,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Fetching playlist items");
+    let title = client.get_playlist_title(playlist_id).await?;
+    let videos = client.get_playlist_items(playlist_id).await?;
+    sp.stop(&format!("Found {} videos in '{}'", videos.len(), title));
+
+    let mut rng = match seed {
+        Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+        None => rand::rngs::StdRng::from_entropy(),
+    };
+
+    let mut shuffled = videos.clone();
+    shuffled.shuffle(&mut rng);
+
+    reorder(
+        client,
+        playlist_id,
+        &title,
+        &videos,
+        &shuffled,
+        dry_run,
+        "shuffle",
+    )
+    .await
+}
+
+/// Reorder a playlist to match `reordered`, or preview the new order on a dry run
+async fn reorder(
+    client: &dyn Provider,
+    playlist_id: &str,
+    title: &str,
+    videos: &[VideoInfo],
+    reordered: &[VideoInfo],
+    dry_run: bool,
+    command: &str,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let current: Vec<String> = videos.iter().map(|v| v.item_id.clone()).collect();
+    let desired: Vec<String> = reordered.iter().map(|v| v.item_id.clone()).collect();
+    let moves = plan_moves(&current, &desired);
+
+    if moves.is_empty() {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        log::info(format!("New order ({} items to move):", moves.len()))?;
+        for (position, video) in reordered.iter().enumerate() {
+            let old_position = current
+                .iter()
+                .position(|id| *id == video.item_id)
+                .unwrap_or(position);
+            let marker = if old_position == position { " " } else { "*" };
+            log::info(format!(
+                "{} {}. {} (was #{})",
+                marker,
+                position + 1,
+                video.title,
+                old_position + 1
+            ))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut record = PlaylistRecord::new(playlist_id, title);
+    record.item_count = Some(videos.len());
+
+    let sp = ui::spinner();
+    sp.start(&format!("Moving {} items", moves.len()));
+    record.failed = apply_order(client, playlist_id, videos.to_vec(), &desired).await?;
+    sp.stop(&if record.failed == 0 {
+        "Moved all items".to_string()
+    } else {
+        format!("{} items could not be moved", record.failed)
+    });
+
+    history::record_run(command, dry_run, vec![record], client.quota_used())?;
+    Ok(Outcome::Done(()))
+}
@@ -0,0 +1,443 @@
+use crate::import::{self, ImportFormat};
+use crate::matcher::{self, Track};
+use crate::provider::Provider;
+use crate::ui::log;
+use crate::youtube::{self, SearchQuery, VideoInfo};
+use crate::{lastfm, listenbrainz, plugin, secret, spotify};
+use chrono::{Duration, NaiveDate, Utc};
+use std::path::PathBuf;
+
+/// Number of days of uploads taken from the subscriptions feed when no window is given
+pub const DEFAULT_LOOKBACK_DAYS: i64 = 7;
+
+/// Number of results taken from a search when no `max` is given
+pub const DEFAULT_SEARCH_RESULTS: u32 = 25;
+
+/// A source of videos, as written in the `sync_from` list of a playlist
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// A YouTube playlist, by ID
+    Playlist(String),
+    /// Recent uploads of every channel the account subscribes to,
+    /// written `subscriptions` or `subscriptions:14d`
+    Subscriptions { lookback_days: i64 },
+    /// Videos liked by the account, written `LL` or `liked`
+    Liked,
+    /// Results of a saved YouTube search, written e.g. `search:q="artist name" order=date`
+    Search(SearchQuery),
+    /// YouTube videos linked from an RSS or Atom feed, written as the feed URL
+    Feed(String),
+    /// A local playlist of another app, read from an export file on each sync,
+    /// written e.g. `newpipe:/path/to/export.zip#Playlist name`
+    Import {
+        format: ImportFormat,
+        path: PathBuf,
+        playlist: String,
+    },
+    /// Tracks loved by a Last.fm user, matched to YouTube videos, written `lastfm:USER`
+    LastFm { user: String },
+    /// Recordings loved by a ListenBrainz user (`listenbrainz:USER`), or the latest
+    /// playlist generated for them (`listenbrainz:USER:weekly-jams`), matched to YouTube videos
+    ListenBrainz {
+        user: String,
+        playlist: Option<String>,
+    },
+    /// Tracks of a public Spotify playlist, matched to YouTube videos,
+    /// written `spotify:PLAYLIST_ID` or as an `open.spotify.com` playlist URL
+    Spotify { playlist_id: String },
+    /// Videos provided by a WebAssembly plugin of the plugins directory,
+    /// written `plugin:NAME` or `plugin:NAME:ARGUMENT`
+    Plugin { name: String, argument: String },
+}
+
+/// ID of the special playlist holding the videos liked by the authenticated account
+const LIKED_PLAYLIST_ID: &str = "LL";
+
+impl Source {
+    /// Parse a `sync_from` entry. Anything that is not a known virtual source is a playlist ID.
+    pub fn parse(value: &str) -> Self {
+        if value == LIKED_PLAYLIST_ID || value.eq_ignore_ascii_case("liked") {
+            return Source::Liked;
+        }
+
+        if let Some(playlist) = value
+            .strip_prefix("spotify:")
+            .or_else(|| value.strip_prefix("https://open.spotify.com/"))
+        {
+            return Source::Spotify {
+                playlist_id: spotify::parse_playlist_id(playlist),
+            };
+        }
+
+        if value.starts_with("https://") || value.starts_with("http://") {
+            return Source::Feed(value.to_string());
+        }
+
+        if let Some((prefix, rest)) = value.split_once(':')
+            && let (Some(format), Some((path, playlist))) =
+                (ImportFormat::from_prefix(prefix), rest.rsplit_once('#'))
+        {
+            return Source::Import {
+                format,
+                path: PathBuf::from(path),
+                playlist: playlist.to_string(),
+            };
+        }
+
+        if let Some(rest) = value.strip_prefix("plugin:") {
+            let (name, argument) = rest.split_once(':').unwrap_or((rest, ""));
+            return Source::Plugin {
+                name: name.to_string(),
+                argument: argument.to_string(),
+            };
+        }
+
+        if let Some(user) = value.strip_prefix("lastfm:") {
+            return Source::LastFm {
+                user: user.to_string(),
+            };
+        }
+
+        if let Some(rest) = value.strip_prefix("listenbrainz:") {
+            let (user, playlist) = match rest.split_once(':') {
+                Some((user, playlist)) => (user, Some(playlist.to_string())),
+                None => (rest, None),
+            };
+
+            return Source::ListenBrainz {
+                user: user.to_string(),
+                playlist,
+            };
+        }
+
+        if let Some(search) = value.strip_prefix("search:")
+            && let Some(query) = parse_search(search)
+        {
+            return Source::Search(query);
+        }
+
+        if let Some(window) = value.strip_prefix("subscriptions") {
+            let days = match window.strip_prefix(':') {
+                Some(days) => days.trim_end_matches('d').parse().ok(),
+                None if window.is_empty() => Some(DEFAULT_LOOKBACK_DAYS),
+                None => None,
+            };
+
+            if let Some(lookback_days) = days {
+                return Source::Subscriptions { lookback_days };
+            }
+        }
+
+        Source::Playlist(value.to_string())
+    }
+
+    /// The playlist ID of the source, if it is a regular playlist
+    pub fn playlist_id(&self) -> Option<&str> {
+        match self {
+            Source::Playlist(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Whether the source can be read offline: from the playlist cache, or from a file
+    pub fn works_offline(&self) -> bool {
+        matches!(
+            self,
+            Source::Playlist(_) | Source::Liked | Source::Import { .. }
+        )
+    }
+
+    /// Whether the source lists music tracks, which are matched to YouTube videos
+    pub fn is_music(&self) -> bool {
+        matches!(
+            self,
+            Source::LastFm { .. } | Source::ListenBrainz { .. } | Source::Spotify { .. }
+        )
+    }
+
+    /// Whether the source returns all of its videos, rather than only recent or top ones
+    pub fn is_complete(&self) -> bool {
+        !matches!(
+            self,
+            Source::Subscriptions { .. }
+                | Source::Search(_)
+                | Source::Feed(_)
+                | Source::Plugin { .. }
+                | Source::ListenBrainz {
+                    playlist: Some(_),
+                    ..
+                }
+        )
+    }
+
+    /// Human-readable description of a virtual source, used in listings
+    pub fn describe(&self) -> String {
+        match self {
+            Source::Playlist(id) => format!("Playlist {}", id),
+            Source::Subscriptions { lookback_days } => {
+                format!("Subscriptions feed (last {} days)", lookback_days)
+            }
+            Source::Liked => "Liked videos".to_string(),
+            Source::Search(search) => format!("Search \"{}\"", search.query),
+            Source::Feed(url) => format!("Feed {}", url),
+            Source::Import {
+                format,
+                path,
+                playlist,
+            } => format!(
+                "{} playlist '{}' ({})",
+                format.prefix(),
+                playlist,
+                path.display()
+            ),
+            Source::LastFm { user } => format!("Last.fm loved tracks of {}", user),
+            Source::ListenBrainz {
+                user,
+                playlist: None,
+            } => format!("ListenBrainz loved recordings of {}", user),
+            Source::ListenBrainz {
+                user,
+                playlist: Some(playlist),
+            } => format!("ListenBrainz {} of {}", playlist, user),
+            Source::Spotify { playlist_id } => format!("Spotify playlist {}", playlist_id),
+            Source::Plugin { name, argument } if argument.is_empty() => {
+                format!("Plugin {}", name)
+            }
+            Source::Plugin { name, argument } => format!("Plugin {} ({})", name, argument),
+        }
+    }
+}
+
+/// Credentials of the music services read by `lastfm:`, `listenbrainz:` and `spotify:`
+/// sources, and whether their tracks are resolved on MusicBrainz first. See
+/// [`Config::music_services`](crate::config::Config::music_services).
+#[derive(Clone, Debug, Default)]
+pub struct MusicServices {
+    pub lastfm_api_key: Option<String>,
+    pub listenbrainz_token: Option<String>,
+    pub spotify_client_id: Option<String>,
+    pub spotify_client_secret: Option<String>,
+    pub musicbrainz: bool,
+}
+
+/// Fetch the videos of a source.
+///
+/// Subscription uploads, search results, feed entries and liked videos are returned
+/// oldest first, so they end up in the target playlist in the order they were
+/// published or liked. Music sources are read with the credentials given to the provider
+/// with [`Provider::use_music_services`].
+pub async fn fetch_videos(
+    provider: &dyn Provider,
+    source: &Source,
+) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+    if provider.is_offline() && !source.works_offline() {
+        return Err(format!("{} can't be read offline", source.describe()).into());
+    }
+
+    match source {
+        Source::Playlist(id) => provider.get_playlist_items(id).await,
+        Source::Subscriptions { lookback_days } => {
+            let since = Utc::now() - Duration::days(*lookback_days);
+            let mut videos = provider.subscription_uploads(since).await?;
+            videos.sort_by_key(|video| video.published_at);
+            Ok(videos)
+        }
+        Source::Search(search) => {
+            let mut videos = provider.search_videos(search).await?;
+            videos.sort_by_key(|video| video.published_at);
+            Ok(videos)
+        }
+        Source::Feed(url) => {
+            let mut videos = fetch_feed(url).await?;
+            videos.sort_by_key(|video| video.published_at);
+            Ok(videos)
+        }
+        Source::Import {
+            format,
+            path,
+            playlist,
+        } => import::read_library(*format, path)?
+            .playlists
+            .into_iter()
+            .find(|p| p.name == *playlist)
+            .map(|p| p.videos)
+            .ok_or_else(|| format!("No playlist '{}' in {}", playlist, path.display()).into()),
+        Source::LastFm { .. } | Source::ListenBrainz { .. } | Source::Spotify { .. } => {
+            let services = provider.music_services();
+            let tracks = fetch_tracks(source, &services).await?;
+            let report = matcher::match_tracks(
+                provider,
+                &tracks,
+                services.musicbrainz,
+                matcher::MAX_LOOKUPS_PER_RUN,
+            )
+            .await?;
+
+            if !report.unmatched.is_empty() {
+                log::warning(format!(
+                    "{} tracks of {} have no match on YouTube",
+                    report.unmatched.len(),
+                    source.describe()
+                ))?;
+            }
+            if !report.deferred.is_empty() {
+                log::info(format!(
+                    "{} tracks will be matched on the next runs, to spare the API quota",
+                    report.deferred.len()
+                ))?;
+            }
+
+            Ok(report.videos())
+        }
+        Source::Plugin { name, argument } => plugin::fetch(name, argument),
+        Source::Liked => {
+            // The liked videos playlist lists the most recent likes first
+            let mut videos = provider.get_playlist_items(LIKED_PLAYLIST_ID).await?;
+            videos.reverse();
+            Ok(videos)
+        }
+    }
+}
+
+/// Fetch the tracks of a music source (see [`Source::is_music`])
+pub async fn fetch_tracks(
+    source: &Source,
+    services: &MusicServices,
+) -> Result<Vec<Track>, Box<dyn std::error::Error>> {
+    match source {
+        Source::LastFm { user } => {
+            let api_key = services
+                .lastfm_api_key
+                .as_deref()
+                .ok_or("Set lastfm_api_key in the configuration to use Last.fm sources")?;
+            lastfm::loved_tracks(&secret::reveal(api_key)?, user).await
+        }
+        Source::ListenBrainz { user, playlist } => {
+            let token = services
+                .listenbrainz_token
+                .as_deref()
+                .map(secret::reveal)
+                .transpose()?;
+            let token = token.as_deref();
+            match playlist {
+                Some(name) => listenbrainz::generated_playlist(user, name, token).await,
+                None => listenbrainz::loved_recordings(user, token).await,
+            }
+        }
+        Source::Spotify { playlist_id } => {
+            let (Some(client_id), Some(client_secret)) =
+                (&services.spotify_client_id, &services.spotify_client_secret)
+            else {
+                return Err(
+                    "Set spotify_client_id and spotify_client_secret in the configuration to use Spotify sources"
+                        .into(),
+                );
+            };
+            spotify::playlist_tracks(client_id, &secret::reveal(client_secret)?, playlist_id).await
+        }
+        _ => Err(format!("{} is not a music source", source.describe()).into()),
+    }
+}
+
+/// Download an RSS or Atom feed and collect the YouTube videos its entries point to.
+///
+/// This costs no API quota: channel feeds (`https://www.youtube.com/feeds/videos.xml?channel_id=...`)
+/// carry the video ID in the entry ID, and other feeds (e.g. podcasts) are scanned for
+/// YouTube links. Entries without a YouTube video are skipped.
+async fn fetch_feed(url: &str) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+    let body = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    let feed = feed_rs::parser::parse(&body[..])?;
+
+    let videos = feed
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let links = entry.links.iter().map(|link| link.href.clone()).chain(
+                entry.media.iter().flat_map(|media| {
+                    media
+                        .content
+                        .iter()
+                        .filter_map(|content| content.url.as_ref().map(|url| url.to_string()))
+                }),
+            );
+
+            let video_id = entry
+                .id
+                .strip_prefix("yt:video:")
+                .map(str::to_string)
+                .or_else(|| {
+                    links
+                        .filter(|link| link.contains("youtu"))
+                        .find_map(|link| youtube::parse_video_id(&link))
+                })?;
+
+            Some(VideoInfo {
+                video_id,
+                title: entry.title.map(|title| title.content).unwrap_or_default(),
+                channel: entry.authors.first().map(|author| author.name.clone()),
+                item_id: String::new(),
+                added_at: None,
+                published_at: entry.published.or(entry.updated),
+                details: None,
+            })
+        })
+        .collect();
+
+    Ok(videos)
+}
+
+/// Build the `sync_from` entry reading a local playlist from an export file
+pub fn import_source(format: ImportFormat, path: &std::path::Path, playlist: &str) -> String {
+    format!("{}:{}#{}", format.prefix(), path.display(), playlist)
+}
+
+/// Parse the `key=value` pairs of a saved search. Values with spaces must be quoted.
+///
+/// Supported keys are `q`, `order`, `channel` (channel ID), `after` (YYYY-MM-DD) and
+/// `max`. `type=video` is accepted for readability, since only videos can be synced.
+fn parse_search(value: &str) -> Option<SearchQuery> {
+    let mut search = SearchQuery {
+        max_results: DEFAULT_SEARCH_RESULTS,
+        ..Default::default()
+    };
+
+    for (key, value) in split_pairs(value)? {
+        match key.as_str() {
+            "q" => search.query = value,
+            "order" => search.order = Some(value),
+            "channel" => search.channel_id = Some(value),
+            "after" => {
+                let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?;
+                search.published_after = Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+            }
+            "max" => search.max_results = value.parse().ok()?,
+            "type" if value == "video" => {}
+            _ => return None,
+        }
+    }
+
+    (!search.query.is_empty()).then_some(search)
+}
+
+/// Split `a=b c="d e"` into key/value pairs, honoring double quotes
+fn split_pairs(value: &str) -> Option<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut rest = value.trim_start();
+
+    while !rest.is_empty() {
+        let (key, after_key) = rest.split_once('=')?;
+
+        let (value, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, after) = quoted.split_once('"')?;
+                (value, after)
+            }
+            None => after_key.split_at(after_key.find(' ').unwrap_or(after_key.len())),
+        };
+
+        pairs.push((key.trim().to_string(), value.to_string()));
+        rest = after_value.trim_start();
+    }
+
+    Some(pairs)
+}
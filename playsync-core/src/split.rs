@@ -0,0 +1,126 @@
+use crate::history::{self, PlaylistRecord};
+use crate::provider::Provider;
+use crate::run::Outcome;
+use crate::sync::{self, SyncReport};
+use crate::ui::{self, log};
+use crate::youtube::VideoInfo;
+use std::collections::BTreeMap;
+
+/// How to divide a playlist into parts
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum SplitBy {
+    /// Fixed number of items per part, in playlist order
+    Count,
+    /// One part per year the items were added to the playlist
+    Year,
+    /// One part per month the items were added to the playlist
+    Month,
+    /// One part per channel
+    Channel,
+}
+
+/// Group videos into parts, each labelled with the suffix used for its playlist title.
+///
+/// Items keep their playlist order within each part.
+pub fn group_videos(
+    videos: Vec<VideoInfo>,
+    by: SplitBy,
+    size: usize,
+) -> Vec<(String, Vec<VideoInfo>)> {
+    if let SplitBy::Count = by {
+        let size = size.max(1);
+        let mut parts = Vec::new();
+        let mut videos = videos.into_iter().peekable();
+
+        while videos.peek().is_some() {
+            let part: Vec<VideoInfo> = videos.by_ref().take(size).collect();
+            parts.push((format!("Part {}", parts.len() + 1), part));
+        }
+
+        return parts;
+    }
+
+    let mut groups: BTreeMap<String, Vec<VideoInfo>> = BTreeMap::new();
+
+    for video in videos {
+        let label = match by {
+            SplitBy::Year => video.added_at.map(|date| date.format("%Y").to_string()),
+            SplitBy::Month => video.added_at.map(|date| date.format("%Y-%m").to_string()),
+            SplitBy::Channel => video.channel.clone(),
+            SplitBy::Count => unreachable!(),
+        }
+        .unwrap_or_else(|| "Unknown".to_string());
+
+        groups.entry(label).or_default().push(video);
+    }
+
+    groups.into_iter().collect()
+}
+
+/// Split a playlist into new playlists, like `playsync split`, titled after it with the
+/// suffix of their part. Unless `keep` is set, the items copied to a part are removed
+/// from the playlist.
+pub async fn split_playlist(
+    client: &dyn Provider,
+    playlist_id: &str,
+    by: SplitBy,
+    size: usize,
+    privacy: &str,
+    keep: bool,
+    dry_run: bool,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Fetching playlist items");
+    let title = client.get_playlist_title(playlist_id).await?;
+    let videos = client.get_playlist_items(playlist_id).await?;
+    sp.stop(&format!("Found {} videos in '{}'", videos.len(), title));
+
+    let parts = group_videos(videos, by, size);
+
+    if parts.len() < 2 {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if dry_run {
+        for (suffix, videos) in &parts {
+            log::info(format!(
+                "Would create '{} ({})' with {} videos",
+                title,
+                suffix,
+                videos.len()
+            ))?;
+        }
+        return Ok(Outcome::Previewed);
+    }
+
+    let mut records = Vec::new();
+    let mut original = PlaylistRecord::new(playlist_id, &title);
+
+    for (suffix, videos) in parts {
+        let part_title = format!("{} ({})", title, suffix);
+        let part_id = client.create_playlist(&part_title, "", privacy).await?;
+        log::success(format!(
+            "Created playlist '{}' (ID: {})",
+            part_title, part_id
+        ))?;
+
+        let mut report = SyncReport::default();
+        sync::add_videos(client, &part_id, videos, &mut report).await?;
+
+        // Only remove the items that made it into the new playlist
+        if !keep {
+            let mut removal = SyncReport::default();
+            sync::remove_videos(client, playlist_id, report.added.clone(), &mut removal).await?;
+            original.removed += removal.removed.len();
+            original.failed += removal.failed.len();
+        }
+
+        records.push(PlaylistRecord::from_report(&part_id, &part_title, &report));
+    }
+
+    records.push(original);
+    history::record_run("split", dry_run, records, client.quota_used())?;
+
+    Ok(Outcome::Done(()))
+}
@@ -0,0 +1,101 @@
+use crate::matcher::Track;
+use serde::Deserialize;
+use std::time::Duration;
+
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const API_URL: &str = "https://api.spotify.com/v1";
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct TracksPage {
+    items: Vec<PlaylistItem>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PlaylistItem {
+    /// Missing for tracks that were removed from Spotify
+    track: Option<SpotifyTrack>,
+}
+
+#[derive(Deserialize)]
+struct SpotifyTrack {
+    name: String,
+    duration_ms: u64,
+    artists: Vec<SpotifyArtist>,
+}
+
+#[derive(Deserialize)]
+struct SpotifyArtist {
+    name: String,
+}
+
+/// Extract a playlist ID from `ID`, `playlist:ID` or an `open.spotify.com/playlist/ID` URL
+pub fn parse_playlist_id(value: &str) -> String {
+    let id = value
+        .rsplit_once("playlist/")
+        .or_else(|| value.rsplit_once("playlist:"))
+        .map_or(value, |(_, id)| id);
+
+    id.split(['?', '/']).next().unwrap_or(id).to_string()
+}
+
+/// Get the tracks of a Spotify playlist, in playlist order.
+///
+/// Uses the client credentials flow, so only public playlists can be read.
+pub async fn playlist_tracks(
+    client_id: &str,
+    client_secret: &str,
+    playlist_id: &str,
+) -> Result<Vec<Track>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    let token: TokenResponse = client
+        .post(TOKEN_URL)
+        .basic_auth(client_id, Some(client_secret))
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let mut tracks = Vec::new();
+    let mut next = Some(format!(
+        "{}/playlists/{}/tracks?limit=100&fields=items(track(name,duration_ms,artists(name))),next",
+        API_URL, playlist_id
+    ));
+
+    while let Some(url) = next {
+        let page: TracksPage = client
+            .get(url)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        tracks.extend(page.items.into_iter().filter_map(|item| {
+            let track = item.track?;
+            Some(Track {
+                artist: track
+                    .artists
+                    .into_iter()
+                    .map(|artist| artist.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                title: track.name,
+                duration: Some(Duration::from_millis(track.duration_ms)),
+            })
+        }));
+
+        next = page.next;
+    }
+
+    Ok(tracks)
+}
@@ -0,0 +1,185 @@
+use crate::config::Playlist;
+use crate::history::{self, PlaylistRecord};
+use crate::output;
+use crate::provider::Provider;
+use crate::ui;
+use crate::youtube::{Availability, VideoInfo};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Number of channels listed in the "top channels" section
+pub const TOP_CHANNELS: usize = 5;
+
+/// Sum the durations of the given videos, ignoring videos with an unknown duration
+pub fn total_duration(videos: &[VideoInfo]) -> Duration {
+    videos.iter().filter_map(VideoInfo::duration).sum()
+}
+
+/// Count the videos that can no longer be watched by anyone but their owner
+pub fn unavailable_count(videos: &[VideoInfo]) -> usize {
+    videos
+        .iter()
+        .filter(|video| {
+            video.details.as_ref().is_some_and(|details| {
+                matches!(
+                    details.availability,
+                    Availability::Private | Availability::Unavailable
+                )
+            })
+        })
+        .count()
+}
+
+/// Count videos per channel and return the `limit` most frequent channels
+pub fn top_channels(videos: &[VideoInfo], limit: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for video in videos {
+        if let Some(channel) = &video.channel {
+            *counts.entry(channel).or_default() += 1;
+        }
+    }
+
+    let mut channels: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(channel, count)| (channel.to_string(), count))
+        .collect();
+
+    // Sort by count, then by name so the output is stable between runs
+    channels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    channels.truncate(limit);
+
+    channels
+}
+
+/// Count the videos shared by two playlists
+pub fn overlap(a: &[VideoInfo], b: &[VideoInfo]) -> usize {
+    let a_ids: HashSet<&str> = a.iter().map(|video| video.video_id.as_str()).collect();
+    let b_ids: HashSet<&str> = b.iter().map(|video| video.video_id.as_str()).collect();
+
+    a_ids.intersection(&b_ids).count()
+}
+
+/// Format a duration as e.g. `3h 05m 12s`
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, (seconds / 60) % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+/// Show the statistics of playlists and the videos they share, like `playsync stats`:
+/// a report per playlist, or all of them at once in JSON or YAML
+pub async fn show(
+    client: &dyn Provider,
+    playlists: &[Playlist],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sp = ui::spinner();
+    sp.start("Fetching playlist items");
+
+    let mut items = Vec::new();
+    for playlist in playlists {
+        items.push(client.get_playlist_items(&playlist.id).await?);
+    }
+
+    sp.set_message("Fetching video details");
+    for videos in &mut items {
+        client.enrich_videos(videos).await?;
+    }
+    sp.stop(&format!("Fetched {} playlists", playlists.len()));
+
+    let previous_runs = history::read_all()?;
+    let mut records = Vec::new();
+    let mut results = output::Stats {
+        playlists: Vec::new(),
+        overlaps: Vec::new(),
+    };
+
+    for (playlist, videos) in playlists.iter().zip(&items) {
+        let items_change = history::last_item_count(&previous_runs, &playlist.id)
+            .map(|previous| videos.len() as i64 - previous as i64);
+        let total_duration = total_duration(videos);
+        let unavailable = unavailable_count(videos);
+        let live = videos.iter().filter(|video| video.is_live()).count();
+        let top_channels = top_channels(videos, TOP_CHANNELS);
+
+        if !output::is_structured() {
+            let mut body = format!("Items: {}", videos.len());
+            if let Some(change) = items_change {
+                body.push_str(&format!(" ({:+} since last run)", change));
+            }
+
+            body.push_str(&format!(
+                "\nTotal duration: {}",
+                format_duration(total_duration)
+            ));
+
+            if unavailable > 0 {
+                body.push_str(&format!("\nUnavailable: {}", unavailable));
+            }
+
+            if live > 0 {
+                body.push_str(&format!("\nLive or upcoming streams: {}", live));
+            }
+
+            if !top_channels.is_empty() {
+                body.push_str("\nTop channels:");
+                for (rank, (channel, count)) in top_channels.iter().enumerate() {
+                    body.push_str(&format!("\n  {}. {} ({})", rank + 1, channel, count));
+                }
+            }
+
+            ui::note(format!("{} (ID: {})", playlist.title, playlist.id), body)?;
+        }
+
+        results.playlists.push(output::PlaylistStats {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            items: videos.len(),
+            items_change,
+            total_duration_seconds: total_duration.as_secs(),
+            unavailable,
+            live,
+            top_channels: top_channels
+                .into_iter()
+                .map(|(channel, videos)| output::ChannelCount { channel, videos })
+                .collect(),
+        });
+
+        records.push(PlaylistRecord {
+            item_count: Some(videos.len()),
+            ..PlaylistRecord::new(&playlist.id, &playlist.title)
+        });
+    }
+
+    let mut overlaps = String::new();
+    for (i, (a, a_videos)) in playlists.iter().zip(&items).enumerate() {
+        for (b, b_videos) in playlists.iter().zip(&items).skip(i + 1) {
+            let shared = overlap(a_videos, b_videos);
+            if shared > 0 {
+                overlaps.push_str(&format!("{} ↔ {}: {} shared\n", a.title, b.title, shared));
+                results.overlaps.push(output::Overlap {
+                    a: a.id.clone(),
+                    b: b.id.clone(),
+                    shared,
+                });
+            }
+        }
+    }
+    if output::is_structured() {
+        output::print(&results)?;
+    } else if playlists.len() > 1 {
+        if overlaps.is_empty() {
+            ui::note("Overlap", "No videos shared between playlists")?;
+        } else {
+            ui::note("Overlap", overlaps.trim_end())?;
+        }
+    }
+
+    history::record_run("stats", false, records, client.quota_used())?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use crate::config::{Config, Playlist};
+use crate::provider::Provider;
+use crate::source::Source;
+use crate::sync::{self, SkipCode};
+use crate::ui::{self, log};
+use crate::youtube::{PlaylistState, VideoInfo};
+use crate::{history, output};
+use std::collections::{BTreeMap, HashMap};
+
+/// Live states of playlists by ID, see [`playlist_states`]
+pub type PlaylistStates = HashMap<String, PlaylistState>;
+
+/// Read the item count and privacy status of playlists: the configured ones signed in to
+/// their account, so their private playlists can be read, the others (like playlist
+/// sources) with `client`
+pub async fn playlist_states(
+    cfg: &Config,
+    client: &dyn Provider,
+    ids: &[String],
+) -> Result<PlaylistStates, Box<dyn std::error::Error>> {
+    let mut by_account: BTreeMap<Option<&String>, Vec<String>> = BTreeMap::new();
+    for id in ids {
+        let account = cfg
+            .playlists
+            .iter()
+            .find(|p| p.id == *id)
+            .and_then(|p| p.account.as_ref());
+        by_account.entry(account).or_default().push(id.clone());
+    }
+
+    let mut states = PlaylistStates::new();
+    for (account, ids) in by_account {
+        match account {
+            Some(account) => {
+                let oauth2_json = cfg
+                    .oauth2_json
+                    .as_ref()
+                    .ok_or("OAuth2 JSON path is not set")?;
+                let account_client = client.for_account(oauth2_json, account).await?;
+                states.extend(account_client.get_playlist_states(&ids).await?);
+            }
+            None => states.extend(client.get_playlist_states(&ids).await?),
+        }
+    }
+    Ok(states)
+}
+
+/// Privacy status of a playlist for listings, `unavailable` when it can't be read
+pub fn privacy_label(state: Option<&PlaylistState>) -> String {
+    state.map_or("unavailable".to_string(), |state| state.privacy.clone())
+}
+
+/// Check how far playlists are behind their sources, like `playsync status`, and show
+/// it, standing out the playlists and sources that can't be read and the playlists
+/// that shrank since the last run. Returns the number of playlists behind.
+pub async fn check(
+    cfg: &Config,
+    client: &dyn Provider,
+    playlists: &[Playlist],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    // The targets and their playlist sources, read at once to spot the ones that went
+    // private, were deleted or shrank
+    let mut ids: Vec<String> = Vec::new();
+    for playlist in playlists {
+        ids.push(playlist.id.clone());
+        for entry in playlist.sync_from.iter().flatten() {
+            if let Source::Playlist(id) = Source::parse(entry)
+                && !ids.contains(&id)
+            {
+                ids.push(id);
+            }
+        }
+    }
+    let states = playlist_states(cfg, client, &ids).await?;
+    let entries = history::read_all()?;
+
+    let mut behind = 0;
+    let mut statuses = Vec::new();
+    for playlist in playlists {
+        let sync_from = playlist.sync_from.as_deref().unwrap_or_default();
+        let state = states.get(&playlist.id);
+        let unavailable_sources: Vec<String> = sync_from
+            .iter()
+            .filter_map(|entry| match Source::parse(entry) {
+                Source::Playlist(id) if !states.contains_key(&id) => Some(id),
+                _ => None,
+            })
+            .collect();
+
+        let mut status = output::PlaylistStatus {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            behind: 0,
+            item_count: state.map(|state| state.item_count as usize),
+            privacy: privacy_label(state),
+            unavailable_sources,
+        };
+
+        let Some(state) = state else {
+            behind += 1;
+            log::error(format!(
+                "{}: can't be read, it was deleted or is private to another account",
+                playlist.title
+            ))?;
+            statuses.push(status);
+            continue;
+        };
+        if let Some(previous) = history::last_item_count(&entries, &playlist.id)
+            .filter(|previous| *previous > state.item_count as usize)
+        {
+            log::warning(format!(
+                "{}: {} items, down from {} at the last run",
+                playlist.title, state.item_count, previous
+            ))?;
+        }
+        if !status.unavailable_sources.is_empty() {
+            behind += 1;
+            log::error(format!(
+                "{}: can't read the source playlists {}, they were deleted or made private",
+                playlist.title,
+                status.unavailable_sources.join(", ")
+            ))?;
+            statuses.push(status);
+            continue;
+        }
+
+        let sp = ui::spinner();
+        sp.start(&format!("Checking {}", playlist.title));
+        let pending = sync::pending_changes(client, &playlist.id, sync_from).await?;
+        sp.clear();
+
+        let count = pending.videos_to_add.len();
+        if count == 0 {
+            log::success(format!(
+                "{}: up to date ({} items, {})",
+                playlist.title, state.item_count, state.privacy
+            ))?;
+        } else {
+            behind += 1;
+            log::warning(format!(
+                "{}: {} behind sources ({} items, {})",
+                playlist.title, count, state.item_count, state.privacy
+            ))?;
+        }
+        status.behind = count;
+        statuses.push(status);
+    }
+    output::print(&statuses)?;
+
+    Ok(behind)
+}
+
+/// Explain whether the next sync of `playlist` adds a video, like `playsync explain`:
+/// already in the playlist, added from one of its sources, skipped with the reason, or
+/// in none of its sources
+pub async fn explain(
+    client: &dyn Provider,
+    playlist: &Playlist,
+    sync_from: &[String],
+    video_id: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let selection = sync::select_videos(client, playlist, sync_from).await?;
+
+    let is_video = |video: &VideoInfo| video.video_id == video_id;
+    let source_of = |video: &VideoInfo| {
+        selection
+            .pending
+            .sources
+            .get(&video.video_id)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let explanation =
+        if let Some(position) = selection.pending.target_videos.iter().position(is_video) {
+            let video = &selection.pending.target_videos[position];
+            format!(
+                "✅ '{}' is already in '{}', at position {}",
+                video.title,
+                playlist.title,
+                position + 1
+            )
+        } else if let Some(video) = selection.videos_to_add.iter().find(|v| is_video(v)) {
+            format!(
+                "➕ '{}' is added by the next sync, from {}",
+                video.title,
+                source_of(video)
+            )
+        } else if let Some((video, reason)) = selection
+            .skipped
+            .iter()
+            .filter(|(video, _)| is_video(video))
+            // A duplicate was left out for the same reason as its first occurrence
+            .min_by_key(|(_, reason)| reason.code == SkipCode::Duplicate)
+        {
+            format!(
+                "⏭️ '{}' from {} is skipped [{}]: {}",
+                video.title,
+                source_of(video),
+                reason.code.as_str(),
+                reason.message
+            )
+        } else {
+            format!(
+                "❔ {} is in none of the sources of '{}': {}",
+                video_id,
+                playlist.title,
+                sync_from.join(", ")
+            )
+        };
+
+    Ok(explanation)
+}
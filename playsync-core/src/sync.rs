@@ -0,0 +1,1096 @@
+use crate::config::{InsertPosition, Playlist, SyncDirection};
+use crate::diff;
+use crate::exit;
+use crate::output;
+use crate::plugin;
+use crate::provider::Provider;
+use crate::script::{Script, Verdict};
+use crate::source::Source;
+use crate::ui::{self, log};
+use crate::youtube::VideoInfo;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Outcome of syncing a single target playlist
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Videos added to the target playlist (or that would be added, on a dry run)
+    pub added: Vec<VideoInfo>,
+    /// Videos removed from the target playlist
+    pub removed: Vec<VideoInfo>,
+    /// Videos that could not be added or removed, with the error message
+    pub failed: Vec<(VideoInfo, String)>,
+    /// Videos of the sources left out, with the reason, only filled by [`sync_playlist`]
+    pub skipped: Vec<(VideoInfo, SkipReason)>,
+    /// Number of items in the target playlist after the sync
+    pub item_count: usize,
+    /// Videos in the target playlist after the sync, only filled by [`sync_playlist`]
+    pub videos: Vec<VideoInfo>,
+    /// Item IDs of the target playlist before the sync, in order, only filled by
+    /// [`sync_playlist`]
+    pub previous_item_ids: Vec<String>,
+}
+
+/// Whether a target playlist is ready to be synced
+#[derive(Debug)]
+pub enum TargetState {
+    /// The playlist exists on YouTube
+    Ready,
+    /// The playlist was just created, with the given ID
+    Created(String),
+    /// The playlist does not exist and was not created
+    Missing,
+    /// The playlist does not exist and would be created, on a dry run. Its sync can be
+    /// previewed with [`preview_new_playlist`].
+    ToCreate,
+}
+
+/// Make sure a target playlist exists, creating it if it is a `new:"Title"` placeholder
+/// or if it was deleted and is configured to be recreated.
+pub async fn ensure_target(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    dry_run: bool,
+) -> Result<TargetState, Box<dyn std::error::Error>> {
+    let placeholder_title = target_playlist.placeholder_title();
+
+    if placeholder_title.is_none() {
+        if provider.playlist_exists(&target_playlist.id).await? {
+            return Ok(TargetState::Ready);
+        }
+
+        if !target_playlist.recreate_if_missing {
+            log::warning(format!(
+                "Playlist '{}' (ID: {}) no longer exists, skipping it. Set recreate_if_missing to recreate it.",
+                target_playlist.title, target_playlist.id
+            ))?;
+            return Ok(TargetState::Missing);
+        }
+    }
+
+    let title = placeholder_title.unwrap_or(&target_playlist.title);
+
+    if dry_run {
+        log::info(format!("Would create playlist '{}'", title))?;
+        return Ok(TargetState::ToCreate);
+    }
+
+    let playlist_id = provider
+        .create_playlist(
+            title,
+            target_playlist.description.as_deref().unwrap_or_default(),
+            target_playlist.privacy.as_deref().unwrap_or("private"),
+        )
+        .await?;
+    log::success(format!(
+        "Created playlist '{}' (ID: {})",
+        title, playlist_id
+    ))?;
+
+    Ok(TargetState::Created(playlist_id))
+}
+
+/// Why a video of the sources isn't added to a target playlist, as a stable code for
+/// reports and scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipCode {
+    /// Already found in an earlier source
+    Duplicate,
+    /// The title doesn't match the `match` regex of the filter
+    FilteredTitle,
+    /// Not from the `channel` of the filter
+    FilteredChannel,
+    /// Published outside the `after` and `before` dates of the filter
+    FilteredDate,
+    /// Shorter or longer than the durations of the filter
+    FilteredDuration,
+    /// A live stream or upcoming premiere, with `no-live`
+    Live,
+    /// Left out by the playlist script
+    Script,
+    /// Replaced by another video by the playlist script
+    Replaced,
+    /// Rejected by a filter plugin
+    Plugin,
+    /// Over `max_adds`, added on a later sync
+    OverLimit,
+}
+
+impl SkipCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkipCode::Duplicate => "duplicate",
+            SkipCode::FilteredTitle => "filtered_title",
+            SkipCode::FilteredChannel => "filtered_channel",
+            SkipCode::FilteredDate => "filtered_date",
+            SkipCode::FilteredDuration => "filtered_duration",
+            SkipCode::Live => "live",
+            SkipCode::Script => "script",
+            SkipCode::Replaced => "replaced",
+            SkipCode::Plugin => "plugin",
+            SkipCode::OverLimit => "over_limit",
+        }
+    }
+}
+
+/// Why a video was left out, with a message for people
+#[derive(Debug, Clone)]
+pub struct SkipReason {
+    pub code: SkipCode,
+    pub message: String,
+}
+
+impl SkipReason {
+    pub fn new(code: SkipCode, message: impl Into<String>) -> Self {
+        SkipReason {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Changes needed to bring a target playlist up to date with its sources
+#[derive(Debug, Default)]
+pub struct PendingChanges {
+    /// Number of items currently in the target playlist
+    pub target_count: usize,
+    /// Videos currently in the target playlist
+    pub target_videos: Vec<VideoInfo>,
+    /// Videos from the sources that are missing from the target, without duplicates
+    pub videos_to_add: Vec<VideoInfo>,
+    /// Videos missing from the target that were already found in an earlier source
+    pub duplicates: Vec<VideoInfo>,
+    /// IDs of all the videos of the sources
+    pub source_video_ids: HashSet<String>,
+    /// `sync_from` entry each video to add was found in, by video ID
+    pub sources: HashMap<String, String>,
+}
+
+/// Compare a target playlist against its sources without making any changes
+pub async fn pending_changes(
+    provider: &dyn Provider,
+    target_playlist_id: &str,
+    source_playlist_ids: &[String],
+) -> Result<PendingChanges, Box<dyn std::error::Error>> {
+    collect_changes(
+        provider,
+        Some(target_playlist_id),
+        source_playlist_ids,
+        None,
+    )
+    .await
+}
+
+/// Compare a target playlist against its sources like [`pending_changes`], a target not
+/// created yet (`None`) being empty. With the title of the target, the target and the
+/// source playlists are fetched with progress bars.
+async fn collect_changes(
+    provider: &dyn Provider,
+    target_playlist_id: Option<&str>,
+    source_playlist_ids: &[String],
+    progress: Option<&str>,
+) -> Result<PendingChanges, Box<dyn std::error::Error>> {
+    // Get existing videos in target playlist
+    let target_videos = match (target_playlist_id, progress) {
+        (None, _) => Vec::new(),
+        (Some(id), Some(title)) => {
+            provider
+                .get_playlist_items_with_progress(id, &format!("'{}'", title))
+                .await?
+        }
+        (Some(id), None) => provider.get_playlist_items(id).await?,
+    };
+    let mut seen_video_ids: HashSet<String> = target_videos
+        .iter()
+        .map(|item| item.video_id.clone())
+        .collect();
+
+    let mut pending = PendingChanges {
+        target_count: target_videos.len(),
+        target_videos,
+        ..Default::default()
+    };
+
+    // Collect videos from all sources. Source playlists can hold thousands of videos, so
+    // they are read a page at a time and only the videos missing from the target are kept.
+    for source_id in source_playlist_ids {
+        let source = Source::parse(source_id);
+        match &source {
+            Source::Playlist(id) => {
+                let label = progress.map(|_| source.describe());
+                let mut pages = provider.playlist_pages(id, label.as_deref()).await?;
+                while let Some(page) = pages.next_page().await? {
+                    pending.add_source_videos(source_id, page, &mut seen_video_ids);
+                }
+            }
+            _ => {
+                let videos = provider.source_videos(source_id).await?;
+                pending.add_source_videos(source_id, videos, &mut seen_video_ids);
+            }
+        }
+    }
+
+    Ok(pending)
+}
+
+impl PendingChanges {
+    /// Sort videos of a source into the videos to add and the duplicates, skipping the
+    /// ones in `seen_video_ids`: the target's and the earlier sources' videos
+    fn add_source_videos(
+        &mut self,
+        source_id: &str,
+        videos: Vec<VideoInfo>,
+        seen_video_ids: &mut HashSet<String>,
+    ) {
+        for video in videos {
+            self.source_video_ids.insert(video.video_id.clone());
+            if seen_video_ids.insert(video.video_id.clone()) {
+                self.sources
+                    .insert(video.video_id.clone(), source_id.to_string());
+                self.videos_to_add.push(video);
+            } else if self.sources.contains_key(&video.video_id) {
+                self.duplicates.push(video);
+            }
+        }
+    }
+}
+
+/// Keep the videos a playlist script accepts, with the replacements it asked for.
+///
+/// Replacements count as videos of the sources, so mirroring doesn't remove them, and
+/// are left out when the playlist already has them. The videos left out are added to
+/// `skipped`.
+fn apply_script(
+    script: &Script,
+    videos: Vec<VideoInfo>,
+    target_videos: &[VideoInfo],
+    sources: &HashMap<String, String>,
+    source_video_ids: &mut HashSet<String>,
+    skipped: &mut Vec<(VideoInfo, SkipReason)>,
+) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+    let mut seen: HashSet<String> = target_videos.iter().map(|v| v.video_id.clone()).collect();
+    let mut kept = Vec::new();
+
+    for video in videos {
+        let source = sources
+            .get(&video.video_id)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let video = match script.verdict(&video, source)? {
+            Verdict::Keep => video,
+            Verdict::Skip => {
+                let reason = SkipReason::new(SkipCode::Script, "skipped by the script");
+                skipped.push((video, reason));
+                continue;
+            }
+            Verdict::Replace(replacement) => {
+                source_video_ids.insert(replacement.video_id.clone());
+                let reason = SkipReason::new(
+                    SkipCode::Replaced,
+                    format!("replaced by {} by the script", replacement.video_id),
+                );
+                skipped.push((video, reason));
+                *replacement
+            }
+        };
+        if seen.insert(video.video_id.clone()) {
+            kept.push(video);
+        }
+    }
+
+    Ok(kept)
+}
+
+/// The videos a sync would add to a target playlist, and the ones it leaves out
+pub struct Selection {
+    /// The target and its sources. Its `videos_to_add` are moved to the selection.
+    pub pending: PendingChanges,
+    pub videos_to_add: Vec<VideoInfo>,
+    pub skipped: Vec<(VideoInfo, SkipReason)>,
+}
+
+/// Select the videos of the sources to add to a target playlist, following its filter,
+/// script, plugin filters and `max_adds`, without making any changes.
+/// Playlists are fetched with progress bars.
+pub async fn select_videos(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+) -> Result<Selection, Box<dyn std::error::Error>> {
+    select(provider, target_playlist, source_playlist_ids, true).await
+}
+
+async fn select(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+    target_exists: bool,
+) -> Result<Selection, Box<dyn std::error::Error>> {
+    let mut pending = collect_changes(
+        provider,
+        target_exists.then_some(target_playlist.id.as_str()),
+        source_playlist_ids,
+        Some(&target_playlist.title),
+    )
+    .await?;
+    let mut videos_to_add = std::mem::take(&mut pending.videos_to_add);
+    let mut skipped: Vec<(VideoInfo, SkipReason)> = pending
+        .duplicates
+        .iter()
+        .map(|video| {
+            let first = pending
+                .sources
+                .get(&video.video_id)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let reason =
+                SkipReason::new(SkipCode::Duplicate, format!("already found in {}", first));
+            (video.clone(), reason)
+        })
+        .collect();
+
+    if let Some(filter) = &target_playlist.filter {
+        if filter.needs_details() {
+            provider.enrich_videos(&mut videos_to_add).await?;
+        }
+        let mut matching = Vec::new();
+        for video in videos_to_add {
+            match filter.rejection(&video) {
+                Some(reason) => skipped.push((video, reason)),
+                None => matching.push(video),
+            }
+        }
+        videos_to_add = matching;
+    }
+
+    // Scripts and plugins can look at the duration
+    if target_playlist.script.is_some() || !target_playlist.plugin_filters.is_empty() {
+        provider.enrich_videos(&mut videos_to_add).await?;
+    }
+
+    if let Some(path) = &target_playlist.script {
+        videos_to_add = apply_script(
+            &Script::load(path)?,
+            videos_to_add,
+            &pending.target_videos,
+            &pending.sources,
+            &mut pending.source_video_ids,
+            &mut skipped,
+        )?;
+    }
+
+    for name in &target_playlist.plugin_filters {
+        let candidates = videos_to_add.clone();
+        videos_to_add = plugin::filter(name, &target_playlist.id, videos_to_add, &pending.sources)?;
+        let kept: HashSet<&str> = videos_to_add.iter().map(|v| v.video_id.as_str()).collect();
+        skipped.extend(
+            candidates
+                .into_iter()
+                .filter(|video| !kept.contains(video.video_id.as_str()))
+                .map(|video| {
+                    let reason = SkipReason::new(
+                        SkipCode::Plugin,
+                        format!("rejected by the plugin '{}'", name),
+                    );
+                    (video, reason)
+                }),
+        );
+    }
+
+    if let Some(max_adds) = target_playlist.max_adds
+        && videos_to_add.len() > max_adds
+    {
+        skipped.extend(videos_to_add.drain(max_adds..).map(|video| {
+            let reason = SkipReason::new(
+                SkipCode::OverLimit,
+                format!("over max_adds ({}), added on a later sync", max_adds),
+            );
+            (video, reason)
+        }));
+    }
+
+    Ok(Selection {
+        pending,
+        videos_to_add,
+        skipped,
+    })
+}
+
+/// Sync a target playlist with its sources, following the options of the playlist
+/// (filter, insert position, number of additions, mirroring and direction)
+pub async fn sync_playlist(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+    dry_run: bool,
+) -> Result<SyncReport, Box<dyn std::error::Error>> {
+    sync_with(
+        provider,
+        target_playlist,
+        source_playlist_ids,
+        dry_run,
+        true,
+    )
+    .await
+}
+
+/// Preview the sync of a target playlist that would be created (see
+/// [`TargetState::ToCreate`]) like a dry run of [`sync_playlist`], against an empty
+/// playlist
+pub async fn preview_new_playlist(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+) -> Result<SyncReport, Box<dyn std::error::Error>> {
+    sync_with(provider, target_playlist, source_playlist_ids, true, false).await
+}
+
+async fn sync_with(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+    dry_run: bool,
+    target_exists: bool,
+) -> Result<SyncReport, Box<dyn std::error::Error>> {
+    let mut report = SyncReport::default();
+    let options = WriteOptions::for_playlist(target_playlist);
+
+    log::step(format!("Syncing playlist: {}", target_playlist.title))?;
+
+    let Selection {
+        pending,
+        videos_to_add,
+        skipped,
+    } = select(
+        provider,
+        target_playlist,
+        source_playlist_ids,
+        target_exists,
+    )
+    .await?;
+    report.item_count = pending.target_count;
+    report.previous_item_ids = pending
+        .target_videos
+        .iter()
+        .map(|video| video.item_id.clone())
+        .collect();
+    report.videos = pending.target_videos;
+    report.skipped = skipped;
+
+    let deferred = report
+        .skipped
+        .iter()
+        .filter(|(_, reason)| reason.code == SkipCode::OverLimit)
+        .count();
+    if deferred > 0 {
+        log::info(format!(
+            "Adding {} of {} videos, the others are added on the next syncs",
+            videos_to_add.len(),
+            videos_to_add.len() + deferred
+        ))?;
+    }
+
+    // Only complete sources tell which videos were removed from them
+    let videos_to_remove: Vec<VideoInfo> = if target_playlist.mirror == Some(true) {
+        if source_playlist_ids
+            .iter()
+            .all(|id| Source::parse(id).is_complete())
+        {
+            report
+                .videos
+                .iter()
+                .filter(|v| !v.is_placeholder() && !pending.source_video_ids.contains(&v.video_id))
+                .cloned()
+                .collect()
+        } else {
+            log::warning(format!(
+                "Not mirroring '{}': some of its sources only hold recent videos",
+                target_playlist.title
+            ))?;
+            Vec::new()
+        }
+    } else {
+        Vec::new()
+    };
+
+    log::step(format!(
+        "Found {} videos to sync to '{}'",
+        videos_to_add.len(),
+        target_playlist.title
+    ))?;
+
+    if output::is_verbose() {
+        for (video, reason) in &report.skipped {
+            log::info(format!(
+                "Skipped '{}' [{}]: {}",
+                video.title,
+                reason.code.as_str(),
+                reason.message
+            ))?;
+        }
+    }
+
+    if dry_run {
+        if !videos_to_add.is_empty() || !videos_to_remove.is_empty() {
+            log::info(format!(
+                "Would add {} and remove {} videos:\n{}",
+                videos_to_add.len(),
+                videos_to_remove.len(),
+                diff::render(
+                    &target_playlist.title,
+                    &report.videos,
+                    &videos_to_add,
+                    &videos_to_remove,
+                    options.at_start,
+                )
+            ))?;
+        }
+        report
+            .videos
+            .retain(|v| !videos_to_remove.iter().any(|r| r.item_id == v.item_id));
+        report.videos.extend(videos_to_add.iter().cloned());
+        report.added = videos_to_add;
+        report.removed = videos_to_remove;
+    } else {
+        if !videos_to_add.is_empty() {
+            add_videos_with(
+                provider,
+                &target_playlist.id,
+                videos_to_add,
+                &mut report,
+                options,
+            )
+            .await?;
+        }
+        if !videos_to_remove.is_empty() {
+            remove_videos(provider, &target_playlist.id, videos_to_remove, &mut report).await?;
+        }
+
+        let removed = &report.removed;
+        report
+            .videos
+            .retain(|v| !removed.iter().any(|r| r.item_id == v.item_id));
+        report.videos.extend(report.added.iter().cloned());
+    }
+
+    if target_playlist.direction == Some(SyncDirection::TwoWay) {
+        push_to_sources(
+            provider,
+            &report.videos,
+            source_playlist_ids,
+            dry_run,
+            options,
+        )
+        .await?;
+    }
+
+    Ok(report)
+}
+
+/// Add the videos of a target playlist to its source playlists missing them, for
+/// two-way syncs. Sources that aren't playlists can't be written to and are skipped.
+async fn push_to_sources(
+    provider: &dyn Provider,
+    target_videos: &[VideoInfo],
+    source_playlist_ids: &[String],
+    dry_run: bool,
+    options: WriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for source in source_playlist_ids.iter().map(|id| Source::parse(id)) {
+        let Some(source_id) = source.playlist_id() else {
+            continue;
+        };
+
+        let source_video_ids: HashSet<String> = provider
+            .get_playlist_items(source_id)
+            .await?
+            .into_iter()
+            .map(|v| v.video_id)
+            .collect();
+        let missing: Vec<VideoInfo> = target_videos
+            .iter()
+            .filter(|v| !v.is_placeholder() && !source_video_ids.contains(&v.video_id))
+            .cloned()
+            .collect();
+
+        if missing.is_empty() {
+            continue;
+        }
+
+        if dry_run {
+            log::info(format!(
+                "Would add {} videos back to source playlist {}",
+                missing.len(),
+                source_id
+            ))?;
+            continue;
+        }
+
+        let mut report = SyncReport::default();
+        add_videos_with(provider, source_id, missing, &mut report, options).await?;
+    }
+
+    Ok(())
+}
+
+/// How videos are written to a playlist
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WriteOptions {
+    /// Insert the videos before the existing items instead of after them
+    pub at_start: bool,
+    /// Pause between two writes
+    pub delay: Option<Duration>,
+}
+
+impl WriteOptions {
+    /// The write options configured for a playlist
+    pub fn for_playlist(playlist: &Playlist) -> Self {
+        WriteOptions {
+            at_start: playlist.position == Some(InsertPosition::Start),
+            delay: playlist.write_delay_ms.map(Duration::from_millis),
+        }
+    }
+}
+
+/// Add videos to a playlist one by one, recording successes and failures in the report
+pub async fn add_videos(
+    provider: &dyn Provider,
+    playlist_id: &str,
+    videos: Vec<VideoInfo>,
+    report: &mut SyncReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    add_videos_with(
+        provider,
+        playlist_id,
+        videos,
+        report,
+        WriteOptions::default(),
+    )
+    .await
+}
+
+/// Add videos to a playlist one by one like [`add_videos`], with write options
+pub async fn add_videos_with(
+    provider: &dyn Provider,
+    playlist_id: &str,
+    videos: Vec<VideoInfo>,
+    report: &mut SyncReport,
+    options: WriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bar = ui::progress_bar(videos.len() as u64);
+    bar.start(&format!("Adding {} videos to playlist", videos.len()));
+    let (added_before, failed_before) = (report.added.len(), report.failed.len());
+
+    // Videos inserted at the start keep the order of the sources
+    let mut position = 0;
+
+    for video in videos {
+        bar.set_message(&format!("Adding: {}", video.title));
+        let result = provider
+            .add_video_to_playlist(
+                playlist_id,
+                &video.video_id,
+                options.at_start.then_some(position),
+            )
+            .await;
+
+        match result {
+            Ok(_) => {
+                report.added.push(video);
+                report.item_count += 1;
+                position += 1;
+            }
+            Err(e) => {
+                exit::mark_failed_item(e.as_ref());
+                report.failed.push((video, e.to_string()));
+            }
+        }
+        bar.inc(1);
+
+        if let Some(delay) = options.delay {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    // Logged once the bar is gone, as it would be drawn over them
+    bar.clear();
+    for video in &report.added[added_before..] {
+        log::info(format!("Added: {}", video.title))?;
+    }
+    for (video, error) in &report.failed[failed_before..] {
+        log::warning(format!("Failed to add '{}': {}", video.title, error))?;
+    }
+
+    log::success(format!("Successfully added {} videos", report.added.len()))?;
+    Ok(())
+}
+
+/// Remove videos from a playlist one by one, recording successes and failures in the report.
+///
+/// Videos fetched from the playlist are removed by their playlist item ID. For other
+/// videos, every item holding the video is removed: they are all looked up at once in
+/// the items of the playlist, rather than with a request per video.
+pub async fn remove_videos(
+    provider: &dyn Provider,
+    playlist_id: &str,
+    videos: Vec<VideoInfo>,
+    report: &mut SyncReport,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if videos.is_empty() {
+        return Ok(());
+    }
+
+    // A video can appear several times in a playlist
+    let mut item_ids: HashMap<String, Vec<String>> = HashMap::new();
+    if videos.iter().any(|video| video.item_id.is_empty()) {
+        for item in provider.get_playlist_items(playlist_id).await? {
+            item_ids
+                .entry(item.video_id)
+                .or_default()
+                .push(item.item_id);
+        }
+    }
+
+    let bar = ui::progress_bar(videos.len() as u64);
+    bar.start(&format!("Removing {} videos from playlist", videos.len()));
+    let (removed_before, failed_before) = (report.removed.len(), report.failed.len());
+
+    for video in videos {
+        bar.set_message(&format!("Removing: {}", video.title));
+        let result = if video.item_id.is_empty() {
+            provider
+                .remove_playlist_items(&item_ids.remove(&video.video_id).unwrap_or_default())
+                .await
+        } else {
+            provider
+                .remove_playlist_item(&video.item_id)
+                .await
+                .map(|_| 1)
+        };
+
+        match result {
+            Ok(count) => {
+                report.removed.push(video);
+                report.item_count = report.item_count.saturating_sub(count);
+            }
+            Err(e) => {
+                exit::mark_failed_item(e.as_ref());
+                report.failed.push((video, e.to_string()));
+            }
+        }
+        bar.inc(1);
+    }
+
+    bar.clear();
+    for video in &report.removed[removed_before..] {
+        log::info(format!("Removed: {}", video.title))?;
+    }
+    for (video, error) in &report.failed[failed_before..] {
+        log::warning(format!("Failed to remove '{}': {}", video.title, error))?;
+    }
+
+    Ok(())
+}
+
+/// Update the title and description of a target playlist from its sources, if configured.
+///
+/// Returns the new title when it changed, so the configuration can be updated.
+pub async fn sync_metadata(
+    provider: &dyn Provider,
+    target_playlist: &Playlist,
+    source_playlist_ids: &[String],
+    dry_run: bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if !target_playlist.sync_metadata && target_playlist.description_template.is_none() {
+        return Ok(None);
+    }
+
+    // Virtual sources (e.g. the subscriptions feed) have no title or description
+    let sources: Vec<Source> = source_playlist_ids
+        .iter()
+        .map(|id| Source::parse(id))
+        .collect();
+    let mut playlist_ids = vec![target_playlist.id.as_str()];
+    playlist_ids.extend(sources.iter().filter_map(Source::playlist_id));
+
+    // The target and its sources are read at once
+    let found = provider.get_playlists_metadata(&playlist_ids).await?;
+    let metadata_of = |playlist_id: &str| {
+        found
+            .get(playlist_id)
+            .cloned()
+            .ok_or_else(|| format!("Playlist {} not found", playlist_id))
+    };
+    let current = metadata_of(&target_playlist.id)?;
+    let sources = playlist_ids[1..]
+        .iter()
+        .map(|playlist_id| metadata_of(playlist_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut metadata = current.clone();
+
+    if target_playlist.sync_metadata
+        && let Some(source) = sources.first()
+    {
+        metadata = source.clone();
+    }
+
+    if let Some(template) = &target_playlist.description_template {
+        let titles: Vec<&str> = sources.iter().map(|s| s.title.as_str()).collect();
+        metadata.description = template.replace("{sources}", &titles.join(", ")).replace(
+            "{date}",
+            &chrono::Local::now().format("%Y-%m-%d").to_string(),
+        );
+    }
+
+    if metadata == current {
+        return Ok(None);
+    }
+
+    if dry_run {
+        log::info(format!(
+            "Would update the title/description of '{}'",
+            target_playlist.title
+        ))?;
+        return Ok(None);
+    }
+
+    provider
+        .update_playlist_metadata(&target_playlist.id, &metadata)
+        .await?;
+    log::info(format!(
+        "Updated the title/description of '{}'",
+        metadata.title
+    ))?;
+
+    Ok((metadata.title != current.title).then_some(metadata.title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::FilterArgs;
+    use crate::provider::InMemoryProvider;
+    use crate::youtube::VideoDetails;
+    use regex::Regex;
+
+    fn video(id: &str) -> VideoInfo {
+        InMemoryProvider::video(id, &id.to_uppercase())
+    }
+
+    fn target() -> Playlist {
+        Playlist {
+            id: "target".to_string(),
+            title: "Target".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sources(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn video_ids(provider: &InMemoryProvider, playlist_id: &str) -> Vec<String> {
+        provider
+            .playlist(playlist_id)
+            .unwrap()
+            .into_iter()
+            .map(|video| video.video_id)
+            .collect()
+    }
+
+    fn skipped(report: &SyncReport) -> Vec<(&str, SkipCode)> {
+        report
+            .skipped
+            .iter()
+            .map(|(video, reason)| (video.video_id.as_str(), reason.code))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn adds_the_missing_videos_of_the_sources() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("a")])
+            .with_playlist("source", "Source", vec![video("a"), video("b")]);
+
+        let report = sync_playlist(&provider, &target(), &sources(&["source"]), false)
+            .await
+            .unwrap();
+
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.item_count, 2);
+        assert_eq!(video_ids(&provider, "target"), ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn dry_run_changes_nothing() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("stale")])
+            .with_playlist("source", "Source", vec![video("a")]);
+        let playlist = Playlist {
+            mirror: Some(true),
+            ..target()
+        };
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["source"]), true)
+            .await
+            .unwrap();
+
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(video_ids(&provider, "target"), ["stale"]);
+    }
+
+    #[tokio::test]
+    async fn mirror_removes_the_videos_gone_from_the_sources() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("a"), video("stale")])
+            .with_playlist("source", "Source", vec![video("a"), video("b")]);
+        let playlist = Playlist {
+            mirror: Some(true),
+            ..target()
+        };
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["source"]), false)
+            .await
+            .unwrap();
+
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].video_id, "stale");
+        assert_eq!(video_ids(&provider, "target"), ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn mirror_keeps_the_videos_of_incomplete_sources() {
+        // Subscriptions only hold recent uploads, so older videos aren't gone from them
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("old")])
+            .with_source("subscriptions", vec![video("new")]);
+        let playlist = Playlist {
+            mirror: Some(true),
+            ..target()
+        };
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["subscriptions"]), false)
+            .await
+            .unwrap();
+
+        assert!(report.removed.is_empty());
+        assert_eq!(video_ids(&provider, "target"), ["old", "new"]);
+    }
+
+    #[tokio::test]
+    async fn reports_why_videos_are_skipped() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![])
+            .with_playlist("first", "First", vec![video("song-a"), video("talk")])
+            .with_playlist("second", "Second", vec![video("song-a"), video("song-b")])
+            .with_details(
+                "song-b",
+                VideoDetails {
+                    title: "SONG-B".to_string(),
+                    duration: Some(Duration::from_secs(3600)),
+                    ..Default::default()
+                },
+            )
+            .with_details(
+                "song-a",
+                VideoDetails {
+                    title: "SONG-A".to_string(),
+                    duration: Some(Duration::from_secs(200)),
+                    ..Default::default()
+                },
+            );
+        let playlist = Playlist {
+            filter: Some(FilterArgs {
+                title: Some(Regex::new("^SONG").unwrap()),
+                max_duration: Some(Duration::from_secs(600)),
+                ..Default::default()
+            }),
+            ..target()
+        };
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["first", "second"]), false)
+            .await
+            .unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["song-a"]);
+        assert_eq!(
+            skipped(&report),
+            [
+                ("song-a", SkipCode::Duplicate),
+                ("talk", SkipCode::FilteredTitle),
+                ("song-b", SkipCode::FilteredDuration),
+            ]
+        );
+        assert_eq!(report.skipped[0].1.message, "already found in first");
+    }
+
+    #[tokio::test]
+    async fn max_adds_defers_the_other_videos_to_the_next_syncs() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![])
+            .with_playlist("source", "Source", vec![video("a"), video("b"), video("c")]);
+        let playlist = Playlist {
+            max_adds: Some(2),
+            ..target()
+        };
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["source"]), false)
+            .await
+            .unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["a", "b"]);
+        assert_eq!(skipped(&report), [("c", SkipCode::OverLimit)]);
+
+        let report = sync_playlist(&provider, &playlist, &sources(&["source"]), false)
+            .await
+            .unwrap();
+
+        assert!(report.skipped.is_empty());
+        assert_eq!(video_ids(&provider, "target"), ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn two_way_pushes_the_videos_of_the_target_to_the_sources() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("x")])
+            .with_playlist("source", "Source", vec![video("a")])
+            .with_source("subscriptions", vec![video("s")]);
+        let playlist = Playlist {
+            direction: Some(SyncDirection::TwoWay),
+            ..target()
+        };
+
+        sync_playlist(
+            &provider,
+            &playlist,
+            &sources(&["source", "subscriptions"]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["x", "a", "s"]);
+        assert_eq!(video_ids(&provider, "source"), ["a", "x", "s"]);
+    }
+
+    #[tokio::test]
+    async fn inserts_at_the_start_in_the_order_of_the_sources() {
+        let provider = InMemoryProvider::new()
+            .with_playlist("target", "Target", vec![video("x")])
+            .with_playlist("source", "Source", vec![video("a"), video("b"), video("c")]);
+        let playlist = Playlist {
+            position: Some(InsertPosition::Start),
+            ..target()
+        };
+
+        sync_playlist(&provider, &playlist, &sources(&["source"]), false)
+            .await
+            .unwrap();
+
+        assert_eq!(video_ids(&provider, "target"), ["a", "b", "c", "x"]);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::config::Playlist;
+use crate::history::{self, PlaylistRecord};
+use crate::provider::Provider;
+use crate::source::{self, Source};
+use crate::sync::{self, SyncReport};
+use crate::ui::{self, log};
+use crate::youtube::VideoInfo;
+use std::collections::{HashMap, HashSet};
+
+/// A maintenance step performed by `playsync tidy`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TidyStep {
+    /// Remove repeated items of the same video, keeping the first one
+    Dedupe,
+    /// Remove items of synced playlists that are no longer in any of their sources
+    Prune,
+    /// Remove "Deleted video" and "Private video" placeholders
+    Placeholders,
+}
+
+/// Items to remove from a playlist, grouped by the step that selected them
+#[derive(Debug, Default)]
+pub struct TidyPlan {
+    pub placeholders: Vec<VideoInfo>,
+    pub duplicates: Vec<VideoInfo>,
+    pub pruned: Vec<VideoInfo>,
+}
+
+impl TidyPlan {
+    /// Total number of items to remove
+    pub fn count(&self) -> usize {
+        self.placeholders.len() + self.duplicates.len() + self.pruned.len()
+    }
+
+    /// All items to remove
+    pub fn items(&self) -> impl Iterator<Item = &VideoInfo> {
+        self.placeholders
+            .iter()
+            .chain(&self.duplicates)
+            .chain(&self.pruned)
+    }
+}
+
+/// Decide which items of a playlist to remove.
+///
+/// `source_video_ids` holds the videos of the playlist's sources, and is `None` for
+/// playlists without sources, which are never pruned. Each item is selected by at
+/// most one step: placeholders first, then duplicates, then pruned items.
+pub fn plan(
+    videos: &[VideoInfo],
+    source_video_ids: Option<&HashSet<String>>,
+    skip: &[TidyStep],
+) -> TidyPlan {
+    let mut plan = TidyPlan::default();
+    let mut seen = HashSet::new();
+
+    for video in videos {
+        if video.is_placeholder() && !skip.contains(&TidyStep::Placeholders) {
+            plan.placeholders.push(video.clone());
+        } else if !seen.insert(video.video_id.as_str()) && !skip.contains(&TidyStep::Dedupe) {
+            plan.duplicates.push(video.clone());
+        } else if source_video_ids.is_some_and(|ids| !ids.contains(&video.video_id))
+            && !video.is_placeholder()
+            && !skip.contains(&TidyStep::Prune)
+        {
+            plan.pruned.push(video.clone());
+        }
+    }
+
+    plan
+}
+
+/// Tidy playlists, like `playsync tidy`: remove their items selected by [`plan`], or
+/// show them on a dry run, then show a report of each playlist
+pub async fn tidy_playlists(
+    client: &dyn Provider,
+    playlists: &[Playlist],
+    skip: &[TidyStep],
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut records = Vec::new();
+    let mut source_cache: HashMap<String, Vec<String>> = HashMap::new();
+    let mut summary = String::new();
+
+    for playlist in playlists {
+        let sp = ui::spinner();
+        sp.start(&format!("Checking {}", playlist.title));
+
+        let videos = client.get_playlist_items(&playlist.id).await?;
+
+        // Sources shared by several targets are only fetched once
+        let mut source_video_ids = None;
+        if let Some(sync_from) = &playlist.sync_from {
+            // Some virtual sources only cover a time window, so pruning against them
+            // would remove everything older than the window
+            let complete = sync_from.iter().all(|id| Source::parse(id).is_complete());
+
+            if complete && !skip.contains(&TidyStep::Prune) {
+                let mut ids = HashSet::new();
+                for source_id in sync_from {
+                    if !source_cache.contains_key(source_id) {
+                        let source_videos =
+                            source::fetch_videos(client, &Source::parse(source_id)).await?;
+                        source_cache.insert(
+                            source_id.clone(),
+                            source_videos.into_iter().map(|v| v.video_id).collect(),
+                        );
+                    }
+                    ids.extend(source_cache[source_id].iter().cloned());
+                }
+                source_video_ids = Some(ids);
+            }
+        }
+
+        let plan = self::plan(&videos, source_video_ids.as_ref(), skip);
+        sp.stop(&format!(
+            "{}: {} duplicates, {} pruned, {} placeholders",
+            playlist.title,
+            plan.duplicates.len(),
+            plan.pruned.len(),
+            plan.placeholders.len()
+        ));
+
+        let record = if dry_run {
+            for video in plan.items() {
+                log::info(format!("  Would remove: {}", video.title))?;
+            }
+            PlaylistRecord {
+                item_count: Some(videos.len()),
+                ..PlaylistRecord::new(&playlist.id, &playlist.title)
+            }
+        } else {
+            let mut removal = SyncReport {
+                item_count: videos.len(),
+                ..Default::default()
+            };
+            sync::remove_videos(
+                client,
+                &playlist.id,
+                plan.items().cloned().collect(),
+                &mut removal,
+            )
+            .await?;
+            PlaylistRecord::from_report(&playlist.id, &playlist.title, &removal)
+        };
+
+        summary.push_str(&format!(
+            "{}: {} to remove ({} duplicates, {} pruned, {} placeholders){}\n",
+            playlist.title,
+            plan.count(),
+            plan.duplicates.len(),
+            plan.pruned.len(),
+            plan.placeholders.len(),
+            if record.failed > 0 {
+                format!(", {} failed", record.failed)
+            } else {
+                String::new()
+            }
+        ));
+        records.push(record);
+    }
+
+    history::record_run("tidy", dry_run, records, client.quota_used())?;
+
+    ui::note("Tidy report", summary.trim_end())?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::fmt::Display;
+use std::io;
+use std::sync::OnceLock;
+
+/// Kind of a message, deciding how it's shown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// A detail, less visible than the other messages
+    Remark,
+    Info,
+    /// A step of a longer task
+    Step,
+    Success,
+    Warning,
+    Error,
+}
+
+/// How the library talks to the person running it: messages, progress and questions.
+///
+/// The command line shows them on the terminal. Until another UI is set with [`set`],
+/// messages are plain lines on stderr, progress isn't shown, and questions fail since
+/// nobody can answer them.
+pub trait Ui: Send + Sync {
+    /// Show a message
+    fn log(&self, level: Level, message: &str) -> io::Result<()>;
+
+    /// Show a block of several lines under a title, like a report
+    fn note(&self, title: &str, body: &str) -> io::Result<()>;
+
+    /// A progress bar for `total` items, `0` while the total isn't known
+    fn progress_bar(&self, total: u64) -> Box<dyn Progress>;
+
+    /// A spinner, for a task that can't count what is left
+    fn spinner(&self) -> Box<dyn Progress>;
+
+    /// Ask a yes or no question
+    fn confirm(&self, prompt: &str, initial_value: bool) -> io::Result<bool>;
+
+    /// Ask for a password, without showing what is typed
+    fn password(&self, prompt: &str) -> io::Result<String>;
+}
+
+/// Progress of a task, shown from [`start`](Progress::start) until it's stopped or cleared
+pub trait Progress {
+    fn start(&self, message: &str);
+
+    /// Change the number of items of a progress bar
+    fn set_length(&self, total: u64);
+
+    fn set_message(&self, message: &str);
+
+    /// Count `delta` more items as done
+    fn inc(&self, delta: u64);
+
+    /// Stop, leaving a message
+    fn stop(&self, message: &str);
+
+    /// Stop on a failure, leaving a message
+    fn error(&self, message: &str);
+
+    /// Stop, leaving nothing behind
+    fn clear(&self);
+}
+
+static UI: OnceLock<Box<dyn Ui>> = OnceLock::new();
+
+/// Use this UI for the rest of the run. Only the first UI set is used.
+pub fn set(ui: impl Ui + 'static) {
+    let _ = UI.set(Box::new(ui));
+}
+
+/// The UI of the run
+pub fn get() -> &'static dyn Ui {
+    match UI.get() {
+        Some(ui) => ui.as_ref(),
+        None => &Plain,
+    }
+}
+
+/// Show a block of lines under a title, see [`Ui::note`]
+pub fn note(title: impl Display, body: impl Display) -> io::Result<()> {
+    get().note(&title.to_string(), &body.to_string())
+}
+
+/// A progress bar for `total` items, see [`Ui::progress_bar`]
+pub fn progress_bar(total: u64) -> Box<dyn Progress> {
+    get().progress_bar(total)
+}
+
+/// A spinner, see [`Ui::spinner`]
+pub fn spinner() -> Box<dyn Progress> {
+    get().spinner()
+}
+
+/// Messages shown through the UI of the run, one function per [`Level`]
+pub mod log {
+    use super::{Level, get};
+    use std::fmt::Display;
+    use std::io;
+
+    pub fn remark(message: impl Display) -> io::Result<()> {
+        get().log(Level::Remark, &message.to_string())
+    }
+
+    pub fn info(message: impl Display) -> io::Result<()> {
+        get().log(Level::Info, &message.to_string())
+    }
+
+    pub fn step(message: impl Display) -> io::Result<()> {
+        get().log(Level::Step, &message.to_string())
+    }
+
+    pub fn success(message: impl Display) -> io::Result<()> {
+        get().log(Level::Success, &message.to_string())
+    }
+
+    pub fn warning(message: impl Display) -> io::Result<()> {
+        get().log(Level::Warning, &message.to_string())
+    }
+
+    pub fn error(message: impl Display) -> io::Result<()> {
+        get().log(Level::Error, &message.to_string())
+    }
+}
+
+/// The UI used until another one is set
+struct Plain;
+
+impl Ui for Plain {
+    fn log(&self, level: Level, message: &str) -> io::Result<()> {
+        let prefix = match level {
+            Level::Warning => "warning: ",
+            Level::Error => "error: ",
+            _ => "",
+        };
+        eprintln!("{}{}", prefix, message);
+        Ok(())
+    }
+
+    fn note(&self, title: &str, body: &str) -> io::Result<()> {
+        eprintln!("{}\n{}", title, body);
+        Ok(())
+    }
+
+    fn progress_bar(&self, _total: u64) -> Box<dyn Progress> {
+        Box::new(Hidden)
+    }
+
+    fn spinner(&self) -> Box<dyn Progress> {
+        Box::new(Hidden)
+    }
+
+    fn confirm(&self, prompt: &str, _initial_value: bool) -> io::Result<bool> {
+        Err(unanswered(prompt))
+    }
+
+    fn password(&self, prompt: &str) -> io::Result<String> {
+        Err(unanswered(prompt))
+    }
+}
+
+fn unanswered(prompt: impl Display) -> io::Error {
+    io::Error::other(format!(
+        "Can't ask \"{}\" without a UI to answer it",
+        prompt
+    ))
+}
+
+/// Progress that isn't shown
+struct Hidden;
+
+impl Progress for Hidden {
+    fn start(&self, _message: &str) {}
+
+    fn set_length(&self, _total: u64) {}
+
+    fn set_message(&self, _message: &str) {}
+
+    fn inc(&self, _delta: u64) {}
+
+    fn stop(&self, _message: &str) {}
+
+    fn error(&self, _message: &str) {}
+
+    fn clear(&self) {}
+}
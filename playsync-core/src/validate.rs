@@ -0,0 +1,383 @@
+use crate::config::Config;
+use crate::import;
+use crate::plugin::Plugin;
+use crate::provider::Provider;
+use crate::schedule;
+use crate::script::Script;
+use crate::source::Source;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// How serious a problem found in the configuration is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// playsync can't work as configured
+    Error,
+    /// playsync works, but probably not as intended
+    Warning,
+}
+
+/// A mistake found in the configuration, with how to fix it
+#[derive(Debug)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(message: String) -> Self {
+        Problem {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Problem {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+/// Read a configuration file and check it without the YouTube API.
+///
+/// Returns the configuration, with environment overrides applied, or `None` if the
+/// file can't be read, along with the problems found.
+pub fn check_file(path: &Path) -> (Option<Config>, Vec<Problem>) {
+    let mut problems = Vec::new();
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            problems.push(Problem::error(format!(
+                "Can't read {}: {}. Run `playsync config --oauth2-json PATH` to create it.",
+                path.display(),
+                e
+            )));
+            return (None, problems);
+        }
+    };
+
+    let mut unknown = Vec::new();
+    let parsed: Result<Config, _> =
+        serde_ignored::deserialize(toml::Deserializer::new(&content), |field| {
+            unknown.push(field.to_string())
+        });
+
+    let mut cfg = match parsed {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            problems.push(Problem::error(format!(
+                "{} is not a valid configuration: {}",
+                path.display(),
+                e.to_string().trim()
+            )));
+            return (None, problems);
+        }
+    };
+
+    if let Err(e) = cfg.load_includes(path) {
+        problems.push(Problem::error(e.to_string()));
+    }
+
+    for field in unknown {
+        problems.push(Problem::warning(format!(
+            "Unknown field `{}` is ignored. Check its spelling.",
+            field
+        )));
+    }
+
+    if let Err(e) = cfg.apply_env_overrides() {
+        problems.push(Problem::error(e.to_string()));
+    }
+
+    problems.extend(check(&cfg));
+
+    (Some(cfg), problems)
+}
+
+/// Check a configuration for mistakes that can be found without the YouTube API
+pub fn check(cfg: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    match &cfg.oauth2_json {
+        None => problems.push(Problem::error(
+            "The OAuth2 JSON path is not set. Run `playsync config --oauth2-json PATH`.".to_string(),
+        )),
+        Some(path) if !Path::new(path).is_file() => problems.push(Problem::error(format!(
+            "The OAuth2 JSON file {} doesn't exist. Run `playsync config --oauth2-json PATH` with the file downloaded from the Google Cloud Console.",
+            path
+        ))),
+        Some(_) => {}
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for playlist in &cfg.playlists {
+        *counts.entry(playlist.id.as_str()).or_default() += 1;
+    }
+    let mut reported = HashSet::new();
+    for playlist in &cfg.playlists {
+        let count = counts[playlist.id.as_str()];
+        if count > 1 && reported.insert(playlist.id.as_str()) {
+            problems.push(Problem::error(format!(
+                "Playlist {} is configured {} times. Keep a single entry with `playsync config edit`.",
+                playlist.id, count
+            )));
+        }
+    }
+
+    let mut aliases: HashMap<String, &str> = HashMap::new();
+    for playlist in &cfg.playlists {
+        let Some(alias) = &playlist.alias else {
+            continue;
+        };
+        if let Some(other) = aliases.insert(alias.to_lowercase(), &playlist.id) {
+            problems.push(Problem::error(format!(
+                "Alias `{}` is used by playlists {} and {}. Give each playlist its own alias.",
+                alias, other, playlist.id
+            )));
+        }
+    }
+
+    if let Err(e) = schedule::parse_windows(&cfg.sync_windows) {
+        problems.push(Problem::error(format!(
+            "{}. The daemon won't sync until it's fixed.",
+            e
+        )));
+    }
+
+    if let Some(Err(e)) = cfg
+        .notifications
+        .digest
+        .as_deref()
+        .map(schedule::parse_interval)
+    {
+        problems.push(Problem::error(format!(
+            "notifications.digest: {}. Syncs are notified one by one until it's fixed.",
+            e
+        )));
+    }
+
+    for (name, limit) in [
+        (
+            "rate_limit.requests_per_second",
+            cfg.rate_limit.requests_per_second,
+        ),
+        (
+            "rate_limit.writes_per_minute",
+            cfg.rate_limit.writes_per_minute,
+        ),
+    ] {
+        if limit == Some(0) {
+            problems.push(Problem::warning(format!(
+                "{}: a limit of 0 would block every request, the default is used instead.",
+                name
+            )));
+        }
+    }
+
+    if let Some(Err(e)) = cfg
+        .defaults
+        .cache_max_age
+        .as_deref()
+        .map(schedule::parse_interval)
+    {
+        problems.push(Problem::warning(format!(
+            "defaults.cache_max_age: {}. It's ignored until it's fixed.",
+            e
+        )));
+    }
+
+    if let Some(cycle) = cfg.find_cycle() {
+        problems.push(Problem::error(format!(
+            "Playlists sync from each other in a loop: {}. Remove one of these sources.",
+            cycle.join(" → ")
+        )));
+    }
+
+    for playlist in &cfg.playlists {
+        let name = display_name(&playlist.title, &playlist.id);
+
+        if let Some(Err(e)) = playlist.schedule.as_deref().map(schedule::parse) {
+            problems.push(Problem::error(format!(
+                "{}: {}. Use a cron expression like `0 7 * * *`.",
+                name, e
+            )));
+        }
+        if let Some(Err(e)) = playlist.interval.as_deref().map(schedule::parse_interval) {
+            problems.push(Problem::error(format!("{}: {}.", name, e)));
+        }
+        if let Some(Err(e)) = playlist
+            .cache_max_age
+            .as_deref()
+            .map(schedule::parse_interval)
+        {
+            problems.push(Problem::warning(format!(
+                "cache_max_age of {}: {}. The default is used until it's fixed.",
+                name, e
+            )));
+        }
+        if playlist.schedule.is_some() && playlist.interval.is_some() {
+            problems.push(Problem::warning(format!(
+                "{} has both a `schedule` and an `interval`. The interval is ignored.",
+                name
+            )));
+        }
+        if let Some(Err(e)) = playlist.script.as_deref().map(Script::load) {
+            problems.push(Problem::error(format!("{}: {}", name, e)));
+        }
+        for plugin_name in &playlist.plugin_filters {
+            match Plugin::load(plugin_name) {
+                Ok(plugin) if !plugin.is_filter() => problems.push(Problem::error(format!(
+                    "{}: plugin '{}' is not a filter.",
+                    name, plugin_name
+                ))),
+                Ok(_) => {}
+                Err(e) => problems.push(Problem::error(format!("{}: {}.", name, e))),
+            }
+        }
+
+        for entry in playlist.sync_from.iter().flatten() {
+            if entry.starts_with("new:") && !counts.contains_key(entry.as_str()) {
+                problems.push(Problem::error(format!(
+                    "{} syncs from {}, which is not a configured playlist. Add it or remove the source.",
+                    name, entry
+                )));
+                continue;
+            }
+
+            match Source::parse(entry) {
+                Source::LastFm { .. } if cfg.lastfm_api_key.is_none() => {
+                    problems.push(Problem::error(format!(
+                        "{} syncs from {}, but `lastfm_api_key` is not set.",
+                        name, entry
+                    )))
+                }
+                Source::Spotify { .. }
+                    if cfg.spotify_client_id.is_none() || cfg.spotify_client_secret.is_none() =>
+                {
+                    problems.push(Problem::error(format!(
+                        "{} syncs from {}, but `spotify_client_id` and `spotify_client_secret` are not both set.",
+                        name, entry
+                    )))
+                }
+                Source::Import {
+                    format,
+                    path,
+                    playlist: local_name,
+                } => match import::read_library(format, &path) {
+                    Ok(library) if !library.playlists.iter().any(|p| p.name == local_name) => {
+                        problems.push(Problem::error(format!(
+                            "{} syncs from {}, but the export has no playlist named \"{}\".",
+                            name, entry, local_name
+                        )))
+                    }
+                    Ok(_) => {}
+                    Err(e) => problems.push(Problem::error(format!(
+                        "{} syncs from {}, but {} can't be read: {}",
+                        name,
+                        entry,
+                        path.display(),
+                        e
+                    ))),
+                },
+                Source::Plugin {
+                    name: plugin_name, ..
+                } => match Plugin::load(&plugin_name) {
+                    Ok(plugin) if !plugin.is_provider() => problems.push(Problem::error(format!(
+                        "{} syncs from {}, but plugin '{}' doesn't provide videos.",
+                        name, entry, plugin_name
+                    ))),
+                    Ok(_) => {}
+                    Err(e) => problems.push(Problem::error(format!(
+                        "{} syncs from {}: {}.",
+                        name, entry, e
+                    ))),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    problems
+}
+
+/// Check that every configured playlist and playlist source can be read on YouTube.
+///
+/// The playlists are looked up 50 per request, each costing one quota unit.
+pub async fn check_playlists(provider: &dyn Provider, cfg: &Config) -> Vec<Problem> {
+    let mut ids = Vec::new();
+    for playlist in &cfg.playlists {
+        if playlist.placeholder_title().is_none() {
+            ids.push(playlist.id.clone());
+        }
+        for entry in playlist.sync_from.iter().flatten() {
+            if let Source::Playlist(id) = Source::parse(entry)
+                && !id.starts_with("new:")
+            {
+                ids.push(id);
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+
+    let states = match provider.get_playlist_states(&ids).await {
+        Ok(states) => states,
+        Err(e) => {
+            return vec![Problem::error(format!(
+                "The playlists can't be looked up on YouTube ({}).",
+                e
+            ))];
+        }
+    };
+
+    let mut problems = Vec::new();
+    let mut reported = HashSet::new();
+
+    for playlist in &cfg.playlists {
+        let name = display_name(&playlist.title, &playlist.id);
+
+        if playlist.placeholder_title().is_none()
+            && !states.contains_key(&playlist.id)
+            && reported.insert(playlist.id.clone())
+        {
+            problems.push(if playlist.recreate_if_missing {
+                Problem::warning(format!(
+                    "{} can't be read. It will be recreated on the next sync.",
+                    name
+                ))
+            } else {
+                Problem::error(format!(
+                    "{} can't be read. Check that it exists and belongs to your account, or remove it with `playsync config --remove {}`.",
+                    name, playlist.id
+                ))
+            });
+        }
+
+        for entry in playlist.sync_from.iter().flatten() {
+            let Source::Playlist(id) = Source::parse(entry) else {
+                continue;
+            };
+            if id.starts_with("new:") || states.contains_key(&id) || !reported.insert(id.clone()) {
+                continue;
+            }
+
+            problems.push(Problem::error(format!(
+                "{} syncs from playlist {}, which can't be read. Check that it exists and is public or unlisted.",
+                name, id
+            )));
+        }
+    }
+
+    problems
+}
+
+fn display_name(title: &str, id: &str) -> String {
+    if title.is_empty() {
+        format!("Playlist {}", id)
+    } else {
+        format!("\"{}\" ({})", title, id)
+    }
+}
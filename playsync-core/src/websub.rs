@@ -0,0 +1,296 @@
+use crate::config::Playlist;
+use crate::http::{self, Request};
+use crate::source::Source;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use regex::Regex;
+use sha1::Sha1;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// YouTube's WebSub (PubSubHubbub) hub
+const HUB_URL: &str = "https://pubsubhubbub.appspot.com/subscribe";
+
+/// How long subscriptions last before they have to be renewed, in seconds
+pub const LEASE_SECONDS: i64 = 5 * 24 * 60 * 60;
+
+/// Topic of the uploads of a channel
+fn topic(channel_id: &str) -> String {
+    format!(
+        "https://www.youtube.com/xml/feeds/videos.xml?channel_id={}",
+        channel_id
+    )
+}
+
+/// Channels whose new uploads a playlist syncs from.
+///
+/// These are the channels of uploads playlists (`UU...`), channel feeds and
+/// searches restricted to a channel.
+pub fn source_channels(playlist: &Playlist) -> HashSet<String> {
+    let mut channels = HashSet::new();
+
+    for entry in playlist.sync_from.iter().flatten() {
+        match Source::parse(entry) {
+            Source::Playlist(id) => {
+                if let Some(rest) = id.strip_prefix("UU") {
+                    channels.insert(format!("UC{}", rest));
+                }
+            }
+            Source::Feed(url) => {
+                if let Some(channel_id) = reqwest::Url::parse(&url).ok().and_then(|url| {
+                    url.query_pairs()
+                        .find(|(key, _)| key == "channel_id")
+                        .map(|(_, value)| value.into_owned())
+                }) {
+                    channels.insert(channel_id);
+                }
+            }
+            Source::Search(search) => {
+                if let Some(channel_id) = search.channel_id {
+                    channels.insert(channel_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    channels
+}
+
+/// Length of the secret the hub signs its notifications with
+const SECRET_LENGTH: usize = 32;
+
+/// The subscriptions of the daemon to the hub, checked by [`listen`]: it only confirms
+/// the subscriptions asked for, and only takes the notifications signed with their
+/// secret.
+pub struct Subscriptions {
+    callback: String,
+    /// Random for each run of the daemon, and sent again with each renewal
+    secret: String,
+    /// Topics asked for
+    topics: Mutex<HashSet<String>>,
+}
+
+impl Subscriptions {
+    /// Subscriptions calling back the public URL `callback`
+    pub fn new(callback: &str) -> Self {
+        let secret = rand::thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(SECRET_LENGTH)
+            .map(char::from)
+            .collect();
+        Subscriptions {
+            callback: callback.to_string(),
+            secret,
+            topics: Mutex::default(),
+        }
+    }
+
+    /// Ask the hub to notify the callback of the new uploads of a channel.
+    ///
+    /// The hub checks the subscription with a request to the callback before it starts
+    /// sending notifications.
+    pub async fn subscribe(&self, channel_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let lease = LEASE_SECONDS.to_string();
+        let topic = topic(channel_id);
+        // The check of the hub can come before its answer
+        self.topics().insert(topic.clone());
+        let params = [
+            ("hub.callback", self.callback.as_str()),
+            ("hub.topic", topic.as_str()),
+            ("hub.mode", "subscribe"),
+            ("hub.verify", "async"),
+            ("hub.lease_seconds", lease.as_str()),
+            ("hub.secret", self.secret.as_str()),
+        ];
+
+        let response = reqwest::Client::new()
+            .post(HUB_URL)
+            .form(&params)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "The WebSub hub refused the subscription to {}: {}",
+                channel_id,
+                response.status()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Whether a check of the hub is for a subscription asked for
+    fn confirms(&self, request: &Request) -> bool {
+        let mode = request.query.get("hub.mode").map(String::as_str);
+        let topic = request.query.get("hub.topic");
+        mode == Some("subscribe") && topic.is_some_and(|topic| self.topics().contains(topic))
+    }
+
+    /// Whether a notification was signed by the hub, with an `X-Hub-Signature` header
+    /// holding the HMAC-SHA1 of its body
+    fn is_signed(&self, request: &Request) -> bool {
+        let Some(signature) = request
+            .headers
+            .get("x-hub-signature")
+            .and_then(|header| header.strip_prefix("sha1="))
+            .and_then(decode_hex)
+        else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC takes keys of any length");
+        mac.update(&request.body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    fn topics(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.topics.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Answer the hub on `port` at `path`, sending the channel of each upload notification
+/// to `notifications`.
+///
+/// Runs until the receiving side is dropped.
+pub async fn listen(
+    port: u16,
+    path: String,
+    subscriptions: Arc<Subscriptions>,
+    notifications: mpsc::Sender<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    let channel_pattern = Regex::new(r"<yt:channelId>([^<]+)</yt:channelId>")?;
+    let closed = notifications.clone();
+
+    let handle = move |request: Request, mut stream: TcpStream| {
+        let path = path.clone();
+        let subscriptions = subscriptions.clone();
+        let notifications = notifications.clone();
+        let channel_pattern = channel_pattern.clone();
+        async move {
+            if request.path != path {
+                let _ = http::respond(&mut stream, 404, "text/plain", b"Not found").await;
+                return;
+            }
+
+            match request.method.as_str() {
+                // Subscription check: echo the challenge
+                "GET" => match request.query.get("hub.challenge") {
+                    Some(challenge) if subscriptions.confirms(&request) => {
+                        let _ = http::respond(&mut stream, 200, "text/plain", challenge.as_bytes())
+                            .await;
+                    }
+                    _ => {
+                        let _ = http::respond(&mut stream, 404, "text/plain", b"Not found").await;
+                    }
+                },
+                // Notification: an Atom entry of a new or updated video.
+                // Deleted videos have no channel ID and are ignored.
+                "POST" => {
+                    // Unsigned notifications are acknowledged all the same, as WebSub
+                    // asks, but ignored
+                    let _ = http::respond(&mut stream, 204, "text/plain", b"").await;
+                    if !subscriptions.is_signed(&request) {
+                        return;
+                    }
+                    let body = String::from_utf8_lossy(&request.body);
+                    for captures in channel_pattern.captures_iter(&body) {
+                        let _ = notifications.send(captures[1].to_string()).await;
+                    }
+                }
+                _ => {
+                    let _ =
+                        http::respond(&mut stream, 405, "text/plain", b"Method not allowed").await;
+                }
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = http::serve(listener, handle) => {}
+        _ = closed.closed() => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn request(method: &str, query: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: "/websub".to_string(),
+            query: query
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            headers: HashMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn confirms_only_the_subscriptions_asked_for() {
+        let subscriptions = Subscriptions::new("https://example.com/websub");
+        subscriptions.topics().insert(topic("UCsubscribed"));
+
+        let check = |mode, channel_id| {
+            let topic = topic(channel_id);
+            let request = request(
+                "GET",
+                &[
+                    ("hub.mode", mode),
+                    ("hub.topic", &topic),
+                    ("hub.challenge", "c"),
+                ],
+            );
+            subscriptions.confirms(&request)
+        };
+
+        assert!(check("subscribe", "UCsubscribed"));
+        assert!(!check("subscribe", "UCother"));
+        assert!(!check("unsubscribe", "UCsubscribed"));
+    }
+
+    #[test]
+    fn takes_only_the_notifications_signed_with_the_secret() {
+        let subscriptions = Subscriptions::new("https://example.com/websub");
+        let mut notification = request("POST", &[]);
+        notification.body = b"<yt:channelId>UCchannel</yt:channelId>".to_vec();
+        assert!(!subscriptions.is_signed(&notification));
+
+        let mut mac = Hmac::<Sha1>::new_from_slice(subscriptions.secret.as_bytes()).unwrap();
+        mac.update(&notification.body);
+        let signature: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        notification
+            .headers
+            .insert("x-hub-signature".to_string(), format!("sha1={}", signature));
+        assert!(subscriptions.is_signed(&notification));
+
+        notification.body.push(b' ');
+        assert!(!subscriptions.is_signed(&notification));
+    }
+}
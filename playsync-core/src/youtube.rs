@@ -0,0 +1,1467 @@
+use crate::cache::{self, Cache};
+use crate::concurrency::{self, AdaptiveLimit};
+use crate::source::MusicServices;
+use crate::{metrics, output, ratelimit};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::{StreamExt, TryStreamExt};
+use google_youtube3::{
+    YouTube,
+    api::{
+        Playlist, PlaylistItem, PlaylistItemListResponse, PlaylistItemSnippet, PlaylistSnippet,
+        PlaylistStatus, ResourceId,
+    },
+    hyper_rustls, hyper_util, yup_oauth2,
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Quota cost of a read (list) request, in YouTube Data API units
+const LIST_COST: u64 = 1;
+/// Quota cost of a write (insert/update/delete) request, in YouTube Data API units
+const WRITE_COST: u64 = 50;
+/// Quota cost of a search request, in YouTube Data API units
+const SEARCH_COST: u64 = 100;
+/// Maximum number of IDs accepted by a single videos.list request
+const MAX_IDS_PER_REQUEST: usize = 50;
+
+/// Requests run at once when a command starts, see [`AdaptiveLimit`]
+const INITIAL_CONCURRENCY: usize = 4;
+/// Requests run at once at most, however healthy the responses
+const MAX_CONCURRENCY: usize = 16;
+/// Times a throttled request is sent again, waiting twice as long each time
+const THROTTLED_RETRIES: u32 = 3;
+
+/// Token cache of the main account, in the config directory
+const TOKEN_CACHE: &str = "token_cache.json";
+
+/// OAuth2 scopes asked when signing in
+const SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/youtube.readonly",
+    "https://www.googleapis.com/auth/youtube",
+];
+
+/// Base URL of the YouTube Data API, for the requests the API client can't make
+const API_URL: &str = "https://www.googleapis.com/youtube/v3/";
+
+/// Google only compresses the responses of clients with `gzip` in their user agent
+const USER_AGENT: &str = concat!("playsync/", env!("CARGO_PKG_VERSION"), " (gzip)");
+
+// Fields of the list responses read by playsync, asked with `fields=` so the API leaves
+// the others (thumbnails, localizations, descriptions...) out of its responses
+const PLAYLIST_ITEM_FIELDS: &str = "etag,nextPageToken,pageInfo/totalResults,\
+    items(id,snippet(title,videoOwnerChannelTitle,publishedAt),\
+    contentDetails(videoId,videoPublishedAt))";
+const PLAYLIST_SNIPPET_FIELDS: &str = "items(id,snippet(title,description))";
+const PLAYLIST_ID_FIELDS: &str = "items/id";
+const PLAYLIST_STATE_FIELDS: &str = "items(id,contentDetails/itemCount,status/privacyStatus)";
+const MY_PLAYLIST_FIELDS: &str = "nextPageToken,items(id,snippet/title,contentDetails/itemCount)";
+const SEARCH_FIELDS: &str =
+    "nextPageToken,items(id/videoId,snippet(title,channelTitle,publishedAt))";
+const SUBSCRIPTION_FIELDS: &str = "nextPageToken,items/snippet/resourceId/channelId";
+const CHANNEL_FIELDS: &str = "items/contentDetails/relatedPlaylists/uploads";
+const VIDEO_FIELDS: &str = "items(id,\
+    snippet(title,channelTitle,publishedAt,liveBroadcastContent),\
+    contentDetails/duration,status(uploadStatus,privacyStatus))";
+
+#[derive(Debug, Clone)]
+pub struct VideoInfo {
+    pub video_id: String,
+    pub title: String,
+    /// Title of the channel that uploaded the video
+    pub channel: Option<String>,
+    /// ID of the playlist item holding the video (empty for videos not fetched from a playlist)
+    pub item_id: String,
+    /// When the video was added to the playlist
+    pub added_at: Option<DateTime<Utc>>,
+    /// When the video was published
+    pub published_at: Option<DateTime<Utc>>,
+    /// Metadata looked up with videos.list, see [`YouTubeClient::enrich_videos`]
+    pub details: Option<VideoDetails>,
+}
+
+/// Metadata of a video that is only available from videos.list
+#[derive(Debug, Clone, Default)]
+pub struct VideoDetails {
+    pub title: String,
+    pub channel: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Length of the video (unknown for upcoming and ongoing live streams)
+    pub duration: Option<Duration>,
+    pub availability: Availability,
+    /// Live broadcast state of the video: `none`, `upcoming` or `live`
+    pub live_broadcast_content: Option<String>,
+}
+
+/// Whether a video can be watched, and by whom
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Availability {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+    /// Deleted, rejected, or otherwise not returned by the API
+    Unavailable,
+}
+
+/// Parameters of a YouTube video search
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    /// The search terms
+    pub query: String,
+    /// Order of the results: `date`, `relevance`, `rating`, `title` or `viewCount`
+    pub order: Option<String>,
+    /// Only return videos uploaded by this channel
+    pub channel_id: Option<String>,
+    /// Only return videos published on or after this date
+    pub published_after: Option<DateTime<Utc>>,
+    /// Maximum number of results
+    pub max_results: u32,
+}
+
+/// Title and description of a playlist
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistMetadata {
+    pub title: String,
+    pub description: String,
+}
+
+impl VideoInfo {
+    /// Whether the item is a placeholder left behind by a deleted or private video
+    pub fn is_placeholder(&self) -> bool {
+        self.channel.is_none() && matches!(self.title.as_str(), "Deleted video" | "Private video")
+    }
+
+    /// Duration of the video, if its details were fetched and it has one
+    pub fn duration(&self) -> Option<Duration> {
+        self.details.as_ref().and_then(|details| details.duration)
+    }
+
+    /// Whether the video is an upcoming or ongoing live stream
+    pub fn is_live(&self) -> bool {
+        self.details
+            .as_ref()
+            .and_then(|details| details.live_broadcast_content.as_deref())
+            .is_some_and(|content| content != "none")
+    }
+}
+
+/// A playlist of the authenticated account, as listed by [`YouTubeClient::get_my_playlists`]
+#[derive(Debug, Clone)]
+pub struct PlaylistSummary {
+    pub id: String,
+    pub title: String,
+    pub item_count: u32,
+}
+
+/// Live state of a playlist, as read by [`YouTubeClient::get_playlist_states`]
+#[derive(Debug, Clone)]
+pub struct PlaylistState {
+    pub item_count: u32,
+    /// `public`, `unlisted` or `private`
+    pub privacy: String,
+}
+
+type Hub =
+    YouTube<hyper_rustls::HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>>;
+
+/// Authenticator of offline clients, which never hands out a token, so no request is sent
+#[derive(Clone)]
+struct NoNetwork;
+
+impl google_youtube3::common::GetToken for NoNetwork {
+    #[allow(clippy::type_complexity)]
+    fn get_token<'a>(
+        &'a self,
+        _scopes: &'a [&str],
+    ) -> Pin<
+        Box<
+            dyn Future<Output = Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>>
+                + Send
+                + 'a,
+        >,
+    > {
+        Box::pin(async { Err("YouTube can't be reached offline".into()) })
+    }
+}
+
+pub struct YouTubeClient {
+    hub: Hub,
+    /// Client of the requests made without the API client, with gzip-compressed responses
+    http: reqwest::Client,
+    quota_used: AtomicU64,
+    /// Local copy of the playlist items, `None` when the cache file can't be opened
+    cache: Option<Mutex<Cache>>,
+    /// How old cached items may be to be used instead of fetching the playlist,
+    /// `None` to always fetch it
+    cache_max_age: Option<TimeDelta>,
+    /// Maximum ages of the playlists with their own, replacing `cache_max_age`
+    playlist_cache_max_ages: HashMap<String, Option<TimeDelta>>,
+    /// Only read playlists from the cache, see [`YouTubeClient::offline`]
+    offline: bool,
+    /// How many requests of [`YouTubeClient::concurrently`] run at once
+    concurrency: AdaptiveLimit,
+    /// Credentials music sources are read with, see [`YouTubeClient::use_music_services`]
+    music_services: Mutex<MusicServices>,
+}
+
+impl YouTubeClient {
+    pub async fn new(oauth_json_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let token_cache_path = crate::config::config_dir()?.join(TOKEN_CACHE);
+
+        Self::connect(oauth_json_path, token_cache_path).await
+    }
+
+    /// Whether the main account has signed in before, so a client can be created
+    /// without opening a browser
+    pub fn is_signed_in() -> bool {
+        crate::config::config_dir().is_ok_and(|dir| dir.join(TOKEN_CACHE).is_file())
+    }
+
+    /// Create a client signed in to another Google account than the main one.
+    ///
+    /// The same OAuth2 credentials are used, but the account has its own token cache
+    /// (`token_cache.<account>.json`), so it signs in separately the first time.
+    pub async fn new_for_account(
+        oauth_json_path: &str,
+        account: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let token_cache_path =
+            crate::config::config_dir()?.join(format!("token_cache.{}.json", account));
+
+        Self::connect(oauth_json_path, token_cache_path).await
+    }
+
+    async fn connect(
+        oauth_json_path: &str,
+        token_cache_path: std::path::PathBuf,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // Read OAuth2 credentials from the provided JSON file
+        let secret = yup_oauth2::read_application_secret(oauth_json_path).await?;
+
+        // Create an authenticator with token persistence and required scopes
+        let auth = yup_oauth2::InstalledFlowAuthenticator::builder(
+            secret,
+            yup_oauth2::InstalledFlowReturnMethod::HTTPRedirect,
+        )
+        .persist_tokens_to_disk(token_cache_path)
+        .build()
+        .await?;
+
+        // Force authentication with all required scopes upfront
+        let _ = auth.token(SCOPES).await?;
+
+        Ok(Self {
+            hub: Self::hub(auth)?,
+            http: Self::http()?,
+            quota_used: AtomicU64::new(0),
+            // Commands work without the cache, they only fetch more
+            cache: Cache::open().ok().map(Mutex::new),
+            cache_max_age: None,
+            playlist_cache_max_ages: HashMap::new(),
+            offline: false,
+            concurrency: AdaptiveLimit::new(INITIAL_CONCURRENCY, MAX_CONCURRENCY),
+            music_services: Mutex::default(),
+        })
+    }
+
+    /// Create a client that reads playlists from the cache, whatever their age, and
+    /// makes no requests: everything else fails. No sign-in is needed.
+    pub fn offline() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            hub: Self::hub(NoNetwork)?,
+            http: Self::http()?,
+            quota_used: AtomicU64::new(0),
+            cache: Some(Mutex::new(Cache::open()?)),
+            cache_max_age: None,
+            playlist_cache_max_ages: HashMap::new(),
+            offline: true,
+            concurrency: AdaptiveLimit::new(INITIAL_CONCURRENCY, MAX_CONCURRENCY),
+            music_services: Mutex::default(),
+        })
+    }
+
+    fn hub(
+        auth: impl google_youtube3::common::GetToken + 'static,
+    ) -> Result<Hub, Box<dyn std::error::Error>> {
+        // Create HTTPS connector
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()?
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        // Create the YouTube API hub
+        Ok(YouTube::new(
+            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
+                .build(connector),
+            auth,
+        ))
+    }
+
+    fn http() -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+        Ok(reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .gzip(true)
+            .build()?)
+    }
+
+    /// Whether this client only reads the cache, see [`YouTubeClient::offline`]
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Use cached playlist items up to `max_age` old instead of fetching the playlists,
+    /// or up to the age given for a playlist in `max_ages` (`None` to always fetch it).
+    /// Playlists are always fetched otherwise, and the cache updated.
+    pub fn use_cached_items(
+        &mut self,
+        max_age: Option<TimeDelta>,
+        max_ages: HashMap<String, Option<TimeDelta>>,
+    ) {
+        self.cache_max_age = max_age;
+        self.playlist_cache_max_ages = max_ages;
+    }
+
+    /// Read the music sources (Last.fm, ListenBrainz, Spotify) with these credentials,
+    /// usually [`Config::music_services`](crate::config::Config::music_services). They
+    /// can be changed while the client is shared, like between the rounds of the daemon.
+    pub fn use_music_services(&self, services: MusicServices) {
+        if let Ok(mut current) = self.music_services.lock() {
+            *current = services;
+        }
+    }
+
+    /// The credentials music sources are read with
+    pub fn music_services(&self) -> MusicServices {
+        self.music_services
+            .lock()
+            .map(|services| services.clone())
+            .unwrap_or_default()
+    }
+
+    /// Run `f` on the cache, if it could be opened. Cache errors are ignored: without
+    /// the cache, playlists are only fetched more often.
+    fn with_cache<T>(
+        &self,
+        f: impl FnOnce(&mut Cache) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Option<T> {
+        let mut cache = self.cache.as_ref()?.lock().ok()?;
+        f(&mut cache).ok()
+    }
+
+    /// Estimated number of API quota units used by this client so far
+    pub fn quota_used(&self) -> u64 {
+        self.quota_used.load(Ordering::Relaxed)
+    }
+
+    /// Wait until the rate limits allow a request, then count its quota cost
+    async fn charge(&self, units: u64) {
+        if units == WRITE_COST {
+            ratelimit::write().await;
+        }
+        ratelimit::request().await;
+        self.quota_used.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// Make a request for each item, several at once, and return the results in the
+    /// order of the items.
+    ///
+    /// How many run at once follows the responses of the API: fewer when it throttles
+    /// playsync, more while it answers fine. Throttled requests are sent again after a
+    /// pause. Requests must not make requests through this method themselves.
+    pub async fn concurrently<I, T, F>(
+        &self,
+        items: impl IntoIterator<Item = I>,
+        request: impl Fn(I) -> F,
+    ) -> Result<Vec<T>, Box<dyn std::error::Error>>
+    where
+        I: Clone,
+        F: Future<Output = Result<T, Box<dyn std::error::Error>>>,
+    {
+        let request = &request;
+        futures::stream::iter(items)
+            .map(|item| async move {
+                let mut retries = 0;
+                loop {
+                    let permit = self.concurrency.acquire().await;
+                    let result = request(item.clone()).await;
+                    self.concurrency.record(&result);
+                    drop(permit);
+
+                    match result {
+                        Err(e)
+                            if retries < THROTTLED_RETRIES
+                                && concurrency::is_throttled(e.as_ref()) =>
+                        {
+                            retries += 1;
+                            if output::is_verbose() {
+                                eprintln!(
+                                    "Throttled by YouTube, down to {} request(s) at once",
+                                    self.concurrency.current()
+                                );
+                            }
+                            tokio::time::sleep(Duration::from_secs(1 << retries)).await;
+                        }
+                        result => break result,
+                    }
+                }
+            })
+            .buffered(MAX_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    pub async fn get_playlist_title(
+        &self,
+        playlist_id: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.charge(LIST_COST).await;
+        let result = metrics::timed(
+            self.hub
+                .playlists()
+                .list(&vec!["snippet".to_string()])
+                .add_id(playlist_id)
+                .param("fields", PLAYLIST_SNIPPET_FIELDS)
+                .doit(),
+        )
+        .await?;
+
+        if let Some(items) = result.1.items {
+            if let Some(playlist) = items.first() {
+                if let Some(snippet) = &playlist.snippet {
+                    return Ok(snippet.title.clone().unwrap_or_default());
+                }
+            }
+        }
+
+        Err("Playlist not found".into())
+    }
+
+    /// Get the title and description of playlists, 50 per request.
+    ///
+    /// Playlists missing from the result were deleted, or are private and owned by
+    /// another account.
+    pub async fn get_playlists_metadata(
+        &self,
+        playlist_ids: &[&str],
+    ) -> Result<HashMap<String, PlaylistMetadata>, Box<dyn std::error::Error>> {
+        let responses = self
+            .concurrently(
+                playlist_ids.chunks(MAX_IDS_PER_REQUEST),
+                |chunk| async move {
+                    let mut request = self
+                        .hub
+                        .playlists()
+                        .list(&vec!["snippet".to_string()])
+                        .max_results(MAX_IDS_PER_REQUEST as u32)
+                        .param("fields", PLAYLIST_SNIPPET_FIELDS);
+
+                    for playlist_id in chunk {
+                        request = request.add_id(playlist_id);
+                    }
+
+                    self.charge(LIST_COST).await;
+                    Ok(metrics::timed(request.doit()).await?.1)
+                },
+            )
+            .await?;
+
+        let mut metadata = HashMap::new();
+        for response in responses {
+            for playlist in response.items.unwrap_or_default() {
+                let (Some(id), Some(snippet)) = (playlist.id, playlist.snippet) else {
+                    continue;
+                };
+                metadata.insert(
+                    id,
+                    PlaylistMetadata {
+                        title: snippet.title.unwrap_or_default(),
+                        description: snippet.description.unwrap_or_default(),
+                    },
+                );
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Update the title and description of a playlist
+    pub async fn update_playlist_metadata(
+        &self,
+        playlist_id: &str,
+        metadata: &PlaylistMetadata,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // The whole snippet is replaced, so the title must always be sent along
+        let playlist = Playlist {
+            id: Some(playlist_id.to_string()),
+            snippet: Some(PlaylistSnippet {
+                title: Some(metadata.title.clone()),
+                description: Some(metadata.description.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.charge(WRITE_COST).await;
+        metrics::timed(
+            self.hub
+                .playlists()
+                .update(playlist)
+                .add_part("snippet")
+                .doit(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Check whether a playlist exists and is visible to the authenticated account
+    pub async fn playlist_exists(
+        &self,
+        playlist_id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        // Reading its items tells whether it's cached
+        if self.offline {
+            return Ok(true);
+        }
+
+        self.charge(LIST_COST).await;
+        let result = metrics::timed(
+            self.hub
+                .playlists()
+                .list(&vec!["id".to_string()])
+                .add_id(playlist_id)
+                .param("fields", PLAYLIST_ID_FIELDS)
+                .doit(),
+        )
+        .await?;
+
+        Ok(result.1.items.is_some_and(|items| !items.is_empty()))
+    }
+
+    /// Get the item count and privacy status of playlists, 50 per request.
+    ///
+    /// Playlists missing from the result were deleted, or are private and owned by
+    /// another account.
+    pub async fn get_playlist_states(
+        &self,
+        playlist_ids: &[String],
+    ) -> Result<HashMap<String, PlaylistState>, Box<dyn std::error::Error>> {
+        let responses = self
+            .concurrently(
+                playlist_ids.chunks(MAX_IDS_PER_REQUEST),
+                |chunk| async move {
+                    let mut request = self
+                        .hub
+                        .playlists()
+                        .list(&vec!["contentDetails".to_string(), "status".to_string()])
+                        .max_results(MAX_IDS_PER_REQUEST as u32)
+                        .param("fields", PLAYLIST_STATE_FIELDS);
+
+                    for playlist_id in chunk {
+                        request = request.add_id(playlist_id);
+                    }
+
+                    self.charge(LIST_COST).await;
+                    Ok(metrics::timed(request.doit()).await?.1)
+                },
+            )
+            .await?;
+
+        let mut states = HashMap::new();
+        for response in responses {
+            for playlist in response.items.unwrap_or_default() {
+                let Some(id) = playlist.id else {
+                    continue;
+                };
+                states.insert(
+                    id,
+                    PlaylistState {
+                        item_count: playlist
+                            .content_details
+                            .and_then(|details| details.item_count)
+                            .unwrap_or_default(),
+                        privacy: playlist
+                            .status
+                            .and_then(|status| status.privacy_status)
+                            .unwrap_or_default(),
+                    },
+                );
+            }
+        }
+
+        Ok(states)
+    }
+
+    /// Create a new playlist on the authenticated account and return its ID
+    pub async fn create_playlist(
+        &self,
+        title: &str,
+        description: &str,
+        privacy: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let playlist = Playlist {
+            snippet: Some(PlaylistSnippet {
+                title: Some(title.to_string()),
+                description: Some(description.to_string()),
+                ..Default::default()
+            }),
+            status: Some(PlaylistStatus {
+                privacy_status: Some(privacy.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.charge(WRITE_COST).await;
+        let result = metrics::timed(
+            self.hub
+                .playlists()
+                .insert(playlist)
+                .add_part("snippet")
+                .add_part("status")
+                .doit(),
+        )
+        .await?;
+
+        Ok(result.1.id.ok_or("Created playlist has no ID")?)
+    }
+
+    pub async fn get_playlist_items(
+        &self,
+        playlist_id: &str,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        self.fetch_playlist_items(playlist_id, None, None).await
+    }
+
+    /// Get the items of a playlist like [`get_playlist_items`](Self::get_playlist_items),
+    /// with a progress bar showing the items fetched out of the total and the time left
+    pub async fn get_playlist_items_with_progress(
+        &self,
+        playlist_id: &str,
+        label: &str,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        self.fetch_playlist_items(playlist_id, None, Some(label))
+            .await
+    }
+
+    /// Download the items of a playlist again like
+    /// [`get_playlist_items_with_progress`](Self::get_playlist_items_with_progress),
+    /// ignoring its cached copy and its ETag
+    pub async fn refresh_playlist_items(
+        &self,
+        playlist_id: &str,
+        label: &str,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        self.with_cache(|cache| cache.remove(playlist_id));
+        self.get_playlist_items_with_progress(playlist_id, label)
+            .await
+    }
+
+    /// Get the items of a playlist published on or after `since`.
+    ///
+    /// Meant for playlists ordered newest first, like the uploads playlist of a
+    /// channel: pages stop being fetched as soon as an older item is found.
+    pub async fn get_recent_playlist_items(
+        &self,
+        playlist_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        self.fetch_playlist_items(playlist_id, Some(since), None)
+            .await
+    }
+
+    async fn fetch_playlist_items(
+        &self,
+        playlist_id: &str,
+        since: Option<DateTime<Utc>>,
+        progress: Option<&str>,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        let mut pages = self.pages(playlist_id, since, progress).await?;
+        let mut videos = Vec::new();
+        while let Some(page) = pages.next_page().await? {
+            videos.extend(page);
+        }
+        Ok(videos)
+    }
+
+    /// Read the items of a playlist a page of 50 at a time, like
+    /// [`get_playlist_items`](Self::get_playlist_items), so each page can be processed
+    /// before the next one is fetched. With a label, a progress bar shows the items read.
+    pub async fn playlist_pages<'a>(
+        &'a self,
+        playlist_id: &str,
+        progress: Option<&str>,
+    ) -> Result<PlaylistPages<'a>, Box<dyn std::error::Error>> {
+        self.pages(playlist_id, None, progress).await
+    }
+
+    async fn pages<'a>(
+        &'a self,
+        playlist_id: &str,
+        since: Option<DateTime<Utc>>,
+        progress: Option<&str>,
+    ) -> Result<PlaylistPages<'a>, Box<dyn std::error::Error>> {
+        let mut pages = PlaylistPages {
+            client: self,
+            playlist_id: playlist_id.to_string(),
+            since,
+            progress: progress.map(|label| {
+                let bar = crate::ui::progress_bar(0);
+                bar.start(&format!("Fetching {}", label));
+                (bar, label.to_string())
+            }),
+            cached: None,
+            first_page: None,
+            page_token: None,
+            etag: None,
+            position: 0,
+            done: false,
+        };
+
+        // Only whole playlists are cached
+        let cached = match since {
+            Some(_) => None,
+            None => self
+                .with_cache(|cache| cache.playlist(playlist_id))
+                .flatten(),
+        };
+        if self.offline {
+            pages.cached = Some(match cached {
+                Some(cached) => cached.items,
+                None if since.is_some() => {
+                    return Err("Recent uploads aren't cached, they can't be read offline".into());
+                }
+                None => {
+                    return Err(format!(
+                        "Playlist {} isn't in the cache, read it once online to use it offline",
+                        playlist_id
+                    )
+                    .into());
+                }
+            });
+            return Ok(pages);
+        }
+        if let Some(cached) = cached {
+            let now = Utc::now();
+            if self
+                .playlist_cache_max_ages
+                .get(playlist_id)
+                .copied()
+                .unwrap_or(self.cache_max_age)
+                .is_some_and(|max_age| now - cached.checked_at <= max_age)
+            {
+                pages.cached = Some(cached.items);
+                return Ok(pages);
+            }
+
+            if let Some(etag) = cached
+                .etag
+                .filter(|_| now - cached.fetched_at <= cache::REVALIDATE_FOR)
+            {
+                match self
+                    .playlist_items_page(playlist_id, None, Some(&etag))
+                    .await
+                {
+                    Ok(None) => {
+                        self.with_cache(|cache| cache.touch(playlist_id));
+                        pages.cached = Some(cached.items);
+                    }
+                    Ok(Some(page)) => pages.first_page = Some(page),
+                    // Fetched the usual way, which reports the error if it isn't only
+                    // about the conditional request
+                    Err(_) => {}
+                }
+            }
+        }
+
+        Ok(pages)
+    }
+
+    /// Fetch a page of 50 items of a playlist. With `etag`, `None` is returned if the
+    /// page still has this ETag: the playlist is unchanged.
+    ///
+    /// The API client can neither send an `If-None-Match` header nor ask for compressed
+    /// responses, so the request is made with the client's token. Large playlists take
+    /// many pages, which gzip makes several times smaller.
+    async fn playlist_items_page(
+        &self,
+        playlist_id: &str,
+        page_token: Option<&str>,
+        etag: Option<&str>,
+    ) -> Result<Option<PlaylistItemListResponse>, Box<dyn std::error::Error>> {
+        let token = self
+            .hub
+            .auth
+            .get_token(SCOPES)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("The YouTube client has no access token")?;
+
+        let mut request = self
+            .http
+            .get(format!("{}playlistItems", API_URL))
+            .query(&[
+                ("part", "snippet,contentDetails"),
+                ("playlistId", playlist_id),
+                ("maxResults", "50"),
+                ("fields", PLAYLIST_ITEM_FIELDS),
+            ])
+            .bearer_auth(token);
+        if let Some(page_token) = page_token {
+            request = request.query(&[("pageToken", page_token)]);
+        }
+        if let Some(etag) = etag {
+            request = request.header(
+                reqwest::header::IF_NONE_MATCH,
+                format!("\"{}\"", etag.trim_matches('"')),
+            );
+        }
+
+        self.charge(LIST_COST).await;
+        let response = metrics::timed(request.send()).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            // Raised like the errors of the API client, so quota and sign-in errors
+            // end playsync with their exit code
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            return Err(google_youtube3::Error::BadRequest(body).into());
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// Search for videos, following result pages until `max_results` videos are found.
+    ///
+    /// Each page costs 100 quota units, so keep `max_results` low.
+    pub async fn search_videos(
+        &self,
+        search: &SearchQuery,
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        let mut videos = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        while videos.len() < search.max_results as usize {
+            let mut request = self
+                .hub
+                .search()
+                .list(&vec!["snippet".to_string()])
+                .q(&search.query)
+                .add_type("video")
+                .max_results((search.max_results as usize - videos.len()).min(50) as u32)
+                .param("fields", SEARCH_FIELDS);
+
+            if let Some(order) = &search.order {
+                request = request.order(order);
+            }
+            if let Some(channel_id) = &search.channel_id {
+                request = request.channel_id(channel_id);
+            }
+            if let Some(published_after) = search.published_after {
+                request = request.published_after(published_after);
+            }
+            if let Some(token) = &page_token {
+                request = request.page_token(token);
+            }
+
+            self.charge(SEARCH_COST).await;
+            let result = metrics::timed(request.doit()).await?;
+
+            for item in result.1.items.unwrap_or_default() {
+                if let (Some(video_id), Some(snippet)) =
+                    (item.id.and_then(|id| id.video_id), item.snippet)
+                {
+                    videos.push(VideoInfo {
+                        video_id,
+                        title: snippet.title.unwrap_or_default(),
+                        channel: snippet.channel_title,
+                        item_id: String::new(),
+                        added_at: None,
+                        published_at: snippet.published_at,
+                        details: None,
+                    });
+                }
+            }
+
+            page_token = result.1.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(videos)
+    }
+
+    /// Get every playlist of the authenticated account
+    pub async fn get_my_playlists(
+        &self,
+    ) -> Result<Vec<PlaylistSummary>, Box<dyn std::error::Error>> {
+        let mut playlists = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .hub
+                .playlists()
+                .list(&vec!["snippet".to_string(), "contentDetails".to_string()])
+                .mine(true)
+                .max_results(50)
+                .param("fields", MY_PLAYLIST_FIELDS);
+
+            if let Some(token) = &page_token {
+                request = request.page_token(token);
+            }
+
+            self.charge(LIST_COST).await;
+            let result = metrics::timed(request.doit()).await?;
+
+            for playlist in result.1.items.unwrap_or_default() {
+                if let Some(id) = playlist.id {
+                    playlists.push(PlaylistSummary {
+                        id,
+                        title: playlist
+                            .snippet
+                            .and_then(|snippet| snippet.title)
+                            .unwrap_or_default(),
+                        item_count: playlist
+                            .content_details
+                            .and_then(|details| details.item_count)
+                            .unwrap_or_default(),
+                    });
+                }
+            }
+
+            page_token = result.1.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    /// Get the IDs of all channels the authenticated account subscribes to
+    pub async fn get_subscribed_channels(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut channel_ids = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .hub
+                .subscriptions()
+                .list(&vec!["snippet".to_string()])
+                .mine(true)
+                .max_results(50)
+                .param("fields", SUBSCRIPTION_FIELDS);
+
+            if let Some(token) = &page_token {
+                request = request.page_token(token);
+            }
+
+            self.charge(LIST_COST).await;
+            let result = metrics::timed(request.doit()).await?;
+
+            for subscription in result.1.items.unwrap_or_default() {
+                if let Some(channel_id) = subscription
+                    .snippet
+                    .and_then(|snippet| snippet.resource_id)
+                    .and_then(|resource| resource.channel_id)
+                {
+                    channel_ids.push(channel_id);
+                }
+            }
+
+            page_token = result.1.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(channel_ids)
+    }
+
+    /// Get the uploads playlist of each of the given channels, in batches of 50 channels
+    pub async fn get_uploads_playlists(
+        &self,
+        channel_ids: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let responses = self
+            .concurrently(
+                channel_ids.chunks(MAX_IDS_PER_REQUEST),
+                |chunk| async move {
+                    let mut request = self
+                        .hub
+                        .channels()
+                        .list(&vec!["contentDetails".to_string()])
+                        .max_results(MAX_IDS_PER_REQUEST as u32)
+                        .param("fields", CHANNEL_FIELDS);
+
+                    for channel_id in chunk {
+                        request = request.add_id(channel_id);
+                    }
+
+                    self.charge(LIST_COST).await;
+                    Ok(metrics::timed(request.doit()).await?.1)
+                },
+            )
+            .await?;
+
+        let mut playlist_ids = Vec::new();
+        for response in responses {
+            for channel in response.items.unwrap_or_default() {
+                if let Some(uploads) = channel
+                    .content_details
+                    .and_then(|details| details.related_playlists)
+                    .and_then(|playlists| playlists.uploads)
+                {
+                    playlist_ids.push(uploads);
+                }
+            }
+        }
+
+        Ok(playlist_ids)
+    }
+
+    /// Look up videos by ID.
+    ///
+    /// Videos that do not exist or are not visible to the authenticated account
+    /// are left out of the result.
+    pub async fn get_videos(
+        &self,
+        video_ids: &[String],
+    ) -> Result<Vec<VideoInfo>, Box<dyn std::error::Error>> {
+        let details = self.get_video_details(video_ids).await?;
+
+        Ok(video_ids
+            .iter()
+            .filter_map(|video_id| {
+                let details = details.get(video_id)?;
+                (details.availability != Availability::Unavailable).then(|| VideoInfo {
+                    video_id: video_id.clone(),
+                    title: details.title.clone(),
+                    channel: details.channel.clone(),
+                    item_id: String::new(),
+                    added_at: None,
+                    published_at: details.published_at,
+                    details: Some(details.clone()),
+                })
+            })
+            .collect())
+    }
+
+    /// Fetch the details (duration, channel, publish date, availability and live
+    /// state) of each of the given videos.
+    ///
+    /// Videos are looked up in batches of 50 IDs per request. Every requested ID is
+    /// in the result: videos not returned by the API are marked as unavailable.
+    pub async fn get_video_details(
+        &self,
+        video_ids: &[String],
+    ) -> Result<HashMap<String, VideoDetails>, Box<dyn std::error::Error>> {
+        let responses = self
+            .concurrently(video_ids.chunks(MAX_IDS_PER_REQUEST), |chunk| async move {
+                let mut request = self
+                    .hub
+                    .videos()
+                    .list(&vec![
+                        "snippet".to_string(),
+                        "contentDetails".to_string(),
+                        "status".to_string(),
+                    ])
+                    .max_results(MAX_IDS_PER_REQUEST as u32)
+                    .param("fields", VIDEO_FIELDS);
+
+                for video_id in chunk {
+                    request = request.add_id(video_id);
+                }
+
+                self.charge(LIST_COST).await;
+                Ok(metrics::timed(request.doit()).await?.1)
+            })
+            .await?;
+
+        let mut details = HashMap::new();
+        for response in responses {
+            for video in response.items.unwrap_or_default() {
+                let Some(video_id) = video.id else {
+                    continue;
+                };
+                let snippet = video.snippet.unwrap_or_default();
+                let status = video.status.unwrap_or_default();
+
+                let availability = match (
+                    status.upload_status.as_deref(),
+                    status.privacy_status.as_deref(),
+                ) {
+                    (Some("deleted" | "failed" | "rejected"), _) => Availability::Unavailable,
+                    (_, Some("private")) => Availability::Private,
+                    (_, Some("unlisted")) => Availability::Unlisted,
+                    _ => Availability::Public,
+                };
+
+                details.insert(
+                    video_id,
+                    VideoDetails {
+                        title: snippet.title.unwrap_or_default(),
+                        channel: snippet.channel_title,
+                        published_at: snippet.published_at,
+                        duration: video
+                            .content_details
+                            .and_then(|details| details.duration)
+                            .and_then(|duration| parse_duration(&duration))
+                            // Live streams report a zero duration until they are over
+                            .filter(|duration| !duration.is_zero()),
+                        availability,
+                        live_broadcast_content: snippet.live_broadcast_content,
+                    },
+                );
+            }
+        }
+
+        for video_id in video_ids {
+            details
+                .entry(video_id.clone())
+                .or_insert_with(|| VideoDetails {
+                    availability: Availability::Unavailable,
+                    ..Default::default()
+                });
+        }
+
+        Ok(details)
+    }
+
+    /// Attach the details of each video, fetched with [`Self::get_video_details`].
+    ///
+    /// The channel and publish date are also filled in when the playlist item did not
+    /// have them. Videos that already have details are not looked up again.
+    pub async fn enrich_videos(
+        &self,
+        videos: &mut [VideoInfo],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut video_ids: Vec<String> = videos
+            .iter()
+            .filter(|video| video.details.is_none())
+            .map(|video| video.video_id.clone())
+            .collect();
+        video_ids.sort();
+        video_ids.dedup();
+
+        let details = self.get_video_details(&video_ids).await?;
+
+        for video in videos.iter_mut().filter(|video| video.details.is_none()) {
+            if let Some(details) = details.get(&video.video_id) {
+                if video.channel.is_none() {
+                    video.channel = details.channel.clone();
+                }
+                if video.published_at.is_none() {
+                    video.published_at = details.published_at;
+                }
+                video.details = Some(details.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a video to a playlist, at the end or at the given zero-based position
+    pub async fn add_video_to_playlist(
+        &self,
+        playlist_id: &str,
+        video_id: &str,
+        position: Option<u32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let playlist_item = PlaylistItem {
+            snippet: Some(PlaylistItemSnippet {
+                playlist_id: Some(playlist_id.to_string()),
+                position,
+                resource_id: Some(ResourceId {
+                    kind: Some("youtube#video".to_string()),
+                    video_id: Some(video_id.to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.charge(WRITE_COST).await;
+        metrics::timed(
+            self.hub
+                .playlist_items()
+                .insert(playlist_item)
+                .add_part("snippet")
+                .doit(),
+        )
+        .await?;
+        self.with_cache(|cache| cache.remove(playlist_id));
+
+        Ok(())
+    }
+
+    /// Move an existing playlist item to a new zero-based position in its playlist.
+    ///
+    /// The position is the index the item has once the move is done: the other items
+    /// shift to make room for it. Positions past the end of the playlist are rejected
+    /// by the API, so callers should keep them below the item count.
+    pub async fn move_playlist_item(
+        &self,
+        playlist_id: &str,
+        video: &VideoInfo,
+        position: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let playlist_item = PlaylistItem {
+            id: Some(video.item_id.clone()),
+            snippet: Some(PlaylistItemSnippet {
+                playlist_id: Some(playlist_id.to_string()),
+                position: Some(position),
+                resource_id: Some(ResourceId {
+                    kind: Some("youtube#video".to_string()),
+                    video_id: Some(video.video_id.clone()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.charge(WRITE_COST).await;
+        metrics::timed(
+            self.hub
+                .playlist_items()
+                .update(playlist_item)
+                .add_part("snippet")
+                .doit(),
+        )
+        .await
+        .map_err(|e| -> Box<dyn std::error::Error> {
+            // Playlists sorted automatically (e.g. by date added) can't be reordered
+            if e.to_string().contains("manualSortRequired") {
+                "The playlist must use manual ordering on YouTube to be reordered".into()
+            } else {
+                e.into()
+            }
+        })?;
+        self.with_cache(|cache| cache.remove(playlist_id));
+
+        Ok(())
+    }
+
+    /// Remove items from their playlist, returning the number of removed items
+    pub async fn remove_playlist_items(
+        &self,
+        item_ids: &[String],
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        for item_id in item_ids {
+            self.remove_playlist_item(item_id).await?;
+        }
+
+        Ok(item_ids.len())
+    }
+
+    /// Remove an item from its playlist, using the playlist item ID (not the video ID)
+    pub async fn remove_playlist_item(
+        &self,
+        item_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.charge(WRITE_COST).await;
+        metrics::timed(self.hub.playlist_items().delete(item_id).doit()).await?;
+        self.with_cache(|cache| cache.remove_item(item_id));
+
+        Ok(())
+    }
+}
+
+/// The items of a playlist, read a page at a time with [`PlaylistPages::next_page`].
+///
+/// Fetched playlists are written to the cache page by page, and only count as cached
+/// once their last page is written.
+pub struct PlaylistPages<'a> {
+    client: &'a YouTubeClient,
+    playlist_id: String,
+    /// Only read the items published on or after this date, see
+    /// [`YouTubeClient::get_recent_playlist_items`]
+    since: Option<DateTime<Utc>>,
+    progress: Option<(Box<dyn crate::ui::Progress>, String)>,
+    /// Items read from the cache, handed out as a single page
+    cached: Option<Vec<VideoInfo>>,
+    /// First page, fetched when checking the ETag of the cached items
+    first_page: Option<PlaylistItemListResponse>,
+    page_token: Option<String>,
+    /// ETag of the first page, which changes with the playlist, its item count included
+    etag: Option<String>,
+    /// Number of items fetched so far
+    position: usize,
+    done: bool,
+}
+
+impl PlaylistPages<'_> {
+    /// The next page of items, `None` once they were all read
+    pub async fn next_page(
+        &mut self,
+    ) -> Result<Option<Vec<VideoInfo>>, Box<dyn std::error::Error>> {
+        if self.done {
+            return Ok(None);
+        }
+        if let Some(cached) = self.cached.take() {
+            self.done = true;
+            return Ok(Some(cached));
+        }
+
+        let client = self.client;
+        let page = match self.first_page.take() {
+            Some(page) => page,
+            None => client
+                .playlist_items_page(&self.playlist_id, self.page_token.as_deref(), None)
+                .await?
+                .ok_or("The API answered an unconditional request with 304")?,
+        };
+
+        if let Some((bar, label)) = &self.progress {
+            if let Some(total) = page.page_info.as_ref().and_then(|p| p.total_results) {
+                bar.set_length(total.max(0) as u64);
+            }
+            let page = page.items.as_deref().unwrap_or_default();
+            bar.inc(page.len() as u64);
+            if let Some(title) = page
+                .last()
+                .and_then(|item| item.snippet.as_ref()?.title.as_ref())
+            {
+                bar.set_message(&format!("Fetching {}: {}", label, title));
+            }
+        }
+
+        let mut videos = Vec::new();
+        for item in page.items.unwrap_or_default() {
+            if let (Some(snippet), Some(content_details)) = (&item.snippet, &item.content_details)
+                && let Some(video_id) = &content_details.video_id
+            {
+                videos.push(VideoInfo {
+                    video_id: video_id.clone(),
+                    title: snippet.title.clone().unwrap_or_default(),
+                    channel: snippet.video_owner_channel_title.clone(),
+                    item_id: item.id.clone().unwrap_or_default(),
+                    added_at: snippet.published_at,
+                    published_at: content_details.video_published_at,
+                    details: None,
+                });
+            }
+        }
+
+        let first = self.page_token.is_none();
+        if first {
+            self.etag = page.etag;
+        }
+        self.page_token = page.next_page_token;
+        self.done = self.page_token.is_none();
+
+        match self.since {
+            Some(since) => {
+                let is_old = |video: &VideoInfo| video.published_at.is_none_or(|date| date < since);
+                if videos.iter().any(is_old) {
+                    videos.retain(|video| !is_old(video));
+                    self.done = true;
+                }
+            }
+            None => {
+                client.with_cache(|cache| {
+                    if first {
+                        cache.start_store(&self.playlist_id)?;
+                    }
+                    cache.store_items(&self.playlist_id, self.position, &videos)?;
+                    if self.done {
+                        cache.finish_store(&self.playlist_id, self.etag.as_deref())?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        self.position += videos.len();
+
+        Ok(Some(videos))
+    }
+}
+
+impl Drop for PlaylistPages<'_> {
+    fn drop(&mut self) {
+        if let Some((bar, _)) = &self.progress {
+            bar.clear();
+        }
+    }
+}
+
+/// URL of a playlist on the YouTube website
+pub fn playlist_url(playlist_id: &str) -> String {
+    format!("https://www.youtube.com/playlist?list={}", playlist_id)
+}
+
+/// URL of a video on the YouTube website
+pub fn video_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
+}
+
+/// Parse an ISO 8601 duration as returned by the API (e.g. `PT1H2M3S` or `P1DT2H`)
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.strip_prefix('P')?;
+    let mut seconds = 0u64;
+    let mut in_time = false;
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = stripped;
+            continue;
+        }
+
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: u64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+
+        seconds += amount
+            * match (unit, in_time) {
+                ('W', false) => 7 * 24 * 3600,
+                ('D', false) => 24 * 3600,
+                ('H', true) => 3600,
+                ('M', true) => 60,
+                ('S', true) => 1,
+                _ => return None,
+            };
+
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+
+    Some(Duration::from_secs(seconds))
+}
+
+/// Extract a video ID from a YouTube URL or a bare video ID.
+///
+/// Supports `watch?v=` URLs (including music.youtube.com), `youtu.be` short links,
+/// and `shorts`, `embed` and `live` URLs.
+pub fn parse_video_id(input: &str) -> Option<String> {
+    let input = input.trim();
+
+    let candidate = if let Some((_, query)) = input.split_once("v=") {
+        query.split(['&', '#']).next()
+    } else if let Some((_, path)) = input.split_once("youtu.be/") {
+        path.split(['?', '&', '#', '/']).next()
+    } else if let Some((_, path)) = ["/shorts/", "/embed/", "/live/"]
+        .iter()
+        .find_map(|prefix| input.split_once(prefix))
+    {
+        path.split(['?', '&', '#', '/']).next()
+    } else {
+        Some(input)
+    }?;
+
+    let is_valid = candidate.len() == 11
+        && candidate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    is_valid.then(|| candidate.to_string())
+}
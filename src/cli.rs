@@ -0,0 +1,602 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use cliclack::outro;
+use playsync_core::{config, filter, graph, history, import, output, sort, split, tidy};
+
+#[derive(Parser, Debug)]
+pub struct Cli {
+    /// Use this configuration file instead of the default one
+    #[clap(long, global = true, env = "PLAYSYNC_CONFIG", value_name = "PATH")]
+    pub config: Option<std::path::PathBuf>,
+
+    /// Use a named profile, with its own credentials and playlists
+    #[clap(long, global = true, env = "PLAYSYNC_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Print the results of `config --list`, `sync`, `status`, `stats` and `history` as
+    /// JSON or YAML on stdout, for scripts, or report syncs to GitHub Actions with `gha`
+    #[clap(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        env = "PLAYSYNC_OUTPUT"
+    )]
+    pub output: output::OutputFormat,
+
+    /// Show more details, like why each video of the sources was skipped
+    #[clap(short = 'v', long, global = true)]
+    pub verbose: bool,
+
+    /// Answer yes to every confirmation, and fail instead of asking anything else
+    #[clap(short = 'y', long, global = true, env = "PLAYSYNC_YES")]
+    pub yes: bool,
+
+    /// Show messages as plain ASCII lines without colors, also done when NO_COLOR is set
+    #[clap(long, global = true)]
+    pub no_color: bool,
+
+    /// Only show errors, for cron jobs and other unattended runs
+    #[clap(
+        short = 'q',
+        long,
+        global = true,
+        env = "PLAYSYNC_QUIET",
+        conflicts_with = "verbose"
+    )]
+    pub quiet: bool,
+
+    /// The command to execute
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Set up playsync step by step: credentials, sign-in and playlists
+    Init,
+    /// Manage playlist configuration
+    Config(ConfigArgs),
+    /// Sync playlists based on configuration
+    Sync {
+        /// Playlist IDs to sync, repeatable (optional, syncs all if not specified)
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_ids: Vec<String>,
+        /// Playlist IDs to leave out, repeatable
+        #[clap(short = 'x', long, value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        exclude: Vec<String>,
+        /// Only sync the playlists tagged with this group
+        #[clap(short = 'g', long, conflicts_with = "playlist_ids")]
+        group: Option<String>,
+        /// Pick the playlists to sync from a list
+        #[clap(long, conflicts_with = "playlist_ids")]
+        pick: bool,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+        /// Compute the dry run from the playlists in the local cache, without network
+        /// access
+        #[clap(long, requires = "dry_run")]
+        offline: bool,
+        /// Make changes even if `dry_run` is set in the configuration defaults. With a
+        /// plan file, make exactly the changes it holds instead of syncing.
+        #[clap(long, value_name = "PLAN", num_args = 0..=1, conflicts_with = "dry_run")]
+        apply: Option<Option<std::path::PathBuf>>,
+        /// Do a dry run and write its changes to a plan file, to review them and make
+        /// them later with `--apply PLAN`
+        #[clap(long, value_name = "PLAN", conflicts_with = "apply")]
+        plan: Option<std::path::PathBuf>,
+        /// Don't sync outside the `sync_windows` of the configuration
+        #[clap(long)]
+        respect_windows: bool,
+    },
+    /// Show the history of previous runs
+    History(history::HistoryArgs),
+    /// Show, refresh and clear the local cache of playlist items
+    Cache(CacheArgs),
+    /// Show how many items each playlist is behind its sources
+    Status {
+        /// Playlist ID to check (optional, checks all if not specified)
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: Option<String>,
+    },
+    /// Browse the playlists, their sync status and pending changes in a full-screen interface
+    Tui,
+    /// Print which playlists sync from which, to render with Graphviz or Mermaid
+    Graph {
+        /// Format of the graph
+        #[clap(short = 'f', long, value_enum, default_value = "dot")]
+        format: graph::GraphFormat,
+    },
+    /// Tell why a video was or wasn't added to a playlist by its sources
+    Explain {
+        /// A video ID or a video URL
+        video: String,
+        /// The target playlist
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+    },
+    /// Find a video across all configured playlists
+    Search {
+        /// Part of the video title, a video ID or a video URL
+        query: String,
+    },
+    /// Create a new playlist from the union of existing playlists
+    Merge {
+        /// IDs of the playlists to merge
+        #[clap(required = true, value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        sources: Vec<String>,
+        /// Title of the new playlist
+        #[clap(short = 't', long)]
+        title: String,
+        /// Description of the new playlist
+        #[clap(long, default_value = "")]
+        description: String,
+        /// Privacy status of the new playlist
+        #[clap(short = 'p', long, default_value = "private", value_parser = ["private", "unlisted", "public"])]
+        privacy: String,
+        /// Sort the merged items instead of keeping the source order
+        #[clap(short = 's', long, value_enum)]
+        sort: Option<sort::SortKey>,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Split a playlist into several new playlists
+    Split {
+        /// ID of the playlist to split
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+        /// How to divide the items between the new playlists
+        #[clap(short = 'b', long, value_enum, default_value = "count")]
+        by: split::SplitBy,
+        /// Number of items per part when splitting by count
+        #[clap(short = 's', long, default_value_t = 200)]
+        size: usize,
+        /// Privacy status of the new playlists
+        #[clap(short = 'p', long, default_value = "private", value_parser = ["private", "unlisted", "public"])]
+        privacy: String,
+        /// Copy the items instead of moving them out of the original playlist
+        #[clap(short = 'k', long)]
+        keep: bool,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Copy the items of a playlist into another playlist once
+    Copy {
+        /// ID of the playlist to copy from
+        #[clap(value_name = "SOURCE_ID", add = ArgValueCompleter::new(complete_playlist))]
+        source: String,
+        /// ID of the playlist to copy to
+        #[clap(value_name = "TARGET_ID", add = ArgValueCompleter::new(complete_playlist))]
+        target: String,
+        /// Maximum number of items to copy
+        #[clap(short = 'l', long)]
+        limit: Option<usize>,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Move videos from a playlist to another
+    Move {
+        /// ID of the playlist to move videos from
+        #[clap(value_name = "SOURCE_ID", add = ArgValueCompleter::new(complete_playlist))]
+        source: String,
+        /// ID of the playlist to move videos to
+        #[clap(value_name = "TARGET_ID", add = ArgValueCompleter::new(complete_playlist))]
+        target: String,
+        #[command(flatten)]
+        filter: filter::FilterArgs,
+        /// Move all videos when no filter is given
+        #[clap(long)]
+        all: bool,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Reorder the items of a playlist
+    Sort {
+        /// ID of the playlist to sort
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+        /// Key to sort the items by
+        #[clap(short = 'b', long, value_enum)]
+        by: sort::SortKey,
+        /// Sort in descending order
+        #[clap(short = 'r', long)]
+        reverse: bool,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Randomly reorder the items of a playlist
+    Shuffle {
+        /// ID of the playlist to shuffle
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+        /// Seed for the random order, to make a shuffle reproducible
+        #[clap(short = 's', long)]
+        seed: Option<u64>,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Remove duplicates, pruned items and placeholders from all configured playlists
+    Tidy {
+        /// Playlist ID to tidy (optional, tidies all if not specified)
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: Option<String>,
+        /// Maintenance steps to skip
+        #[clap(short = 's', long, value_enum, value_delimiter = ',')]
+        skip: Vec<tidy::TidyStep>,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Add videos to a playlist
+    AddVideo {
+        /// ID of the playlist to add the videos to
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+        /// URLs or IDs of the videos to add
+        #[clap(required = true, value_name = "VIDEO")]
+        videos: Vec<String>,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Remove videos from a playlist
+    RemoveVideo {
+        /// ID of the playlist to remove the videos from
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: String,
+        /// URLs or IDs of the videos to remove
+        #[clap(value_name = "VIDEO")]
+        videos: Vec<String>,
+        #[command(flatten)]
+        filter: filter::FilterArgs,
+        /// Remove "Deleted video" and "Private video" placeholders (restricted by --match if given)
+        #[clap(long)]
+        placeholders: bool,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Open a configured playlist in the browser
+    Open {
+        /// ID, alias or title of the configured playlist
+        #[clap(value_name = "PLAYLIST", add = ArgValueCompleter::new(complete_playlist))]
+        playlist: String,
+        /// Print the URL instead of opening it
+        #[clap(short = 'p', long)]
+        print: bool,
+    },
+    /// Convert a playlist from another service (or any sync source) into a YouTube playlist once
+    Convert {
+        /// What to convert: spotify:<id>, lastfm:<user>, listenbrainz:<user>[:<playlist>],
+        /// youtube:<playlist id> or any other sync source
+        #[clap(long, value_name = "SOURCE")]
+        from: String,
+        /// The YouTube playlist to add the videos to: youtube:<playlist id> or youtube:new
+        #[clap(long, value_name = "TARGET")]
+        to: String,
+        /// Title of the playlist created with youtube:new
+        #[clap(short = 't', long)]
+        title: Option<String>,
+        /// Privacy status of the playlist created with youtube:new
+        #[clap(short = 'p', long, default_value = "private", value_parser = ["private", "unlisted", "public"])]
+        privacy: String,
+        /// Maximum number of tracks searched on YouTube (each search costs 100 quota units)
+        #[clap(long, default_value_t = 100)]
+        max_lookups: usize,
+        /// Perform a dry run without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Download configured playlists into their local directories with yt-dlp
+    Download {
+        /// Playlist ID to download (optional, downloads all playlists with a download_dir)
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: Option<String>,
+        /// Perform a dry run without downloading or removing files
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Import local playlists and subscriptions exported from another app
+    Import {
+        /// The export file
+        file: std::path::PathBuf,
+        /// The app the file was exported from
+        #[clap(short = 'f', long, value_enum)]
+        from: import::ImportFormat,
+        /// Perform a dry run without changing the configuration
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+    },
+    /// Show statistics about the configured playlists
+    Stats {
+        /// Playlist ID to show statistics for (optional, shows all if not specified)
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_id: Option<String>,
+    },
+    /// Keep running and sync each playlist on its schedule
+    Daemon {
+        /// Minutes between two syncs of the playlists without a `schedule` or `interval`
+        #[clap(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..))]
+        interval: u32,
+        /// Perform dry runs without making changes
+        #[clap(short = 'd', long)]
+        dry_run: bool,
+        /// Public URL of this machine's WebSub listener. Playlists syncing from channels
+        /// are then synced as soon as YouTube announces a new upload.
+        #[clap(long, value_name = "URL")]
+        websub_url: Option<String>,
+        /// Port of the WebSub listener
+        #[clap(long, default_value_t = 8585, requires = "websub_url")]
+        websub_port: u16,
+        /// Address to serve Prometheus metrics on, at /metrics
+        #[clap(long, value_name = "ADDRESS")]
+        metrics_bind: Option<std::net::SocketAddr>,
+    },
+    /// Check the configuration, credentials and API access, exiting with an error if one fails
+    Healthcheck,
+    /// Serve an HTTP API to list playlists, start syncs and follow their progress
+    Serve {
+        /// Address to listen on
+        #[clap(long, default_value = "127.0.0.1:8686")]
+        bind: std::net::SocketAddr,
+        /// Token clients have to send as `Authorization: Bearer <token>`
+        #[clap(long, env = "PLAYSYNC_API_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
+    /// Run syncs in the background with a systemd user service, or launchd on macOS
+    InstallService {
+        /// Minutes between two syncs
+        #[clap(long, default_value_t = 1440, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "daemon")]
+        interval: u32,
+        /// Keep `playsync daemon` running instead of syncing on a timer
+        #[clap(long)]
+        daemon: bool,
+        /// Write and enable the service instead of only showing it
+        #[clap(long)]
+        install: bool,
+    },
+    /// List the WebAssembly plugins of the plugins directory and what they provide
+    Plugins,
+    /// Update playsync to the latest release on GitHub, checking the download first
+    SelfUpdate {
+        /// Only tell whether a newer release is available
+        #[clap(long)]
+        check: bool,
+
+        /// Update even though this build can't check the signature of the release, only
+        /// its checksum
+        #[clap(long)]
+        insecure: bool,
+    },
+    /// Print the shell code completing playsync commands, including configured playlists
+    Completions {
+        /// The shell to complete in
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: Option<ConfigAction>,
+
+    /// Add a new playlist to the configuration
+    #[clap(short = 'a', long, alias = "add-playlist", value_name = "PLAYLIST_ID")]
+    pub add: Option<String>,
+
+    /// Pick playlists of your channel to add, from a list
+    #[clap(long)]
+    pub add_mine: bool,
+
+    /// Short name to refer to the added playlist on the command line
+    #[clap(long, requires = "add")]
+    pub alias: Option<String>,
+
+    /// Groups to tag the added playlist with
+    #[clap(
+        long = "tag",
+        requires = "add",
+        value_delimiter = ',',
+        value_name = "GROUP"
+    )]
+    pub tags: Vec<String>,
+
+    /// Only list the playlists tagged with this group
+    #[clap(short = 'g', long, requires = "list")]
+    pub group: Option<String>,
+
+    /// Remove a playlist from the configuration (without an ID, pick them from a list)
+    #[clap(
+        short = 'r',
+        long,
+        alias = "remove-playlist",
+        value_name = "PLAYLIST_ID",
+        num_args = 0..=1,
+        add = ArgValueCompleter::new(complete_playlist)
+    )]
+    pub remove: Option<Option<String>>,
+
+    /// List all playlists in the configuration
+    #[clap(short = 'l', long, alias = "list-playlists")]
+    pub list: bool,
+
+    /// Order of the listed playlists
+    #[clap(long, value_enum, default_value = "config", requires = "list")]
+    pub sort: ListSort,
+
+    /// Read the item count and privacy status of the listed playlists from YouTube
+    #[clap(long, requires = "list")]
+    pub live: bool,
+
+    /// Reset the configuration to default values
+    #[clap(long)]
+    pub reset: bool,
+
+    /// Path to the OAuth2 JSON file for YouTube API authentication
+    #[clap(
+        short = 'o',
+        long,
+        alias = "oauth2-json",
+        value_name = "OAUTH2_JSON_PATH"
+    )]
+    pub oauth2_json: Option<String>,
+}
+
+/// Order of the playlists listed by `config --list`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ListSort {
+    /// The order of the configuration file
+    Config,
+    Title,
+    Alias,
+    /// Most sources first
+    Sources,
+    /// Most items first
+    Items,
+    /// Most recently synced first
+    LastSync,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Open the configuration file in your editor, saving it only if it's valid
+    Edit,
+
+    /// Add and remove sources in a full-screen view of the sync graph, checked for loops
+    EditGraph,
+
+    /// Check the configuration for mistakes
+    Validate {
+        /// Skip the checks needing the YouTube API (playlists that can't be read)
+        #[clap(long)]
+        offline: bool,
+    },
+
+    /// Encrypt the API keys and tokens stored in the configuration with a passphrase
+    Encrypt,
+
+    /// Store the API keys and tokens of the configuration in plain text again
+    Decrypt,
+
+    /// Print a setting, e.g. `musicbrainz` or `playlists.chill.max_adds`
+    Get {
+        /// Dotted path of the setting; playlists are given by ID, alias, title or index
+        key: String,
+    },
+
+    /// Change a setting, e.g. `playsync config set playlists.chill.max_adds 20`
+    Set {
+        /// Dotted path of the setting; playlists are given by ID, alias, title or index
+        key: String,
+        /// New value, as a TOML value (`20`, `true`, `["a", "b"]`) or plain text
+        value: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    /// Without an action, list the cached playlists with the age of their items
+    #[command(subcommand)]
+    pub action: Option<CacheAction>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Download playlists again, ignoring their cached copy (all the cached playlists
+    /// if none is given)
+    Refresh {
+        /// Playlist IDs to refresh, repeatable
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_ids: Vec<String>,
+    },
+
+    /// Drop playlists from the cache (all of them if none is given)
+    Clear {
+        /// Playlist IDs to drop, repeatable
+        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID", add = ArgValueCompleter::new(complete_playlist))]
+        playlist_ids: Vec<String>,
+        /// Only drop the playlists not checked for this many days
+        #[clap(long, value_name = "DAYS", conflicts_with = "playlist_ids")]
+        older_than: Option<i64>,
+    },
+}
+
+/// Complete a configured playlist on the command line, by alias or by ID, with its
+/// title as help. The configuration given with `PLAYSYNC_CONFIG` or `PLAYSYNC_PROFILE`
+/// is used, since the command line isn't parsed while completing.
+pub fn complete_playlist(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
+    if let Some(path) = std::env::var_os("PLAYSYNC_CONFIG") {
+        let _ = config::set_config_path(path.into());
+    } else if let Ok(profile) = std::env::var("PLAYSYNC_PROFILE") {
+        let _ = config::set_profile(profile);
+    }
+
+    // Reading a missing configuration would create it
+    let cfg = match config::config_path() {
+        Ok(path) if path.exists() => config::Config::read().unwrap_or_default(),
+        _ => return Vec::new(),
+    };
+    let current = current.to_string_lossy();
+
+    let mut candidates = Vec::new();
+    for playlist in &cfg.playlists {
+        for value in playlist.alias.iter().chain([&playlist.id]) {
+            if value.starts_with(current.as_ref()) {
+                candidates.push(
+                    CompletionCandidate::new(value).help(Some(playlist.title.clone().into())),
+                );
+            }
+        }
+    }
+    candidates
+}
+
+/// Replace the playlist aliases and titles given on the command line by playlist IDs
+pub fn resolve_playlist_args(command: &mut Commands) -> Result<(), Box<dyn std::error::Error>> {
+    let playlist_ids: Vec<&mut String> = match command {
+        Commands::Sync {
+            playlist_ids,
+            exclude,
+            ..
+        } => playlist_ids.iter_mut().chain(exclude.iter_mut()).collect(),
+        Commands::Status { playlist_id }
+        | Commands::Tidy { playlist_id, .. }
+        | Commands::Download { playlist_id, .. }
+        | Commands::Stats { playlist_id } => playlist_id.iter_mut().collect(),
+        Commands::Split { playlist_id, .. }
+        | Commands::Explain { playlist_id, .. }
+        | Commands::Sort { playlist_id, .. }
+        | Commands::Shuffle { playlist_id, .. }
+        | Commands::AddVideo { playlist_id, .. }
+        | Commands::RemoveVideo { playlist_id, .. } => vec![playlist_id],
+        Commands::Copy { source, target, .. } | Commands::Move { source, target, .. } => {
+            vec![source, target]
+        }
+        Commands::Merge { sources, .. } => sources.iter_mut().collect(),
+        Commands::Cache(CacheArgs {
+            action:
+                Some(CacheAction::Refresh { playlist_ids } | CacheAction::Clear { playlist_ids, .. }),
+        }) => playlist_ids.iter_mut().collect(),
+        _ => Vec::new(),
+    };
+
+    if playlist_ids.is_empty() {
+        return Ok(());
+    }
+
+    let cfg = config::Config::read().unwrap_or_default();
+    for playlist_id in playlist_ids {
+        *playlist_id = cfg.resolve_playlist(playlist_id).inspect_err(|e| {
+            let _ = outro(format!("❌ {}", e));
+        })?;
+    }
+
+    Ok(())
+}
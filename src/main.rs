@@ -1,219 +1,2399 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser};
 use cliclack::{confirm, intro, note, outro};
 
-mod config;
-mod sync;
-mod youtube;
+mod cli;
+mod serve;
+mod service;
+mod term;
+mod tui;
+mod update;
 
-use youtube::YouTubeClient;
+use playsync_core::i18n::{tr, tr_args};
+use playsync_core::run::Outcome;
+use playsync_core::youtube::YouTubeClient;
+use playsync_core::{
+    cache, config, diff, download, edit, exit, filter, graph, history, http, i18n, import, metrics,
+    output, plan, plugin, progress, ratelimit, run, schedule, secret, sort, source, split, stats,
+    status, sync, tidy, ui, validate, youtube,
+};
 
-#[derive(Parser, Debug)]
-struct Cli {
-    /// The command to execute
-    #[command(subcommand)]
-    command: Commands,
-}
+use cli::{Cli, Commands};
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    let code = match run().await {
+        Ok(()) => exit::success(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            exit::code_for(e.as_ref()) as u8
+        }
+    };
 
-#[derive(Subcommand, Debug)]
-enum Commands {
-    /// Manage playlist configuration
-    Config(config::ConfigArgs),
-    /// Sync playlists based on configuration
-    Sync {
-        /// Playlist ID to sync (optional, syncs all if not specified)
-        #[clap(short = 'i', long = "id", value_name = "PLAYLIST_ID")]
-        playlist_id: Option<String>,
-        /// Perform a dry run without making changes
-        #[clap(short = 'd', long)]
-        dry_run: bool,
-    },
+    std::process::ExitCode::from(code)
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Answers the shell code printed by `playsync completions` and exits, before
+    // anything else is printed
+    clap_complete::CompleteEnv::with_factory(Cli::command).complete();
+
     rustls::crypto::ring::default_provider()
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
-    let cli = Cli::parse();
+    // clap ends with 2 on usage errors, which means partial failures here
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            std::process::exit(exit::Code::Usage as i32);
+        }
+        e.exit()
+    });
+
+    if let Some(path) = cli.config.clone() {
+        config::set_config_path(path)?;
+    }
+    if let Some(profile) = cli.profile.clone() {
+        config::set_profile(profile)?;
+    }
+    output::set_format(cli.output);
+    output::set_verbose(cli.verbose);
+    output::set_assume_yes(cli.yes);
+    term::set_style(cli.quiet, !cli.no_color);
+    ui::set(term::Terminal);
+    i18n::set_language(config::language().as_deref());
+
+    let mut cli = cli;
+    cli::resolve_playlist_args(&mut cli.command)?;
 
     let mut youtube_client = None;
 
-    if matches!(cli.command, Commands::Sync { .. })
-        || matches!(
-            cli.command,
-            Commands::Config(config::ConfigArgs { add: _, .. })
-        )
-    {
+    if matches!(cli.command, Commands::Sync { offline: true, .. }) {
+        youtube_client = Some(YouTubeClient::offline()?);
+    } else if matches!(
+        cli.command,
+        Commands::Sync { .. }
+            | Commands::Status { .. }
+            | Commands::Explain { .. }
+            | Commands::Tui
+            | Commands::Search { .. }
+            | Commands::Merge { .. }
+            | Commands::Split { .. }
+            | Commands::Copy { .. }
+            | Commands::Move { .. }
+            | Commands::Sort { .. }
+            | Commands::Shuffle { .. }
+            | Commands::Tidy { .. }
+            | Commands::AddVideo { .. }
+            | Commands::RemoveVideo { .. }
+            | Commands::Stats { .. }
+            | Commands::Download { .. }
+            | Commands::Convert { .. }
+            | Commands::Daemon { .. }
+            | Commands::Serve { .. }
+    ) || matches!(
+        cli.command,
+        Commands::Config(cli::ConfigArgs { add: Some(_), .. })
+            | Commands::Config(cli::ConfigArgs { add_mine: true, .. })
+            | Commands::Config(cli::ConfigArgs { live: true, .. })
+            | Commands::Cache(cli::CacheArgs {
+                action: Some(cli::CacheAction::Refresh { .. })
+            })
+    ) {
         // Ensure the OAuth2 JSON path is set before proceeding with sync or config reset
         let cfg = config::Config::read().unwrap_or_default();
         if cfg.oauth2_json.is_none() {
-            outro("❌ The path to the OAuth2 JSON file is not set. Please set it before syncing.")?;
-            return Err("OAuth2 JSON path is not set".into());
+            outro(tr("missing-oauth2-json"))?;
+            return Err(exit::failure(
+                exit::Code::Config,
+                "OAuth2 JSON path is not set",
+            ));
         }
 
         let oauth2_json = cfg
             .oauth2_json
             .as_ref()
             .ok_or("OAuth2 JSON path is not set")?;
+        ratelimit::configure(&cfg.rate_limit);
 
-        youtube_client = Some(YouTubeClient::new(oauth2_json).await?);
+        let mut client = YouTubeClient::new(oauth2_json)
+            .await
+            .map_err(|e| exit::failure(exit::Code::Auth, e))?;
+        client.use_music_services(cfg.music_services());
+        // Read-only commands can show items fetched a few minutes ago, syncs only read
+        // the sources from the cache if their `cache_max_age` allows it
+        let default_max_age = cfg
+            .defaults
+            .cache_max_age
+            .as_deref()
+            .and_then(|value| schedule::parse_interval(value).ok());
+        if matches!(
+            cli.command,
+            Commands::Status { .. }
+                | Commands::Stats { .. }
+                | Commands::Explain { .. }
+                | Commands::Search { .. }
+        ) {
+            client.use_cached_items(
+                default_max_age.or(Some(cache::MAX_AGE)),
+                cfg.cache_max_ages(false),
+            );
+        } else if matches!(cli.command, Commands::Sync { .. } | Commands::Daemon { .. }) {
+            client.use_cached_items(default_max_age, cfg.cache_max_ages(true));
+        }
+        youtube_client = Some(client);
     }
 
     match cli.command {
+        Commands::Init => handle_init().await?,
         Commands::Config(args) => handle_config(args, youtube_client).await?,
         Commands::Sync {
+            playlist_ids,
+            exclude,
+            group,
+            pick,
+            dry_run,
+            offline: _,
+            apply,
+            plan,
+            respect_windows,
+        } => {
+            if respect_windows {
+                let cfg = config::Config::read()?;
+                let windows = schedule::parse_windows(&cfg.sync_windows)?;
+                let now = chrono::Local::now();
+                if !schedule::in_windows(&windows, now) {
+                    outro(match schedule::next_window(&windows, now) {
+                        Some(opens) => tr_args(
+                            "outside-sync-windows-until",
+                            &[("time", opens.format("%H:%M").to_string().into())],
+                        ),
+                        None => tr("outside-sync-windows"),
+                    })?;
+                    return Ok(());
+                }
+            }
+            if let Some(Some(path)) = apply {
+                handle_apply(path, youtube_client.as_ref()).await?;
+                return Ok(());
+            }
+            let dry_run = dry_run
+                || plan.is_some()
+                || (apply.is_none()
+                    && config::Config::read().is_ok_and(|cfg| cfg.defaults.dry_run));
+            let selection = run::Selection {
+                playlist_ids,
+                exclude,
+                group,
+            };
+            handle_sync(selection, pick, dry_run, plan, youtube_client.as_ref()).await?
+        }
+        Commands::History(args) => handle_history(args)?,
+        Commands::Cache(args) => handle_cache(args, youtube_client).await?,
+        Commands::Status { playlist_id } => handle_status(playlist_id, youtube_client).await?,
+        Commands::Tui => handle_tui(youtube_client).await?,
+        Commands::Graph { format } => handle_graph(format)?,
+        Commands::Explain { video, playlist_id } => {
+            handle_explain(video, playlist_id, youtube_client).await?
+        }
+        Commands::Search { query } => handle_search(query, youtube_client).await?,
+        Commands::Merge {
+            sources,
+            title,
+            description,
+            privacy,
+            sort,
+            dry_run,
+        } => {
+            handle_merge(
+                sources,
+                title,
+                description,
+                privacy,
+                sort,
+                dry_run,
+                youtube_client,
+            )
+            .await?
+        }
+        Commands::Split {
+            playlist_id,
+            by,
+            size,
+            privacy,
+            keep,
+            dry_run,
+        } => {
+            handle_split(
+                playlist_id,
+                by,
+                size,
+                privacy,
+                keep,
+                dry_run,
+                youtube_client,
+            )
+            .await?
+        }
+        Commands::Copy {
+            source,
+            target,
+            limit,
+            dry_run,
+        } => handle_copy(source, target, limit, dry_run, youtube_client).await?,
+        Commands::Move {
+            source,
+            target,
+            filter,
+            all,
+            dry_run,
+        } => handle_move(source, target, filter, all, dry_run, youtube_client).await?,
+        Commands::Sort {
+            playlist_id,
+            by,
+            reverse,
+            dry_run,
+        } => handle_sort(playlist_id, by, reverse, dry_run, youtube_client).await?,
+        Commands::Shuffle {
             playlist_id,
+            seed,
             dry_run,
-        } => handle_sync(playlist_id, dry_run, youtube_client).await?,
+        } => handle_shuffle(playlist_id, seed, dry_run, youtube_client).await?,
+        Commands::Tidy {
+            playlist_id,
+            skip,
+            dry_run,
+        } => handle_tidy(playlist_id, skip, dry_run, youtube_client).await?,
+        Commands::AddVideo {
+            playlist_id,
+            videos,
+            dry_run,
+        } => handle_add_video(playlist_id, videos, dry_run, youtube_client).await?,
+        Commands::RemoveVideo {
+            playlist_id,
+            videos,
+            filter,
+            placeholders,
+            dry_run,
+        } => {
+            handle_remove_video(
+                playlist_id,
+                videos,
+                filter,
+                placeholders,
+                dry_run,
+                youtube_client,
+            )
+            .await?
+        }
+        Commands::Open { playlist, print } => handle_open(playlist, print)?,
+        Commands::Convert {
+            from,
+            to,
+            title,
+            privacy,
+            max_lookups,
+            dry_run,
+        } => {
+            handle_convert(
+                from,
+                to,
+                title,
+                privacy,
+                max_lookups,
+                dry_run,
+                youtube_client,
+            )
+            .await?
+        }
+        Commands::Download {
+            playlist_id,
+            dry_run,
+        } => handle_download(playlist_id, dry_run, youtube_client).await?,
+        Commands::Import {
+            file,
+            from,
+            dry_run,
+        } => handle_import(file, from, dry_run)?,
+        Commands::Stats { playlist_id } => handle_stats(playlist_id, youtube_client).await?,
+        Commands::Daemon {
+            interval,
+            dry_run,
+            websub_url,
+            websub_port,
+            metrics_bind,
+        } => {
+            let options = run::DaemonOptions {
+                interval,
+                dry_run,
+                websub_url,
+                websub_port,
+                metrics_bind,
+            };
+            handle_daemon(options, youtube_client).await?
+        }
+        Commands::Healthcheck => handle_healthcheck().await?,
+        Commands::Serve { bind, token } => handle_serve(bind, token, youtube_client).await?,
+        Commands::InstallService {
+            interval,
+            daemon,
+            install,
+        } => handle_install_service(interval, daemon, install)?,
+        Commands::Plugins => handle_plugins()?,
+        Commands::SelfUpdate { check, insecure } => handle_self_update(check, insecure).await?,
+        Commands::Completions { shell } => handle_completions(shell)?,
+    }
+
+    Ok(())
+}
+
+/// First-run wizard: set the OAuth2 credentials, sign in, and pick the playlists to sync
+async fn handle_init() -> Result<(), Box<dyn std::error::Error>> {
+    output::ensure_interactive("The setup")?;
+    intro(tr("setup-intro"))?;
+
+    let lock = config::Config::lock()?;
+    let mut cfg = config::Config::read().unwrap_or_default();
+
+    if !cfg.playlists.is_empty() {
+        let proceed = confirm(tr_args(
+            "setup-add-more",
+            &[("count", cfg.playlists.len().into())],
+        ))
+        .interact()?;
+        if !proceed {
+            outro(tr("setup-cancelled"))?;
+            return Ok(());
+        }
+    }
+
+    note(
+        "YouTube API credentials",
+        "1. Open https://console.cloud.google.com/ and create a project\n\
+         2. Enable the YouTube Data API v3\n\
+         3. Create OAuth2 credentials for a Desktop application\n\
+         4. Download the credentials as a JSON file",
+    )?;
+
+    let oauth2_json: String = cliclack::input(tr("oauth2-json-prompt"))
+        .default_input(cfg.oauth2_json.as_deref().unwrap_or_default())
+        .validate(|path: &String| {
+            if std::path::Path::new(path).is_file() {
+                Ok(())
+            } else {
+                Err(tr("no-file-at-path"))
+            }
+        })
+        .interact()?;
+    cfg.set_oauth_path(Some(oauth2_json.clone()));
+    cfg.write(&lock)?;
+
+    let sp = cliclack::spinner();
+    sp.start("Signing in to YouTube (complete the sign-in in your browser)");
+    let client = match YouTubeClient::new(&oauth2_json).await {
+        Ok(client) => client,
+        Err(e) => {
+            sp.error("Sign-in failed");
+            outro(tr_args(
+                "sign-in-failed",
+                &[("error", e.to_string().into())],
+            ))?;
+            return Err(e);
+        }
+    };
+    let my_playlists = client.get_my_playlists().await?;
+    sp.stop(format!("Signed in, found {} playlists", my_playlists.len()));
+
+    let selected = pick_my_playlists(&cfg, &my_playlists)?;
+    if selected.is_empty() {
+        outro(tr("setup-completed-empty"))?;
+        return Ok(());
+    }
+
+    for playlist in selected {
+        let mut sources: Vec<(String, String, &str)> = my_playlists
+            .iter()
+            .filter(|p| p.id != playlist.id)
+            .map(|p| (p.id.clone(), p.title.clone(), ""))
+            .collect();
+        sources.push((
+            "liked".to_string(),
+            "Liked videos".to_string(),
+            "your likes",
+        ));
+
+        let sync_from: Vec<String> = cliclack::multiselect(tr_args(
+            "setup-sync-from",
+            &[("title", playlist.title.as_str().into())],
+        ))
+        .items(&sources)
+        .filter_mode()
+        .required(false)
+        .interact()?;
+
+        cfg.add_playlist(config::Playlist {
+            id: playlist.id,
+            title: playlist.title,
+            sync_from: (!sync_from.is_empty()).then_some(sync_from),
+            ..Default::default()
+        });
     }
 
+    cfg.write(&lock)?;
+
+    outro(tr_args(
+        "setup-completed",
+        &[("count", cfg.playlists.len().into())],
+    ))?;
     Ok(())
 }
 
+/// Let the user pick playlists of their channel that are not configured yet
+fn pick_my_playlists(
+    cfg: &config::Config,
+    my_playlists: &[youtube::PlaylistSummary],
+) -> Result<Vec<youtube::PlaylistSummary>, Box<dyn std::error::Error>> {
+    let available: Vec<(String, String, String)> = my_playlists
+        .iter()
+        .filter(|p| cfg.playlists.iter().all(|configured| configured.id != p.id))
+        .map(|p| {
+            (
+                p.id.clone(),
+                p.title.clone(),
+                format!("{} videos", p.item_count),
+            )
+        })
+        .collect();
+
+    if available.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let selected: Vec<String> = cliclack::multiselect(tr("setup-pick-playlists"))
+        .items(&available)
+        .filter_mode()
+        .required(false)
+        .interact()?;
+
+    Ok(my_playlists
+        .iter()
+        .filter(|p| selected.contains(&p.id))
+        .cloned()
+        .collect())
+}
+
+/// Ask the user to select playlists to sync from/to.
+///
+/// This function will present a list of playlists that are not the current playlist
+/// and that do not already have a sync relationship with the current playlist.
+/// It will return a vector of playlist IDs that the user has selected.
+fn ask_for_sync_items(playlist_id: String) -> Vec<String> {
+    use cliclack::multiselect;
+
+    let cfg = config::Config::read().unwrap_or_default();
+    let playlists = cfg
+        .playlists
+        .iter()
+        .filter(|p| {
+            // Skip the current playlist
+            if p.id == playlist_id {
+                return false;
+            }
+
+            // Skip playlists that are already set to sync from the current playlist
+            // This is to prevent circular dependencies
+            if let Some(sync_from) = &p.sync_from {
+                return !sync_from.contains(&playlist_id);
+            }
+
+            true
+        })
+        .collect::<Vec<&config::Playlist>>();
+
+    // Without asking, the playlist is only managed
+    if playlists.is_empty() || output::assume_yes() {
+        return Vec::new();
+    }
+
+    let mut items: Vec<(String, String, &str)> = playlists
+        .iter()
+        .map(|p| (p.id.clone(), p.title.clone(), ""))
+        .collect();
+    items.push((
+        "liked".to_string(),
+        "Liked videos".to_string(),
+        "your likes",
+    ));
+
+    let selected = multiselect(tr("pick-sources"))
+        .items(&items)
+        .filter_mode()
+        .required(false)
+        .interact()
+        .unwrap_or_default();
+
+    selected
+}
+
 async fn handle_config(
-    args: config::ConfigArgs,
+    args: cli::ConfigArgs,
     youtube_client: Option<YouTubeClient>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    intro("📝 Playlist Configuration")?;
+    intro(tr("config-intro"))?;
+
+    match args.action {
+        Some(cli::ConfigAction::Edit) => return edit_config(),
+        Some(cli::ConfigAction::EditGraph) => {
+            output::ensure_interactive("Editing the sync graph")?;
+            let saved = tui::edit_graph(config::Config::read()?)?;
+            outro(if saved {
+                tr("graph-saved")
+            } else {
+                tr("config-unchanged")
+            })?;
+            return Ok(());
+        }
+        Some(cli::ConfigAction::Validate { offline }) => return validate_config(offline).await,
+        Some(cli::ConfigAction::Encrypt) => return encrypt_secrets(true),
+        Some(cli::ConfigAction::Decrypt) => return encrypt_secrets(false),
+        Some(cli::ConfigAction::Get { key }) => {
+            let cfg = config::Config::read()?;
+            let value = cfg.get_value(&key).inspect_err(|e| {
+                let _ = outro(format!("❌ {}", e));
+            })?;
+
+            match value {
+                toml::Value::String(text) => println!("{}", text),
+                toml::Value::Table(table) => print!("{}", toml::to_string_pretty(&table)?),
+                other => println!("{}", other),
+            }
+            return Ok(());
+        }
+        Some(cli::ConfigAction::Set { key, value }) => {
+            let lock = config::Config::lock()?;
+            let mut cfg = config::Config::read()?;
+            cfg.set_value(&key, &value).inspect_err(|e| {
+                let _ = outro(format!("❌ {}", e));
+            })?;
+            cfg.write(&lock)?;
+
+            outro(tr_args("setting-set", &[("key", key.as_str().into())]))?;
+            return Ok(());
+        }
+        None => {}
+    }
 
+    let lock = config::Config::lock()?;
     let mut cfg = config::Config::read().unwrap_or_default();
 
     if args.reset {
-        let confirmed = confirm("Are you sure you want to reset the configuration?").interact()?;
+        let confirmed = output::confirm(tr("reset-confirm"), false)?;
 
         if confirmed {
             cfg = config::Config::default();
-            cfg.write()?;
-            outro("✅ Configuration reset successfully")?;
+            cfg.write(&lock)?;
+            outro(tr("config-reset"))?;
         }
         return Ok(());
     }
 
     if !args.oauth2_json.is_none() {
         cfg.set_oauth_path(args.oauth2_json.clone());
-        cfg.write()?;
-        outro("✅ OAuth2 JSON path set successfully")?;
+        cfg.write(&lock)?;
+        outro(tr("oauth2-json-set"))?;
     }
 
-    if !args.add.is_empty() {
-        let client = youtube_client.ok_or_else(|| {
-            let _ = outro("❌ YouTube client is not initialized.");
+    if let Some(add) = args.add {
+        let client = youtube_client.as_ref().ok_or_else(|| {
+            let _ = outro(tr("client-not-initialized"));
             "YouTube client is not initialized"
         })?;
 
-        match client.get_playlist_title(&args.add).await {
+        let placeholder = config::Playlist {
+            id: add.clone(),
+            ..Default::default()
+        };
+        let title = match placeholder.placeholder_title() {
+            Some(title) => Ok(title.to_string()),
+            None => client.get_playlist_title(&add).await,
+        };
+
+        match title {
             Ok(playlist_title) => {
                 let sync_from = if cfg.playlists.len() > 0 {
-                    config::ask_for_sync_items(args.add.clone())
+                    ask_for_sync_items(add.clone())
                 } else {
                     Vec::new()
                 };
 
                 let playlist = config::Playlist {
-                    id: args.add.clone(),
+                    id: add,
                     title: playlist_title,
+                    alias: args.alias,
+                    tags: args.tags,
                     sync_from: if sync_from.is_empty() {
                         None
                     } else {
                         Some(sync_from)
                     },
+                    ..Default::default()
                 };
 
                 cfg.add_playlist(playlist);
-                cfg.write()?;
-                outro("✅ Playlist added successfully")?;
+                cfg.write(&lock)?;
+                outro(tr("playlist-added"))?;
             }
             Err(e) => {
-                outro(&format!("❌ Failed to fetch playlist info: {}", e))?;
+                outro(tr_args(
+                    "playlist-fetch-failed",
+                    &[("error", e.to_string().into())],
+                ))?;
                 return Err(e);
             }
         }
     }
 
-    if !args.remove.is_empty() {
-        cfg.remove_playlist(&args.remove);
-        cfg.write()?;
-        outro("✅ Playlist removed successfully")?;
-    }
+    if args.add_mine {
+        let client = youtube_client.as_ref().ok_or_else(|| {
+            let _ = outro(tr("client-not-initialized"));
+            "YouTube client is not initialized"
+        })?;
 
-    if args.list {
-        if let Some(oauth2_json) = &cfg.oauth2_json {
-            note("OAuth2 JSON path", oauth2_json)?;
+        let sp = cliclack::spinner();
+        sp.start("Fetching your playlists");
+        let my_playlists = client.get_my_playlists().await?;
+        sp.stop(format!("Found {} playlists", my_playlists.len()));
+
+        let selected = pick_my_playlists(&cfg, &my_playlists)?;
+        if selected.is_empty() {
+            outro(tr("no-playlists-added"))?;
         } else {
-            note("OAuth2 JSON path", "<not set>")?;
+            let count = selected.len();
+            for playlist in selected {
+                cfg.add_playlist(config::Playlist {
+                    id: playlist.id,
+                    title: playlist.title,
+                    ..Default::default()
+                });
+            }
+            cfg.write(&lock)?;
+            outro(tr_args("playlists-added", &[("count", count.into())]))?;
         }
+    }
+
+    match args.remove {
+        Some(Some(remove)) => {
+            let id = cfg
+                .find_playlist(&remove)
+                .map(|p| p.id.clone())
+                .unwrap_or(remove);
+            remove_playlists(&mut cfg, &[id], &lock)?;
+            outro(tr("playlist-removed"))?;
+        }
+        Some(None) => {
+            if cfg.playlists.is_empty() {
+                outro(tr("no-playlists-configured-yet"))?;
+                return Ok(());
+            }
 
-        intro("📜 Listing all playlists:")?;
+            let items: Vec<(String, String, String)> = cfg
+                .playlists
+                .iter()
+                .map(|p| (p.id.clone(), p.title.clone(), p.id.clone()))
+                .collect();
+            output::ensure_interactive("Choosing the playlists to remove")?;
+            let ids: Vec<String> = cliclack::multiselect(tr("pick-playlists-to-remove"))
+                .items(&items)
+                .filter_mode()
+                .required(false)
+                .interact()?;
+
+            if ids.is_empty() {
+                outro(tr("no-playlists-removed"))?;
+            } else {
+                remove_playlists(&mut cfg, &ids, &lock)?;
+                outro(tr_args("playlists-removed", &[("count", ids.len().into())]))?;
+            }
+        }
+        None => {}
+    }
 
-        for playlist in &cfg.playlists {
-            let playlist_msg = format!("{} (ID: {})", playlist.title, playlist.id);
+    if args.list {
+        let states = match youtube_client.as_ref().filter(|_| args.live) {
+            Some(client) => {
+                let ids: Vec<String> = cfg.playlists.iter().map(|p| p.id.clone()).collect();
+                let sp = cliclack::spinner();
+                sp.start("Reading the playlists from YouTube");
+                let states = status::playlist_states(&cfg, client, &ids).await;
+                sp.clear();
+                Some(states?)
+            }
+            None => None,
+        };
+        list_playlists(&cfg, args.group.as_deref(), args.sort, states.as_ref())?;
+    }
 
-            if playlist.sync_from.is_some() {
-                let mut sync_sources_msg = String::new();
+    Ok(())
+}
 
-                for sync_id in playlist.sync_from.as_ref().unwrap() {
-                    if let Some(sync_playlist) = &cfg.playlists.iter().find(|p| p.id == *sync_id) {
-                        sync_sources_msg.push_str(&format!(
-                            "{} (ID: {})\n",
-                            sync_playlist.title, sync_playlist.id
-                        ));
+/// List the configured playlists as a table, or in JSON or YAML, with their sources
+/// and the item count and result of their last sync from the history. With the live
+/// states of the playlists, their item count and privacy status are shown instead,
+/// and playlists that can't be read or have fewer items than at the last run stand out.
+fn list_playlists(
+    cfg: &config::Config,
+    group: Option<&str>,
+    sort: cli::ListSort,
+    states: Option<&status::PlaylistStates>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let entries = history::read_all()?;
+    let mut listing: Vec<output::PlaylistListing> = cfg
+        .playlists
+        .iter()
+        .filter(|p| group.is_none_or(|g| p.has_tag(g)))
+        .map(|playlist| {
+            let last_sync = history::last_sync(&entries, &playlist.id);
+            output::PlaylistListing {
+                id: playlist.id.clone(),
+                title: playlist.title.clone(),
+                alias: playlist.alias.clone(),
+                tags: playlist.tags.clone(),
+                account: playlist.account.clone(),
+                sync_from: playlist
+                    .sync_from
+                    .iter()
+                    .flatten()
+                    .map(|entry| output::SourceListing {
+                        entry: entry.clone(),
+                        description: match cfg.playlists.iter().find(|p| p.id == *entry) {
+                            Some(source) => source.title.clone(),
+                            None => source::Source::parse(entry).describe(),
+                        },
+                    })
+                    .collect(),
+                item_count: match states {
+                    Some(states) => states
+                        .get(&playlist.id)
+                        .map(|state| state.item_count as usize),
+                    None => history::last_item_count(&entries, &playlist.id),
+                },
+                privacy: states.map(|states| status::privacy_label(states.get(&playlist.id))),
+                last_sync: last_sync.map(|(entry, _)| entry.timestamp),
+                last_result: last_sync.map(|(entry, record)| {
+                    if entry.error.is_some() {
+                        "aborted".to_string()
+                    } else if record.failed > 0 {
+                        "failed".to_string()
                     } else {
-                        sync_sources_msg.push_str(&format!("Unknown Playlist ID: {}\n", sync_id));
+                        "ok".to_string()
                     }
-                }
+                }),
+            }
+        })
+        .collect();
 
-                note(playlist_msg, &sync_sources_msg)?;
-            } else {
-                note(playlist_msg, "No sync sources")?;
+    // Stable, so ties keep the order of the configuration
+    match sort {
+        cli::ListSort::Config => {}
+        cli::ListSort::Title => listing.sort_by_key(|p| p.title.to_lowercase()),
+        cli::ListSort::Alias => listing.sort_by_key(|p| (p.alias.is_none(), p.alias.clone())),
+        cli::ListSort::Sources => listing.sort_by_key(|p| std::cmp::Reverse(p.sync_from.len())),
+        cli::ListSort::Items => listing.sort_by_key(|p| std::cmp::Reverse(p.item_count)),
+        cli::ListSort::LastSync => listing.sort_by_key(|p| std::cmp::Reverse(p.last_sync)),
+    }
+
+    if output::is_structured() {
+        return output::print(&listing);
+    }
+
+    note(
+        "OAuth2 JSON path",
+        cfg.oauth2_json.as_deref().unwrap_or("<not set>"),
+    )?;
+    intro(tr("list-intro"))?;
+
+    if listing.is_empty() {
+        outro(tr("no-playlists-to-list"))?;
+        return Ok(());
+    }
+
+    // Fewer items than at the last run, by playlist ID
+    let shrunk: std::collections::HashMap<&str, usize> = listing
+        .iter()
+        .filter(|_| states.is_some())
+        .filter_map(|playlist| {
+            let previous = history::last_item_count(&entries, &playlist.id)?;
+            let fewer = previous.checked_sub(playlist.item_count?)?;
+            (fewer > 0).then_some((playlist.id.as_str(), fewer))
+        })
+        .collect();
+
+    let mut rows: Vec<Vec<String>> = listing
+        .iter()
+        .map(|playlist| {
+            vec![
+                playlist.alias.clone().unwrap_or_default(),
+                console::truncate_str(&playlist.title, 40, "…").into_owned(),
+                playlist.id.clone(),
+                match &playlist.account {
+                    Some(account) => format!("YouTube ({})", account),
+                    None => "YouTube".to_string(),
+                },
+                playlist.sync_from.len().to_string(),
+                match (playlist.item_count, shrunk.get(playlist.id.as_str())) {
+                    (Some(count), Some(fewer)) => format!("{} (-{})", count, fewer),
+                    (Some(count), None) => count.to_string(),
+                    (None, _) => String::new(),
+                },
+                playlist
+                    .last_sync
+                    .map(|time| {
+                        time.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "never".to_string()),
+                playlist.last_result.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let mut header = vec![
+        "Alias",
+        "Title",
+        "ID",
+        "Account",
+        "Sources",
+        "Items",
+        "Last sync",
+        "Result",
+    ];
+    if states.is_some() {
+        header.push("Privacy");
+        for (row, playlist) in rows.iter_mut().zip(&listing) {
+            row.push(playlist.privacy.clone().unwrap_or_default());
+        }
+    }
+    note(
+        format!("{} playlists", listing.len()),
+        term::table(&header, &rows),
+    )?;
+
+    for playlist in &listing {
+        if playlist.privacy.as_deref() == Some("unavailable") {
+            cliclack::log::warning(format!(
+                "{} can't be read: it was deleted, or is private to another account",
+                playlist.title
+            ))?;
+        }
+        if let Some(fewer) = shrunk.get(playlist.id.as_str()) {
+            cliclack::log::warning(format!(
+                "{} has {} fewer items than at the last run",
+                playlist.title, fewer
+            ))?;
+        }
+    }
+
+    // The sources, which don't fit in the table
+    if output::is_verbose() {
+        for playlist in listing.iter().filter(|p| !p.sync_from.is_empty()) {
+            let sources: Vec<String> = playlist
+                .sync_from
+                .iter()
+                .map(|source| source.description.clone())
+                .collect();
+            note(&playlist.title, sources.join("\n"))?;
+        }
+    }
+
+    outro(tr("list-completed"))?;
+    Ok(())
+}
+
+/// Remove playlists from the configuration, and after confirmation from the sources
+/// of the other playlists, so they don't show up as unknown sources
+fn remove_playlists(
+    cfg: &mut config::Config,
+    ids: &[String],
+    lock: &config::ConfigLock,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for id in ids {
+        cfg.remove_playlist(id);
+    }
+
+    let referencing: Vec<&str> = cfg
+        .playlists
+        .iter()
+        .filter(|p| {
+            p.sync_from
+                .iter()
+                .flatten()
+                .any(|source| ids.contains(source))
+        })
+        .map(|p| p.title.as_str())
+        .collect();
+
+    if !referencing.is_empty() {
+        let clean = output::confirm(
+            tr_args(
+                "remove-from-sources",
+                &[
+                    ("count", referencing.len().into()),
+                    ("playlists", referencing.join(", ").into()),
+                ],
+            ),
+            true,
+        )?;
+
+        if clean {
+            for title in cfg.remove_sources(ids) {
+                cliclack::log::warning(format!(
+                    "'{}' has no sources left and won't receive new videos",
+                    title
+                ))?;
+            }
+        }
+    }
+
+    cfg.write(lock)?;
+
+    Ok(())
+}
+
+/// Check the configuration for mistakes and report them with how to fix them
+async fn validate_config(offline: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config::config_path()?;
+    let (cfg, mut problems) = validate::check_file(&path);
+
+    let has_errors = |problems: &[validate::Problem]| {
+        problems
+            .iter()
+            .any(|p| p.severity == validate::Severity::Error)
+    };
+
+    // Playlists are only checked on YouTube when the client can be created
+    if let Some(cfg) = cfg.filter(|_| !offline && !has_errors(&problems))
+        && let Some(oauth2_json) = &cfg.oauth2_json
+    {
+        let sp = cliclack::spinner();
+        sp.start("Checking playlists on YouTube");
+
+        match YouTubeClient::new(oauth2_json).await {
+            Ok(client) => {
+                problems.extend(validate::check_playlists(&client, &cfg).await);
+                sp.stop("Checked playlists on YouTube");
+            }
+            Err(e) => {
+                sp.stop("Could not check playlists on YouTube");
+                problems.push(validate::Problem {
+                        severity: validate::Severity::Error,
+                        message: format!(
+                            "Failed to authenticate with YouTube: {}. Check the OAuth2 JSON file, or delete token_cache.json to sign in again.",
+                            e
+                        ),
+                    });
+            }
+        }
+    }
+
+    for problem in &problems {
+        match problem.severity {
+            validate::Severity::Error => cliclack::log::error(&problem.message)?,
+            validate::Severity::Warning => cliclack::log::warning(&problem.message)?,
+        }
+    }
+
+    if has_errors(&problems) {
+        outro(tr_args(
+            "config-invalid",
+            &[
+                ("path", path.display().to_string().into()),
+                ("count", problems.len().into()),
+            ],
+        ))?;
+        return Err(exit::failure(
+            exit::Code::Config,
+            "The configuration is invalid",
+        ));
+    }
+
+    outro(if problems.is_empty() {
+        tr_args(
+            "config-valid",
+            &[("path", path.display().to_string().into())],
+        )
+    } else {
+        tr_args(
+            "config-valid-with-warnings",
+            &[
+                ("path", path.display().to_string().into()),
+                ("count", problems.len().into()),
+            ],
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Encrypt the API keys and tokens of the configuration with a passphrase, or store
+/// them in plain text again
+fn encrypt_secrets(encrypt: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let lock = config::Config::lock()?;
+    let mut cfg = config::Config::read()?;
+
+    if cfg.secrets_mut().iter().all(|(_, value)| value.is_none()) {
+        outro(tr("no-secrets-stored"))?;
+        return Ok(());
+    }
+
+    let passphrase = secret::passphrase(encrypt)?;
+    let mut changed = Vec::new();
+
+    for (name, value) in cfg.secrets_mut() {
+        let Some(current) = value.as_mut() else {
+            continue;
+        };
+
+        // Encrypted values are decrypted either way, so all secrets keep the same passphrase
+        if secret::is_encrypted(current) {
+            let plain = secret::decrypt(current, &passphrase)?;
+            if !encrypt {
+                *current = plain;
+                changed.push(name);
             }
+        } else if encrypt {
+            *current = secret::encrypt(current, &passphrase)?;
+            changed.push(name);
         }
+    }
 
-        outro("✅ Configuration listing completed")?;
+    if changed.is_empty() {
+        outro(if encrypt {
+            tr("secrets-already-encrypted")
+        } else {
+            tr("no-secrets-encrypted")
+        })?;
+        return Ok(());
     }
 
+    cfg.write(&lock)?;
+    outro(tr_args(
+        if encrypt {
+            "secrets-encrypted"
+        } else {
+            "secrets-decrypted"
+        },
+        &[("names", changed.join(", ").into())],
+    ))?;
+
     Ok(())
 }
 
+/// Edit the configuration file in the user's editor.
+///
+/// The editor works on a copy, which only replaces the configuration once it passes the
+/// checks of `config validate`, so a typo can't leave playsync with a broken
+/// configuration.
+fn edit_config() -> Result<(), Box<dyn std::error::Error>> {
+    let path = config::config_path()?;
+    let lock = config::Config::lock()?;
+    if !path.exists() {
+        config::config_dir()?;
+        config::Config::default().write(&lock)?;
+    }
+
+    let draft = path.with_extension("edit.toml");
+    std::fs::copy(&path, &draft)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let mut editor_args = editor.split_whitespace();
+    let program = editor_args.next().ok_or("The editor command is empty")?;
+
+    loop {
+        let status = std::process::Command::new(program)
+            .args(editor_args.clone())
+            .arg(&draft)
+            .status();
+
+        if !status.as_ref().is_ok_and(|s| s.success()) {
+            let _ = std::fs::remove_file(&draft);
+            outro(tr_args(
+                "editor-failed",
+                &[("editor", editor.as_str().into())],
+            ))?;
+            return match status {
+                Err(e) => Err(e.into()),
+                Ok(_) => Err("The editor exited with an error".into()),
+            };
+        }
+
+        // The same checks as `config validate`, without the YouTube API
+        let (_, problems) = validate::check_file(&draft);
+        for problem in &problems {
+            match problem.severity {
+                validate::Severity::Error => cliclack::log::error(&problem.message)?,
+                validate::Severity::Warning => cliclack::log::warning(&problem.message)?,
+            }
+        }
+        let has_errors = problems
+            .iter()
+            .any(|p| p.severity == validate::Severity::Error);
+
+        // Warnings, like misspelled fields, are only saved once confirmed
+        let save = !has_errors
+            && (problems.is_empty()
+                || output::assume_yes()
+                || confirm(tr("save-with-warnings")).interact()?);
+        if save {
+            // Saved as written, keeping the user's comments and layout
+            config::write_atomic(&path, &std::fs::read_to_string(&draft)?, &lock)?;
+            let _ = std::fs::remove_file(&draft);
+            outro(tr_args(
+                "config-saved",
+                &[("path", path.display().to_string().into())],
+            ))?;
+            return Ok(());
+        }
+
+        // Editing again would loop forever with a scripted editor
+        if output::assume_yes() || !confirm(tr("edit-again")).interact()? {
+            let _ = std::fs::remove_file(&draft);
+            outro(tr("changes-discarded"))?;
+            return Ok(());
+        }
+    }
+}
+
+/// Sync the selected playlists, after letting the user pick among them with `pick`
 async fn handle_sync(
-    playlist_id: Option<String>,
+    mut selection: run::Selection,
+    pick: bool,
     dry_run: bool,
-    youtube_client: Option<YouTubeClient>,
+    plan: Option<std::path::PathBuf>,
+    youtube_client: Option<&YouTubeClient>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     intro(if dry_run {
-        "🔍 Playlist Sync (Dry Run)"
+        tr("sync-intro-dry-run")
     } else {
-        "🔄 Playlist Sync"
+        tr("sync-intro")
     })?;
 
-    let cfg = config::Config::read()?;
+    let mut cfg = config::Config::read()?;
 
-    let playlists_to_sync = if let Some(id) = playlist_id {
-        cfg.playlists.into_iter().filter(|p| p.id == id).collect()
+    let selected: Vec<&config::Playlist> = cfg
+        .playlists
+        .iter()
+        .filter(|p| selection.matches(p))
+        .collect();
+
+    if selected.is_empty() {
+        outro(tr("no-playlists-to-sync"))?;
+        return Ok(());
+    }
+
+    if pick {
+        let items: Vec<(String, String, String)> = selected
+            .iter()
+            .map(|p| (p.id.clone(), p.title.clone(), p.id.clone()))
+            .collect();
+
+        output::ensure_interactive("Picking the playlists to sync")?;
+        let picked: Vec<String> = cliclack::multiselect(tr("pick-playlists-to-sync"))
+            .items(&items)
+            .filter_mode()
+            .required(false)
+            .interact()?;
+
+        if picked.is_empty() {
+            outro(tr("no-playlists-selected"))?;
+            return Ok(());
+        }
+        selection.playlist_ids = picked;
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    run::sync(&mut cfg, client, &selection, dry_run, plan.as_deref()).await?;
+
+    outro(if dry_run {
+        tr("dry-run-completed")
     } else {
-        cfg.playlists
-    };
+        tr("sync-completed")
+    })?;
+    Ok(())
+}
+
+/// Make the changes of a plan written by `sync --plan`, and nothing else
+async fn handle_apply(
+    path: std::path::PathBuf,
+    youtube_client: Option<&YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("apply-intro"))?;
 
-    if playlists_to_sync.is_empty() {
-        outro("❌ No playlists found to sync")?;
+    let plan = plan::Plan::read(&path)?;
+    if plan.playlists.is_empty() {
+        outro(tr("plan-empty"))?;
         return Ok(());
     }
 
+    let cfg = config::Config::read()?;
     let client = youtube_client.ok_or_else(|| {
-        let _ = outro("❌ YouTube client is not initialized.");
+        let _ = outro(tr("client-not-initialized"));
         "YouTube client is not initialized"
     })?;
 
-    for playlist in playlists_to_sync {
-        if let Some(sync_from) = &playlist.sync_from {
-            sync::sync_playlist(&client, &playlist, sync_from, dry_run).await?;
+    if let Err(e) = run::apply(&cfg, client, &plan).await {
+        if e.is::<run::PlanRefused>() {
+            outro(tr("plan-refused"))?;
         }
+        return Err(e);
     }
 
-    outro(if dry_run {
-        "✅ Dry run completed"
-    } else {
-        "✅ Sync completed"
+    outro(tr("plan-applied"))?;
+    Ok(())
+}
+
+/// Sync playlists as they come due, until interrupted
+async fn handle_daemon(
+    options: run::DaemonOptions,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("daemon-intro"))?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
     })?;
+
+    run::daemon(&client, &options, async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await?;
+
+    outro(tr("daemon-stopped"))?;
+    Ok(())
+}
+
+/// Check that playsync can sync, for Docker `HEALTHCHECK` and monitoring.
+///
+/// Never asks anything: a missing sign-in or a hanging request is a failure.
+async fn handle_healthcheck() -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("health-intro"))?;
+
+    // Exits with the code of the failure, so monitoring can tell them apart
+    let fail = |code: exit::Code, message: String| -> Result<(), Box<dyn std::error::Error>> {
+        cliclack::log::error(&message)?;
+        outro(tr("unhealthy"))?;
+        Err(exit::failure(code, message))
+    };
+
+    let path = config::config_path()?;
+    let (cfg, problems) = validate::check_file(&path);
+    let errors: Vec<&validate::Problem> = problems
+        .iter()
+        .filter(|p| p.severity == validate::Severity::Error)
+        .collect();
+    let Some(cfg) = cfg.filter(|_| errors.is_empty()) else {
+        for problem in errors {
+            cliclack::log::error(&problem.message)?;
+        }
+        return fail(exit::Code::Config, format!("{} is invalid", path.display()));
+    };
+    cliclack::log::success("Configuration is valid")?;
+
+    if !YouTubeClient::is_signed_in() {
+        return fail(
+            exit::Code::Auth,
+            "Not signed in to YouTube. Run `playsync sync` once interactively to sign in."
+                .to_string(),
+        );
+    }
+    let oauth2_json = cfg.oauth2_json.as_deref().unwrap_or_default();
+
+    // A revoked token would make the client wait for a browser sign-in
+    let check = async {
+        let client = YouTubeClient::new(oauth2_json).await?;
+        match cfg
+            .playlists
+            .iter()
+            .find(|p| p.placeholder_title().is_none())
+        {
+            Some(playlist) => client.get_playlist_title(&playlist.id).await.map(|_| ()),
+            None => client.get_my_playlists().await.map(|_| ()),
+        }
+    };
+    match tokio::time::timeout(std::time::Duration::from_secs(60), check).await {
+        Ok(Ok(())) => cliclack::log::success("YouTube API is reachable")?,
+        Ok(Err(e)) => {
+            return fail(
+                exit::code_for(e.as_ref()),
+                format!("YouTube API request failed: {}", e),
+            );
+        }
+        Err(_) => {
+            return fail(
+                exit::Code::Network,
+                "YouTube API did not answer within 60 seconds".to_string(),
+            );
+        }
+    }
+
+    outro(tr("healthy"))?;
+    Ok(())
+}
+
+/// Serve the HTTP API until interrupted
+async fn handle_serve(
+    bind: std::net::SocketAddr,
+    token: Option<String>,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("serve-intro"))?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    if token.is_none() && !bind.ip().is_loopback() {
+        cliclack::log::warning(
+            "The API is reachable from other machines without a token. Set one with --token.",
+        )?;
+    }
+    cliclack::log::info(format!("Listening on http://{}", bind))?;
+
+    serve::run(bind, token, client).await?;
+
+    outro(tr("server-stopped"))?;
+    Ok(())
+}
+
+/// Print the sync graph to stdout, so it can be piped to Graphviz or pasted in Markdown
+fn handle_graph(format: graph::GraphFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = config::Config::read()?;
+    if let Some(cycle) = cfg.find_cycle() {
+        cliclack::log::warning(format!(
+            "Playlists sync from each other in a loop, shown in red: {}",
+            cycle.join(" → ")
+        ))?;
+    }
+
+    print!(
+        "{}",
+        match format {
+            graph::GraphFormat::Dot => graph::to_dot(&cfg),
+            graph::GraphFormat::Mermaid => graph::to_mermaid(&cfg),
+        }
+    );
+    Ok(())
+}
+
+async fn handle_self_update(check: bool, insecure: bool) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("self-update-intro"))?;
+
+    let sp = cliclack::spinner();
+    sp.start("Looking for the latest release");
+    let release = update::latest_release().await;
+    sp.clear();
+    let release = release.map_err(|e| exit::failure(exit::Code::Network, e))?;
+
+    let current = update::current_version();
+    let latest = release.version()?;
+    if latest <= current {
+        outro(tr_args(
+            "up-to-date",
+            &[("version", current.to_string().into())],
+        ))?;
+        return Ok(());
+    }
+    if check {
+        outro(tr_args(
+            "update-available",
+            &[
+                ("latest", latest.to_string().into()),
+                ("current", current.to_string().into()),
+                ("url", release.html_url.as_str().into()),
+            ],
+        ))?;
+        return Ok(());
+    }
+
+    if let Some(manager) = update::package_manager() {
+        outro(tr_args(
+            "update-with-package-manager",
+            &[
+                ("latest", latest.to_string().into()),
+                ("manager", manager.into()),
+            ],
+        ))?;
+        return Ok(());
+    }
+    let target = update::target().ok_or_else(|| {
+        format!(
+            "No release is built for {} {}, build playsync from source instead",
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        )
+    })?;
+    if update::public_key().is_none() {
+        if !insecure {
+            return Err(update::NO_PUBLIC_KEY.into());
+        }
+        cliclack::log::warning(
+            "This build has no release signing key, only the checksum of the download is checked",
+        )?;
+    }
+
+    if !output::confirm(
+        tr_args(
+            "self-update-confirm",
+            &[
+                ("current", current.to_string().into()),
+                ("latest", latest.to_string().into()),
+            ],
+        ),
+        true,
+    )? {
+        outro(tr("self-update-unchanged"))?;
+        return Ok(());
+    }
+
+    let sp = cliclack::spinner();
+    sp.start(format!("Downloading playsync {}", latest));
+    let installed = update::install(&release, target, insecure).await;
+    sp.clear();
+    installed.inspect_err(|e| {
+        let _ = outro(format!("❌ {}", e));
+    })?;
+
+    outro(tr_args(
+        "updated",
+        &[("version", latest.to_string().into())],
+    ))?;
+    Ok(())
+}
+
+/// Print the shell code registering the completions. It calls playsync back while
+/// completing, so configured playlists can be completed by alias or ID.
+fn handle_completions(shell: clap_complete::Shell) -> Result<(), Box<dyn std::error::Error>> {
+    let shells = clap_complete::env::Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| format!("Completions aren't supported for {}", shell))?;
+    let bin = std::env::current_exe()?;
+
+    let mut stdout = std::io::stdout();
+    completer.write_registration(
+        "COMPLETE",
+        "playsync",
+        "playsync",
+        &bin.to_string_lossy(),
+        &mut stdout,
+    )?;
+    Ok(())
+}
+
+/// List the plugins found in the plugins directory, loading each to check it
+fn handle_plugins() -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("plugins-intro"))?;
+
+    let dir = plugin::plugins_dir()?;
+    let names = plugin::discover()?;
+    if names.is_empty() {
+        outro(tr_args(
+            "no-plugins",
+            &[("dir", dir.display().to_string().into())],
+        ))?;
+        return Ok(());
+    }
+
+    for name in &names {
+        match plugin::Plugin::load(name) {
+            Ok(loaded) => {
+                let mut provides = Vec::new();
+                if loaded.is_provider() {
+                    provides.push(format!("source `plugin:{}`", name));
+                }
+                if loaded.is_filter() {
+                    provides.push("filter".to_string());
+                }
+                if provides.is_empty() {
+                    cliclack::log::warning(format!("{}: provides nothing", name))?;
+                } else {
+                    cliclack::log::success(format!("{}: {}", name, provides.join(", ")))?;
+                }
+            }
+            Err(e) => cliclack::log::error(e)?,
+        }
+    }
+
+    outro(tr_args(
+        "plugins-found",
+        &[
+            ("count", names.len().into()),
+            ("dir", dir.display().to_string().into()),
+        ],
+    ))?;
+    Ok(())
+}
+
+/// Show the service files running playsync in the background, and install them
+fn handle_install_service(
+    interval: u32,
+    daemon: bool,
+    install: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("service-intro"))?;
+
+    let mode = if daemon {
+        service::ServiceMode::Daemon
+    } else {
+        service::ServiceMode::Timer { minutes: interval }
+    };
+
+    if !install {
+        for file in service::files(&mode)? {
+            note(file.path.display().to_string(), file.content.trim_end())?;
+        }
+        outro(tr("service-not-installed"))?;
+        return Ok(());
+    }
+
+    for path in service::install(&mode)? {
+        cliclack::log::success(format!("Wrote {}", path.display()))?;
+    }
+    if config::Config::read().is_ok_and(|mut cfg| {
+        cfg.secrets_mut()
+            .into_iter()
+            .any(|(_, value)| value.as_deref().is_some_and(secret::is_encrypted))
+    }) {
+        cliclack::log::warning(
+            "The configuration has encrypted secrets. Set PLAYSYNC_PASSPHRASE in the service environment, since it can't be asked.",
+        )?;
+    }
+
+    outro(tr("service-installed"))?;
+    Ok(())
+}
+
+fn handle_history(args: history::HistoryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("history-intro"))?;
+
+    let entries: Vec<history::HistoryEntry> = history::read_all()?
+        .into_iter()
+        .filter(|entry| history::matches(entry, &args))
+        .collect();
+
+    if entries.is_empty() {
+        output::print(&entries)?;
+        outro(tr("no-runs"))?;
+        return Ok(());
+    }
+
+    let skip = entries.len().saturating_sub(args.limit);
+
+    if output::is_structured() {
+        output::print(&entries[skip..])?;
+    } else {
+        for entry in entries.iter().skip(skip) {
+            let title = format!(
+                "{} · {}{}",
+                entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%a %Y-%m-%d %H:%M"),
+                entry.command,
+                if entry.dry_run { " (dry run)" } else { "" }
+            );
+
+            let mut body = String::new();
+            for playlist in &entry.playlists {
+                body.push_str(&format!(
+                    "{} (ID: {}): +{} -{} ✗{}\n",
+                    playlist.title, playlist.id, playlist.added, playlist.removed, playlist.failed
+                ));
+            }
+            if entry.playlists.is_empty() {
+                body.push_str("No playlists processed\n");
+            }
+            body.push_str(&format!("Quota used: {} units", entry.quota_used));
+            if let Some(error) = &entry.error {
+                body.push_str(&format!("\nError: {}", error));
+            }
+
+            note(title, body)?;
+        }
+    }
+
+    outro(tr_args(
+        "runs-shown",
+        &[
+            ("shown", (entries.len() - skip).into()),
+            ("total", entries.len().into()),
+        ],
+    ))?;
+    Ok(())
+}
+
+async fn handle_cache(
+    args: cli::CacheArgs,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("cache-intro"))?;
+
+    let cache = cache::Cache::open()?;
+    let cfg = config::Config::read().unwrap_or_default();
+    let title = |playlist_id: &str| {
+        cfg.playlists
+            .iter()
+            .find(|p| p.id == playlist_id)
+            .map_or_else(|| playlist_id.to_string(), |p| p.title.clone())
+    };
+
+    match args.action {
+        None => {
+            let entries = cache.entries()?;
+            output::print(&entries)?;
+            if entries.is_empty() {
+                outro(tr("cache-empty"))?;
+                return Ok(());
+            }
+
+            let now = chrono::Utc::now();
+            let age = |time: chrono::DateTime<chrono::Utc>| {
+                stats::format_duration((now - time).to_std().unwrap_or_default())
+            };
+            for entry in &entries {
+                cliclack::log::info(format!(
+                    "{} (ID: {}): {} items, downloaded {} ago, checked {} ago",
+                    title(&entry.playlist_id),
+                    entry.playlist_id,
+                    entry.item_count,
+                    age(entry.fetched_at),
+                    age(entry.checked_at)
+                ))?;
+            }
+            outro(tr_args("cache-listed", &[("count", entries.len().into())]))?;
+        }
+        Some(cli::CacheAction::Refresh { playlist_ids }) => {
+            let client = youtube_client.ok_or_else(|| {
+                let _ = outro(tr("client-not-initialized"));
+                "YouTube client is not initialized"
+            })?;
+            let playlist_ids = if playlist_ids.is_empty() {
+                cache
+                    .entries()?
+                    .into_iter()
+                    .map(|entry| entry.playlist_id)
+                    .collect()
+            } else {
+                playlist_ids
+            };
+            // The client has its own connection to the cache
+            drop(cache);
+
+            let mut refreshed = 0;
+            for playlist_id in &playlist_ids {
+                let title = title(playlist_id);
+                match client.refresh_playlist_items(playlist_id, &title).await {
+                    Ok(items) => {
+                        refreshed += 1;
+                        cliclack::log::success(format!("{}: {} items", title, items.len()))?;
+                    }
+                    Err(e) => {
+                        exit::mark_failed_item(e.as_ref());
+                        cliclack::log::error(format!("{}: {}", title, e))?;
+                    }
+                }
+            }
+            outro(tr_args(
+                "cache-refreshed",
+                &[
+                    ("count", refreshed.into()),
+                    ("total", playlist_ids.len().into()),
+                ],
+            ))?;
+        }
+        Some(cli::CacheAction::Clear {
+            playlist_ids,
+            older_than,
+        }) => {
+            let removed = if let Some(days) = older_than {
+                let cutoff = chrono::Utc::now() - chrono::TimeDelta::days(days);
+                let mut removed = 0;
+                for entry in cache.entries()? {
+                    if entry.checked_at < cutoff && cache.remove(&entry.playlist_id)? {
+                        removed += 1;
+                    }
+                }
+                removed
+            } else if playlist_ids.is_empty() {
+                cache.clear()?
+            } else {
+                let mut removed = 0;
+                for playlist_id in &playlist_ids {
+                    if cache.remove(playlist_id)? {
+                        removed += 1;
+                    } else {
+                        cliclack::log::warning(format!(
+                            "{} isn't in the cache",
+                            title(playlist_id)
+                        ))?;
+                    }
+                }
+                removed
+            };
+            outro(tr_args("cache-cleared", &[("count", removed.into())]))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_status(
+    playlist_id: Option<String>,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("status-intro"))?;
+
+    let cfg = config::Config::read()?;
+
+    let playlists: Vec<config::Playlist> = cfg
+        .playlists
+        .iter()
+        .filter(|p| p.sync_from.is_some())
+        .filter(|p| playlist_id.as_ref().is_none_or(|id| p.id == *id))
+        .cloned()
+        .collect();
+
+    if playlists.is_empty() {
+        output::print(&Vec::<output::PlaylistStatus>::new())?;
+        outro(tr("no-sync-relationships"))?;
+        return Ok(());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let behind = status::check(&cfg, &client, &playlists).await?;
+
+    outro(if behind == 0 {
+        tr("all-up-to-date")
+    } else {
+        tr_args(
+            "playlists-behind",
+            &[("behind", behind.into()), ("total", playlists.len().into())],
+        )
+    })?;
+    Ok(())
+}
+
+/// Run the TUI. Syncs asked for in it run like `playsync sync`, outside of the TUI,
+/// which comes back once they are done.
+async fn handle_tui(
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    output::ensure_interactive("The TUI")?;
+    let client = youtube_client.ok_or("YouTube client is not initialized")?;
+
+    let mut app = tui::App::new()?;
+    loop {
+        match tui::run(&mut app, &client).await? {
+            tui::Action::Quit => return Ok(()),
+            tui::Action::Sync {
+                playlist_id,
+                dry_run,
+            } => {
+                let selection = run::Selection {
+                    playlist_ids: vec![playlist_id],
+                    ..Default::default()
+                };
+                if let Err(e) = handle_sync(selection, false, dry_run, None, Some(&client)).await {
+                    cliclack::log::error(format!("The sync failed: {}", e))?;
+                }
+
+                cliclack::log::remark("Press Enter to go back to the TUI")?;
+                std::io::stdin().read_line(&mut String::new())?;
+                app.reload()?;
+            }
+        }
+    }
+}
+
+async fn handle_explain(
+    video: String,
+    playlist_id: String,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("explain-intro"))?;
+
+    let cfg = config::Config::read()?;
+    let Some(playlist) = cfg.playlists.iter().find(|p| p.id == playlist_id) else {
+        outro(tr_args(
+            "playlist-not-configured",
+            &[("playlist", playlist_id.as_str().into())],
+        ))?;
+        return Ok(());
+    };
+    let playlist = cfg.with_defaults(playlist);
+    let Some(sync_from) = playlist.sync_from.as_deref() else {
+        outro(tr_args(
+            "no-sources",
+            &[("title", playlist.title.as_str().into())],
+        ))?;
+        return Ok(());
+    };
+    let Some(video_id) = youtube::parse_video_id(&video) else {
+        outro(tr_args("not-a-video", &[("video", video.as_str().into())]))?;
+        return Ok(());
+    };
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    outro(status::explain(&client, &playlist, sync_from, &video_id).await?)?;
+    Ok(())
+}
+
+async fn handle_search(
+    query: String,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("search-intro"))?;
+
+    let cfg = config::Config::read()?;
+
+    if cfg.playlists.is_empty() {
+        outro(tr("no-playlists-configured"))?;
+        return Ok(());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let video_id = youtube::parse_video_id(&query);
+    let needle = query.to_lowercase();
+    let mut found = 0;
+
+    for playlist in &cfg.playlists {
+        let sp = cliclack::spinner();
+        sp.start(format!("Searching {}", playlist.title));
+        let videos = client.get_playlist_items(&playlist.id).await?;
+        sp.clear();
+
+        let mut matches = String::new();
+        for (position, video) in videos.iter().enumerate() {
+            if video_id.as_ref() == Some(&video.video_id)
+                || video.title.to_lowercase().contains(&needle)
+            {
+                found += 1;
+                matches.push_str(&format!(
+                    "#{} {} (ID: {})\n",
+                    position + 1,
+                    video.title,
+                    video.video_id
+                ));
+            }
+        }
+
+        if !matches.is_empty() {
+            note(
+                format!("{} (ID: {})", playlist.title, playlist.id),
+                matches.trim_end(),
+            )?;
+        }
+    }
+
+    if found == 0 {
+        outro(tr_args("no-match", &[("query", query.as_str().into())]))?;
+    } else {
+        outro(tr_args("matches-found", &[("count", found.into())]))?;
+    }
+    Ok(())
+}
+
+async fn handle_merge(
+    sources: Vec<String>,
+    title: String,
+    description: String,
+    privacy: String,
+    sort: Option<sort::SortKey>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("merge-intro-dry-run")
+    } else {
+        tr("merge-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    match edit::merge(
+        &client,
+        &sources,
+        &title,
+        &description,
+        &privacy,
+        sort,
+        dry_run,
+    )
+    .await?
+    {
+        Outcome::Done(playlist_id) => outro(tr_args(
+            "merged",
+            &[
+                ("title", title.as_str().into()),
+                ("id", playlist_id.as_str().into()),
+            ],
+        ))?,
+        Outcome::Previewed | Outcome::Unchanged => outro(tr("dry-run-completed"))?,
+    }
+    Ok(())
+}
+
+async fn handle_split(
+    playlist_id: String,
+    by: split::SplitBy,
+    size: usize,
+    privacy: String,
+    keep: bool,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("split-intro-dry-run")
+    } else {
+        tr("split-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let outcome =
+        split::split_playlist(&client, &playlist_id, by, size, &privacy, keep, dry_run).await?;
+    outro(match outcome {
+        Outcome::Done(()) => tr("split-completed"),
+        Outcome::Previewed => tr("dry-run-completed"),
+        Outcome::Unchanged => tr("split-nothing"),
+    })?;
+    Ok(())
+}
+
+async fn handle_copy(
+    source: String,
+    target: String,
+    limit: Option<usize>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("copy-intro-dry-run")
+    } else {
+        tr("copy-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    outro(
+        match edit::copy(&client, &source, &target, limit, dry_run).await? {
+            Outcome::Done(()) => tr("copy-completed"),
+            Outcome::Previewed => tr("dry-run-completed"),
+            Outcome::Unchanged => tr("nothing-to-copy"),
+        },
+    )?;
+    Ok(())
+}
+
+async fn handle_move(
+    source: String,
+    target: String,
+    filter: filter::FilterArgs,
+    all: bool,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("move-intro-dry-run")
+    } else {
+        tr("move-intro")
+    })?;
+
+    // Moving a whole playlist by accident is hard to undo, so require an explicit opt-in
+    if filter.is_empty() && !all {
+        outro(tr("move-no-filter"))?;
+        return Err("No filter given".into());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    outro(
+        match edit::move_videos(&client, &source, &target, &filter, dry_run).await? {
+            Outcome::Done(()) => tr("move-completed"),
+            Outcome::Previewed => tr("dry-run-completed"),
+            Outcome::Unchanged => tr("nothing-to-move"),
+        },
+    )?;
+    Ok(())
+}
+
+async fn handle_sort(
+    playlist_id: String,
+    by: sort::SortKey,
+    reverse: bool,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("sort-intro-dry-run")
+    } else {
+        tr("sort-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let outcome = sort::sort_playlist(&client, &playlist_id, by, reverse, dry_run).await?;
+    outro_reordered(outcome)
+}
+
+/// End a command reordering a playlist
+fn outro_reordered(outcome: Outcome) -> Result<(), Box<dyn std::error::Error>> {
+    outro(match outcome {
+        Outcome::Done(()) => tr("playlist-reordered"),
+        Outcome::Previewed => tr("dry-run-completed"),
+        Outcome::Unchanged => tr("already-sorted"),
+    })?;
+    Ok(())
+}
+
+async fn handle_shuffle(
+    playlist_id: String,
+    seed: Option<u64>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("shuffle-intro-dry-run")
+    } else {
+        tr("shuffle-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let outcome = sort::shuffle_playlist(&client, &playlist_id, seed, dry_run).await?;
+    outro_reordered(outcome)
+}
+
+async fn handle_tidy(
+    playlist_id: Option<String>,
+    skip: Vec<tidy::TidyStep>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("tidy-intro-dry-run")
+    } else {
+        tr("tidy-intro")
+    })?;
+
+    let cfg = config::Config::read()?;
+
+    let playlists: Vec<config::Playlist> = if let Some(id) = playlist_id {
+        cfg.playlists.into_iter().filter(|p| p.id == id).collect()
+    } else {
+        cfg.playlists
+    };
+
+    if playlists.is_empty() {
+        outro(tr("no-playlists-to-tidy"))?;
+        return Ok(());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    tidy::tidy_playlists(&client, &playlists, &skip, dry_run).await?;
+
+    outro(if dry_run {
+        tr("dry-run-completed")
+    } else {
+        tr("tidy-completed")
+    })?;
+    Ok(())
+}
+
+async fn handle_add_video(
+    playlist_id: String,
+    videos: Vec<String>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("add-intro-dry-run")
+    } else {
+        tr("add-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    outro(
+        match edit::add_videos(&client, &playlist_id, &videos, dry_run).await? {
+            Outcome::Done(()) => tr("videos-added"),
+            Outcome::Previewed => tr("dry-run-completed"),
+            Outcome::Unchanged => tr("nothing-to-add"),
+        },
+    )?;
+    Ok(())
+}
+
+async fn handle_remove_video(
+    playlist_id: String,
+    videos: Vec<String>,
+    filter: filter::FilterArgs,
+    placeholders: bool,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("remove-intro-dry-run")
+    } else {
+        tr("remove-intro")
+    })?;
+
+    if videos.is_empty() && filter.is_empty() && !placeholders {
+        outro(tr("remove-nothing-selected"))?;
+        return Err("Nothing selected".into());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let outcome = edit::remove_videos(
+        &client,
+        &playlist_id,
+        &videos,
+        &filter,
+        placeholders,
+        dry_run,
+    )
+    .await?;
+    outro(match outcome {
+        Outcome::Done(()) => tr("videos-removed"),
+        Outcome::Previewed => tr("dry-run-completed"),
+        Outcome::Unchanged => tr("nothing-to-remove"),
+    })?;
+    Ok(())
+}
+
+fn handle_open(playlist: String, print: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = config::Config::read()?;
+
+    let playlist_id = cfg.resolve_playlist(&playlist).inspect_err(|e| {
+        let _ = outro(format!("❌ {}", e));
+    })?;
+    let Some(playlist) = cfg.find_playlist(&playlist_id) else {
+        outro(tr_args(
+            "not-a-configured-playlist",
+            &[("playlist", playlist.as_str().into())],
+        ))?;
+        return Err("Playlist not found".into());
+    };
+
+    let url = youtube::playlist_url(&playlist.id);
+
+    if print {
+        println!("{}", url);
+    } else {
+        open::that(&url)?;
+        outro(tr_args(
+            "opened",
+            &[("title", playlist.title.as_str().into())],
+        ))?;
+    }
+
+    Ok(())
+}
+
+async fn handle_convert(
+    from: String,
+    to: String,
+    title: Option<String>,
+    privacy: String,
+    max_lookups: usize,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("convert-intro-dry-run")
+    } else {
+        tr("convert-intro")
+    })?;
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    let outcome = edit::convert(&client, &from, &to, title, &privacy, max_lookups, dry_run).await?;
+    outro(match outcome {
+        Outcome::Done((target_id, target_title)) => tr_args(
+            "converted",
+            &[
+                ("title", target_title.as_str().into()),
+                ("url", youtube::playlist_url(&target_id).into()),
+            ],
+        ),
+        Outcome::Previewed => tr("dry-run-completed"),
+        Outcome::Unchanged => tr("nothing-to-convert"),
+    })?;
+    Ok(())
+}
+
+async fn handle_download(
+    playlist_id: Option<String>,
+    dry_run: bool,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("download-intro-dry-run")
+    } else {
+        tr("download-intro")
+    })?;
+
+    let cfg = config::Config::read()?;
+    let playlists: Vec<&config::Playlist> = cfg
+        .playlists
+        .iter()
+        .filter(|p| p.download_dir.is_some())
+        .filter(|p| playlist_id.as_ref().is_none_or(|id| p.id == *id))
+        .collect();
+
+    if playlists.is_empty() {
+        outro(tr("no-download-dir"))?;
+        return Ok(());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    for playlist in playlists {
+        let videos = client.get_playlist_items(&playlist.id).await?;
+        if let Some(dir) = &playlist.download_dir {
+            download::mirror_playlist(playlist, dir, &videos, dry_run).await?;
+        }
+    }
+
+    outro(if dry_run {
+        tr("dry-run-completed")
+    } else {
+        tr("download-completed")
+    })?;
+    Ok(())
+}
+
+fn handle_import(
+    file: std::path::PathBuf,
+    from: import::ImportFormat,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(if dry_run {
+        tr("import-intro-dry-run")
+    } else {
+        tr("import-intro")
+    })?;
+
+    // Sources keep reading the file on each sync, so store an absolute path
+    let file = file.canonicalize()?;
+    let library = import::read_library(from, &file)?;
+
+    let choices = import::choices(&library);
+    if choices.is_empty() {
+        outro(tr("import-nothing"))?;
+        return Ok(());
+    }
+
+    output::ensure_interactive("Choosing what to import")?;
+    let selected: Vec<String> = cliclack::multiselect(tr("pick-imports"))
+        .items(&choices)
+        .required(false)
+        .interact()?;
+
+    let lock = config::Config::lock()?;
+    let mut cfg = config::Config::read()?;
+    let imported = import::add_playlists(&mut cfg, from, &file, &library, &selected, dry_run)?;
+
+    if dry_run {
+        outro(tr("dry-run-completed"))?;
+        return Ok(());
+    }
+
+    cfg.write(&lock)?;
+    outro(tr_args("imported", &[("count", imported.into())]))?;
+    Ok(())
+}
+
+async fn handle_stats(
+    playlist_id: Option<String>,
+    youtube_client: Option<YouTubeClient>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    intro(tr("stats-intro"))?;
+
+    let cfg = config::Config::read()?;
+
+    let playlists: Vec<config::Playlist> = if let Some(id) = playlist_id {
+        cfg.playlists.into_iter().filter(|p| p.id == id).collect()
+    } else {
+        cfg.playlists
+    };
+
+    if playlists.is_empty() {
+        outro(tr("no-playlists-found"))?;
+        return Ok(());
+    }
+
+    let client = youtube_client.ok_or_else(|| {
+        let _ = outro(tr("client-not-initialized"));
+        "YouTube client is not initialized"
+    })?;
+
+    stats::show(&client, &playlists).await?;
+
+    outro(tr("stats-completed"))?;
     Ok(())
 }
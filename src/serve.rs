@@ -0,0 +1,341 @@
+use crate::config::Config;
+use crate::history;
+use crate::http::{self, Request};
+use crate::metrics;
+use crate::progress::{self, Event};
+use crate::sync;
+use crate::youtube::YouTubeClient;
+use playsync_core::run;
+use serde::Serialize;
+use serde_json::json;
+use std::cell::Cell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+
+/// State shared by the connections of the API server
+struct Server {
+    client: YouTubeClient,
+    /// Token clients have to send as `Authorization: Bearer <token>`
+    token: Option<String>,
+    /// Whether a sync started through the API is running
+    syncing: Cell<bool>,
+}
+
+/// Web dashboard, served at `/`
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+/// Serve the HTTP API on `address` until interrupted.
+///
+/// Endpoints:
+/// - `GET /`: the web dashboard
+/// - `GET /healthz`: `200` unless the last sync failed
+/// - `GET /status`: whether a sync is running
+/// - `GET /playlists`: the configured playlists
+/// - `GET /pending?id=...`: the videos the next sync of a playlist would add
+/// - `POST /sync`: start a sync, with optional `id` (comma-separated IDs or aliases),
+///   `group` and `dry_run=true` query parameters
+/// - `GET /report`: the last sync recorded in the history
+/// - `GET /progress`: server-sent events of the running syncs
+/// - `GET /metrics`: Prometheus metrics
+pub async fn run(
+    address: SocketAddr,
+    token: Option<String>,
+    client: YouTubeClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(address).await?;
+    let server = Rc::new(Server {
+        client,
+        token,
+        syncing: Cell::new(false),
+    });
+
+    // Syncs aren't `Send`, so connections and syncs run on this thread
+    tokio::task::LocalSet::new()
+        .run_until(accept(listener, server))
+        .await
+}
+
+async fn accept(
+    listener: TcpListener,
+    server: Rc<Server>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        // Failures like running out of file descriptors pass, the server keeps going
+        let stream = match accepted {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                let _ = cliclack::log::warning(format!("Failed to accept a connection: {}", e));
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let server = server.clone();
+        tokio::task::spawn_local(async move {
+            if let Err(e) = handle(server, stream).await {
+                let _ = cliclack::log::warning(format!("Request failed: {}", e));
+            }
+        });
+    }
+}
+
+async fn handle(
+    server: Rc<Server>,
+    mut stream: TcpStream,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let request = match http::read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => return respond_error(&mut stream, 400, &e.to_string()).await,
+    };
+
+    // Health checks don't need the token
+    if request.method == "GET" && request.path == "/healthz" {
+        return metrics::respond_health(&mut stream).await;
+    }
+
+    // The dashboard asks for the token itself
+    if request.method == "GET" && request.path == "/" {
+        return http::respond(
+            &mut stream,
+            200,
+            "text/html; charset=utf-8",
+            DASHBOARD.as_bytes(),
+        )
+        .await;
+    }
+
+    if is_foreign(&request, server.token.is_some()) {
+        return respond_error(&mut stream, 403, "Requests from other sites are refused").await;
+    }
+
+    if let Some(token) = &server.token {
+        // Browsers can't set headers on event streams, so the token can also be a parameter
+        let authorized = request.headers.get("authorization") == Some(&format!("Bearer {}", token))
+            || request.query.get("token") == Some(token);
+        if !authorized {
+            return respond_error(&mut stream, 401, "Missing or wrong API token").await;
+        }
+    }
+
+    if let Err(e) = route(&server, &request, &mut stream).await {
+        return respond_error(&mut stream, 500, &e.to_string()).await;
+    }
+
+    Ok(())
+}
+
+/// Whether a request was made by a web page of another site. Browsers let any page send
+/// requests to local servers, so they could start syncs: their `Origin` isn't this
+/// server's. Without a token, the server is also only reached by IP address or
+/// `localhost`, so a page can't rebind its own domain name to this machine.
+fn is_foreign(request: &Request, has_token: bool) -> bool {
+    let host = request
+        .headers
+        .get("host")
+        .map(String::as_str)
+        .unwrap_or_default();
+    let origin = format!("http://{}", host);
+
+    let foreign_origin = request
+        .headers
+        .get("origin")
+        .is_some_and(|value| *value != origin);
+    let named_host = reqwest::Url::parse(&origin)
+        .ok()
+        .is_none_or(|url| url.domain().is_some_and(|domain| domain != "localhost"));
+
+    foreign_origin || (!has_token && named_host)
+}
+
+async fn route(
+    server: &Rc<Server>,
+    request: &Request,
+    stream: &mut TcpStream,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => {
+            respond_json(stream, 200, &json!({ "syncing": server.syncing.get() })).await
+        }
+        ("GET", "/pending") => pending(server, request, stream).await,
+        ("GET", "/metrics") => {
+            http::respond(
+                stream,
+                200,
+                "text/plain; version=0.0.4",
+                metrics::render().as_bytes(),
+            )
+            .await
+        }
+        ("GET", "/playlists") => {
+            let cfg = Config::read()?;
+            respond_json(stream, 200, &cfg.playlists).await
+        }
+        ("POST", "/sync") => start_sync(server, request, stream).await,
+        ("GET", "/report") => {
+            let last_sync = history::read_all()?
+                .into_iter()
+                .rev()
+                .find(|entry| entry.command == "sync");
+            match last_sync {
+                Some(entry) => respond_json(stream, 200, &entry).await,
+                None => respond_error(stream, 404, "No sync has been recorded yet").await,
+            }
+        }
+        ("GET", "/progress") => stream_progress(stream).await,
+        (
+            _,
+            "/status" | "/pending" | "/metrics" | "/playlists" | "/sync" | "/report" | "/progress",
+        ) => respond_error(stream, 405, "Method not allowed").await,
+        _ => respond_error(stream, 404, "Not found").await,
+    }
+}
+
+/// Compare a playlist with its sources, as `playsync status` does
+async fn pending(
+    server: &Rc<Server>,
+    request: &Request,
+    stream: &mut TcpStream,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = Config::read()?;
+    let Some(query) = request.query.get("id") else {
+        return respond_error(stream, 400, "The `id` parameter is required").await;
+    };
+    let playlist = match cfg.resolve_playlist(query) {
+        Ok(id) => cfg.find_playlist(&id),
+        Err(e) => return respond_error(stream, 400, &e.to_string()).await,
+    };
+    let Some(playlist) = playlist.filter(|p| p.sync_from.is_some()) else {
+        return respond_error(stream, 404, "The playlist doesn't sync from any source").await;
+    };
+
+    let sync_from = playlist.sync_from.as_deref().unwrap_or_default();
+    let pending = sync::pending_changes(&server.client, &playlist.id, sync_from).await?;
+    let to_add: Vec<_> = pending
+        .videos_to_add
+        .iter()
+        .map(|video| {
+            json!({
+                "video_id": video.video_id,
+                "title": video.title,
+                "channel": video.channel,
+            })
+        })
+        .collect();
+
+    respond_json(
+        stream,
+        200,
+        &json!({
+            "id": playlist.id,
+            "title": playlist.title,
+            "item_count": pending.target_count,
+            "to_add": to_add,
+        }),
+    )
+    .await
+}
+
+/// Start a sync in the background, unless one is already running
+async fn start_sync(
+    server: &Rc<Server>,
+    request: &Request,
+    stream: &mut TcpStream,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if server.syncing.get() {
+        return respond_error(stream, 409, "A sync is already running").await;
+    }
+
+    let mut cfg = Config::read()?;
+    let mut playlist_ids = Vec::new();
+    for query in request
+        .query
+        .get("id")
+        .into_iter()
+        .flat_map(|ids| ids.split(','))
+        .filter(|id| !id.is_empty())
+    {
+        match cfg.resolve_playlist(query) {
+            Ok(id) => playlist_ids.push(id),
+            Err(e) => return respond_error(stream, 400, &e.to_string()).await,
+        }
+    }
+    let dry_run = match request.query.get("dry_run").map(String::as_str) {
+        Some("true") | Some("1") => true,
+        Some("false") | Some("0") => false,
+        _ => cfg.defaults.dry_run,
+    };
+
+    let selection = run::Selection {
+        playlist_ids,
+        exclude: Vec::new(),
+        group: request.query.get("group").cloned(),
+    };
+
+    server.syncing.set(true);
+    let server = server.clone();
+    tokio::task::spawn_local(async move {
+        progress::publish(Event::SyncStarted { dry_run });
+        let error = run::sync(&mut cfg, &server.client, &selection, dry_run, None)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        progress::publish(Event::SyncFinished { error });
+        server.syncing.set(false);
+    });
+
+    respond_json(
+        stream,
+        202,
+        &json!({ "status": "started", "dry_run": dry_run }),
+    )
+    .await
+}
+
+/// Send the progress events as server-sent events until the client disconnects
+async fn stream_progress(stream: &mut TcpStream) -> Result<(), Box<dyn std::error::Error>> {
+    let mut events = progress::subscribe();
+    http::start_stream(stream, "text/event-stream").await?;
+
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                let data = format!("data: {}\n\n", serde_json::to_string(&event)?);
+                if stream.write_all(data.as_bytes()).await.is_err() {
+                    return Ok(());
+                }
+            }
+            // Events missed by a slow client are skipped
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+async fn respond_json(
+    stream: &mut TcpStream,
+    status: u16,
+    value: &impl Serialize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    http::respond(
+        stream,
+        status,
+        "application/json",
+        &serde_json::to_vec(value)?,
+    )
+    .await
+}
+
+async fn respond_error(
+    stream: &mut TcpStream,
+    status: u16,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    respond_json(stream, status, &json!({ "error": message })).await
+}
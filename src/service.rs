@@ -0,0 +1,197 @@
+use crate::config;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A file making the system run playsync in the background
+pub struct ServiceFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// How the background job runs playsync
+pub enum ServiceMode {
+    /// `playsync sync` every `minutes`
+    Timer { minutes: u32 },
+    /// `playsync daemon`, kept running
+    Daemon,
+}
+
+/// Name of the service, with the profile so each profile can have its own
+fn service_name() -> String {
+    match config::profile() {
+        Some(profile) => format!("playsync-{}", profile),
+        None => "playsync".to_string(),
+    }
+}
+
+/// Arguments of the playsync command run by the service.
+///
+/// The configuration file is always given by its absolute path, so the service uses the
+/// same configuration (and profile) as the current run.
+fn command_args(mode: &ServiceMode) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let exe = std::env::current_exe()?;
+    let config_path = config::config_path()?;
+
+    let mut args = vec![
+        exe.to_string_lossy().into_owned(),
+        "--config".to_string(),
+        config_path.to_string_lossy().into_owned(),
+    ];
+    args.push(match mode {
+        ServiceMode::Timer { .. } => "sync".to_string(),
+        ServiceMode::Daemon => "daemon".to_string(),
+    });
+
+    Ok(args)
+}
+
+fn home_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| "HOME is not set".into())
+}
+
+/// The service files for this system: a systemd user service (and timer) on Linux,
+/// a launchd agent on macOS
+pub fn files(mode: &ServiceMode) -> Result<Vec<ServiceFile>, Box<dyn std::error::Error>> {
+    if cfg!(target_os = "macos") {
+        launchd_files(mode)
+    } else if cfg!(unix) {
+        systemd_files(mode)
+    } else {
+        Err(
+            "Services can only be generated on Linux and macOS. Use the Task Scheduler on Windows."
+                .into(),
+        )
+    }
+}
+
+fn systemd_files(mode: &ServiceMode) -> Result<Vec<ServiceFile>, Box<dyn std::error::Error>> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir()?.join(".config"),
+    }
+    .join("systemd/user");
+    let name = service_name();
+
+    // systemd splits ExecStart on spaces and expands `%` specifiers, so every argument
+    // is quoted and escaped
+    let exec = command_args(mode)?
+        .iter()
+        .map(|arg| {
+            let arg = arg
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%");
+            format!("\"{}\"", arg)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut files = Vec::new();
+    match mode {
+        ServiceMode::Timer { minutes } => {
+            files.push(ServiceFile {
+                path: dir.join(format!("{}.service", name)),
+                content: format!(
+                    "[Unit]\nDescription=Sync YouTube playlists with playsync\nWants=network-online.target\nAfter=network-online.target\n\n[Service]\nType=oneshot\nExecStart={}\n",
+                    exec
+                ),
+            });
+            files.push(ServiceFile {
+                path: dir.join(format!("{}.timer", name)),
+                content: format!(
+                    "[Unit]\nDescription=Run playsync every {} minutes\n\n[Timer]\nOnBootSec=5min\nOnUnitActiveSec={}min\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+                    minutes, minutes
+                ),
+            });
+        }
+        ServiceMode::Daemon => files.push(ServiceFile {
+            path: dir.join(format!("{}.service", name)),
+            content: format!(
+                "[Unit]\nDescription=playsync daemon\nWants=network-online.target\nAfter=network-online.target\n\n[Service]\nExecStart={}\nRestart=on-failure\nRestartSec=60\n\n[Install]\nWantedBy=default.target\n",
+                exec
+            ),
+        }),
+    }
+
+    Ok(files)
+}
+
+fn launchd_files(mode: &ServiceMode) -> Result<Vec<ServiceFile>, Box<dyn std::error::Error>> {
+    let label = launchd_label();
+    let log = config::config_dir()?.join(format!("{}.log", service_name()));
+
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let args = command_args(mode)?
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape(arg)))
+        .collect::<String>();
+    let schedule = match mode {
+        ServiceMode::Timer { minutes } => format!(
+            "    <key>StartInterval</key>\n    <integer>{}</integer>\n",
+            u64::from(*minutes) * 60
+        ),
+        ServiceMode::Daemon => "    <key>KeepAlive</key>\n    <true/>\n".to_string(),
+    };
+    let log = escape(&log.to_string_lossy());
+
+    Ok(vec![ServiceFile {
+        path: home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label)),
+        content: format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n<dict>\n    <key>Label</key>\n    <string>{}</string>\n    <key>ProgramArguments</key>\n    <array>\n{}    </array>\n    <key>RunAtLoad</key>\n    <true/>\n{}    <key>StandardOutPath</key>\n    <string>{}</string>\n    <key>StandardErrorPath</key>\n    <string>{}</string>\n</dict>\n</plist>\n",
+            label, args, schedule, log, log
+        ),
+    }])
+}
+
+fn launchd_label() -> String {
+    format!("com.kildesu.{}", service_name())
+}
+
+/// Write the service files and enable them, replacing a previous installation
+pub fn install(mode: &ServiceMode) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let files = files(mode)?;
+
+    for file in &files {
+        if let Some(dir) = file.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&file.path, &file.content)?;
+    }
+
+    if cfg!(target_os = "macos") {
+        let plist = files[0].path.to_string_lossy().into_owned();
+        // Unloading fails when the agent isn't loaded yet, which is fine
+        let _ = Command::new("launchctl").args(["unload", &plist]).status();
+        run("launchctl", &["load", "-w", &plist])?;
+    } else {
+        let unit = match mode {
+            ServiceMode::Timer { .. } => format!("{}.timer", service_name()),
+            ServiceMode::Daemon => format!("{}.service", service_name()),
+        };
+        run("systemctl", &["--user", "daemon-reload"])?;
+        run("systemctl", &["--user", "enable", "--now", &unit])?;
+    }
+
+    Ok(files.into_iter().map(|file| file.path).collect())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if !status.success() {
+        return Err(format!("`{} {}` failed ({})", program, args.join(" "), status).into());
+    }
+
+    Ok(())
+}
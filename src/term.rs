@@ -0,0 +1,329 @@
+use cliclack::{Theme, ThemeState};
+use playsync_core::ui::{Level, Progress, Ui};
+use std::io;
+
+/// The UI of the command line: messages, progress and prompts drawn with cliclack
+pub struct Terminal;
+
+/// Template of the progress bars: the items done out of the total, the time left and
+/// the message, cut to the width of the terminal
+const PROGRESS_TEMPLATE: &str = "{bar:30.magenta} {pos}/{len} [{eta} left] {wide_msg}";
+
+impl Ui for Terminal {
+    fn log(&self, level: Level, message: &str) -> io::Result<()> {
+        match level {
+            Level::Remark => cliclack::log::remark(message),
+            Level::Info => cliclack::log::info(message),
+            Level::Step => cliclack::log::step(message),
+            Level::Success => cliclack::log::success(message),
+            Level::Warning => cliclack::log::warning(message),
+            Level::Error => cliclack::log::error(message),
+        }
+    }
+
+    fn note(&self, title: &str, body: &str) -> io::Result<()> {
+        cliclack::note(title, body)
+    }
+
+    fn progress_bar(&self, total: u64) -> Box<dyn Progress> {
+        Box::new(Bar(
+            cliclack::progress_bar(total).with_template(PROGRESS_TEMPLATE)
+        ))
+    }
+
+    fn spinner(&self) -> Box<dyn Progress> {
+        Box::new(Bar(cliclack::spinner()))
+    }
+
+    fn confirm(&self, prompt: &str, initial_value: bool) -> io::Result<bool> {
+        cliclack::confirm(prompt)
+            .initial_value(initial_value)
+            .interact()
+    }
+
+    fn password(&self, prompt: &str) -> io::Result<String> {
+        cliclack::password(prompt).mask('▪').interact()
+    }
+}
+
+/// A progress bar or spinner of cliclack
+struct Bar(cliclack::ProgressBar);
+
+impl Progress for Bar {
+    fn start(&self, message: &str) {
+        self.0.start(message);
+    }
+
+    fn set_length(&self, total: u64) {
+        self.0.set_length(total);
+    }
+
+    fn set_message(&self, message: &str) {
+        self.0.set_message(message);
+    }
+
+    fn inc(&self, delta: u64) {
+        self.0.inc(delta);
+    }
+
+    fn stop(&self, message: &str) {
+        self.0.stop(message);
+    }
+
+    fn error(&self, message: &str) {
+        self.0.error(message);
+    }
+
+    fn clear(&self) {
+        self.0.clear();
+    }
+}
+
+/// Set how messages look for the whole run. `quiet` only shows errors, for unattended
+/// runs; results printed on stdout, like JSON output, are left as they are. Without
+/// `color`, or when `NO_COLOR` is set, messages are plain ASCII lines without colors.
+pub fn set_style(quiet: bool, color: bool) {
+    let color = color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    if !color {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+    if quiet || !color {
+        cliclack::set_theme(LogTheme {
+            quiet,
+            plain: !color,
+        });
+    }
+}
+
+/// Theme for logs: only errors when `quiet`, and plain ASCII lines when `plain`
+struct LogTheme {
+    quiet: bool,
+    plain: bool,
+}
+
+/// Replace the emoji and symbols of messages with ASCII, keeping other characters,
+/// like the accents of video titles
+fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '→' => plain.push_str("->"),
+            '↔' => plain.push_str("<->"),
+            '…' => plain.push_str("..."),
+            '·' | '−' => plain.push('-'),
+            '✓' => plain.push('+'),
+            '✗' => plain.push('x'),
+            // Emoji, with the space after them
+            '\u{2300}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}' => {
+                while chars
+                    .next_if(|&c| c == '\u{FE0F}' || c == '\u{200D}')
+                    .is_some()
+                {}
+                chars.next_if_eq(&' ');
+            }
+            '\u{FE0F}' | '\u{200D}' => {}
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
+
+impl LogTheme {
+    /// A line per line of the text, the first one after the symbol
+    fn plain_lines(&self, text: &str, symbol: &str) -> String {
+        let mut lines = String::new();
+        for (index, line) in plain_text(text).lines().enumerate() {
+            let prefix = if index == 0 { symbol } else { " " };
+            lines.push_str(&format!("{}  {}\n", prefix, line));
+        }
+        lines
+    }
+}
+
+/// The default cliclack theme, rendering what [`LogTheme`] leaves as it is
+struct ClackTheme;
+
+impl Theme for ClackTheme {}
+
+impl Theme for LogTheme {
+    fn remark_symbol(&self) -> String {
+        if self.plain {
+            "-".into()
+        } else {
+            ClackTheme.remark_symbol()
+        }
+    }
+
+    fn info_symbol(&self) -> String {
+        if self.plain {
+            "i".into()
+        } else {
+            ClackTheme.info_symbol()
+        }
+    }
+
+    fn warning_symbol(&self) -> String {
+        if self.plain {
+            "!".into()
+        } else {
+            ClackTheme.warning_symbol()
+        }
+    }
+
+    fn error_symbol(&self) -> String {
+        if self.plain {
+            "x".into()
+        } else {
+            ClackTheme.error_symbol()
+        }
+    }
+
+    fn active_symbol(&self) -> String {
+        if self.plain {
+            "+".into()
+        } else {
+            ClackTheme.active_symbol()
+        }
+    }
+
+    fn submit_symbol(&self) -> String {
+        if self.plain {
+            "o".into()
+        } else {
+            ClackTheme.submit_symbol()
+        }
+    }
+
+    fn spinner_chars(&self) -> String {
+        if self.plain {
+            "-\\|/-".into()
+        } else {
+            ClackTheme.spinner_chars()
+        }
+    }
+
+    fn progress_chars(&self) -> String {
+        if self.plain {
+            "#-".into()
+        } else {
+            ClackTheme.progress_chars()
+        }
+    }
+
+    fn format_intro(&self, title: &str) -> String {
+        match (self.quiet, self.plain) {
+            (true, _) => String::new(),
+            (false, true) => self.plain_lines(title, "=="),
+            (false, false) => ClackTheme.format_intro(title),
+        }
+    }
+
+    fn format_outro(&self, message: &str) -> String {
+        match (self.quiet, self.plain) {
+            (true, _) => String::new(),
+            (false, true) => self.plain_lines(message, "=="),
+            (false, false) => ClackTheme.format_outro(message),
+        }
+    }
+
+    fn format_outro_cancel(&self, message: &str) -> String {
+        if self.plain {
+            self.plain_lines(message, &self.error_symbol())
+        } else {
+            ClackTheme.format_outro_cancel(message)
+        }
+    }
+
+    fn format_note(&self, prompt: &str, message: &str) -> String {
+        match (self.quiet, self.plain) {
+            (true, _) => String::new(),
+            (false, true) => self.plain_lines(&format!("{}\n{}", prompt, message), "-"),
+            (false, false) => ClackTheme.format_note(prompt, message),
+        }
+    }
+
+    fn format_outro_note(&self, prompt: &str, message: &str) -> String {
+        match (self.quiet, self.plain) {
+            (true, _) => String::new(),
+            (false, true) => self.plain_lines(&format!("{}\n{}", prompt, message), "=="),
+            (false, false) => ClackTheme.format_outro_note(prompt, message),
+        }
+    }
+
+    fn format_log(&self, text: &str, symbol: &str) -> String {
+        if self.quiet && symbol != self.error_symbol() {
+            String::new()
+        } else if self.plain {
+            self.plain_lines(text, symbol)
+        } else if self.quiet {
+            // Without the empty line between messages, which only separates them
+            ClackTheme.format_log_with_spacing(text, symbol, false)
+        } else {
+            ClackTheme.format_log(text, symbol)
+        }
+    }
+
+    fn format_progress_start(&self, template: &str, grouped: bool, last: bool) -> String {
+        match (self.quiet, self.plain) {
+            (true, _) => String::new(),
+            (false, true) => format!("{{spinner}}  {}", template),
+            (false, false) => ClackTheme.format_progress_start(template, grouped, last),
+        }
+    }
+
+    fn format_progress_with_state(
+        &self,
+        msg: &str,
+        grouped: bool,
+        last: bool,
+        state: &ThemeState,
+    ) -> String {
+        let failed = matches!(state, ThemeState::Error(_) | ThemeState::Cancel);
+        if self.quiet && !failed {
+            return String::new();
+        }
+        if !self.plain {
+            return ClackTheme.format_progress_with_state(msg, grouped, last, state);
+        }
+
+        let symbol = match state {
+            // The message of a running bar is its template, which isn't plain text
+            ThemeState::Active => return msg.to_string(),
+            ThemeState::Submit => self.submit_symbol(),
+            ThemeState::Cancel | ThemeState::Error(_) => self.error_symbol(),
+        };
+        self.plain_lines(msg, &symbol).trim_end().to_string()
+    }
+}
+
+/// Lay out rows under a header, each column as wide as its widest cell
+pub fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header
+        .iter()
+        .map(|cell| console::measure_text_width(cell))
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(console::measure_text_width(cell));
+        }
+    }
+
+    let line = |cells: Vec<&str>| -> String {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| console::pad_str(cell, *width, console::Alignment::Left, None))
+            .map(|cell| cell.into_owned())
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+
+    let mut lines = vec![line(header.to_vec())];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(String::as_str).collect())),
+    );
+    lines.join("\n")
+}
@@ -0,0 +1,903 @@
+use crate::config::{Config, InsertPosition, Playlist};
+use crate::history::{self, HistoryEntry};
+use crate::source::Source;
+use crate::sync;
+use crate::youtube::{VideoInfo, YouTubeClient};
+use crate::{diff, graph};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// What the TUI hands back to the regular commands, which run outside of it
+pub enum Action {
+    Quit,
+    /// Sync a playlist, or only show what would change with `dry_run`
+    Sync {
+        playlist_id: String,
+        dry_run: bool,
+    },
+}
+
+/// What the panel on the right shows
+enum Detail {
+    /// Sources and last sync of the playlist
+    Overview,
+    /// The videos of the sources missing from the playlist, as a diff
+    Pending(String),
+    /// The items of the playlist
+    Items(Vec<VideoInfo>),
+    /// Choosing the sources of the playlist
+    Sources(SourcePicker),
+}
+
+/// The other playlists, to tick the ones the selected playlist syncs from
+struct SourcePicker {
+    /// ID and title of each playlist
+    candidates: Vec<(String, String)>,
+    chosen: Vec<String>,
+    state: ListState,
+}
+
+/// State of the TUI, kept while a sync runs so it comes back as it was
+pub struct App {
+    cfg: Config,
+    history: Vec<HistoryEntry>,
+    list: ListState,
+    /// Number of videos of the sources missing from the checked playlists, by ID
+    behind: HashMap<String, usize>,
+    detail: Detail,
+    /// The sync graph editor, shown instead of the playlists when open
+    graph: Option<GraphEditor>,
+    scroll: u16,
+    /// Outcome of the last key, shown at the bottom
+    message: String,
+}
+
+impl App {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut app = App {
+            cfg: Config::default(),
+            history: Vec::new(),
+            list: ListState::default(),
+            behind: HashMap::new(),
+            detail: Detail::Overview,
+            graph: None,
+            scroll: 0,
+            message: String::new(),
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Read the configuration and the history again, e.g. after a sync
+    pub fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.cfg = Config::read()?;
+        self.history = history::read_all().unwrap_or_default();
+        self.behind.clear();
+        self.detail = Detail::Overview;
+        self.scroll = 0;
+
+        let selected = self.list.selected().unwrap_or_default();
+        self.list.select(
+            (!self.cfg.playlists.is_empty()).then(|| selected.min(self.cfg.playlists.len() - 1)),
+        );
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<Playlist> {
+        let index = self.list.selected()?;
+        self.cfg
+            .playlists
+            .get(index)
+            .map(|playlist| self.cfg.with_defaults(playlist))
+    }
+
+    fn select(&mut self, offset: isize) {
+        move_in(&mut self.list, self.cfg.playlists.len(), offset);
+        self.detail = Detail::Overview;
+        self.scroll = 0;
+        self.message.clear();
+    }
+}
+
+/// Run the TUI until it's quit or a sync is asked for
+pub async fn run(
+    app: &mut App,
+    client: &YouTubeClient,
+) -> Result<Action, Box<dyn std::error::Error>> {
+    let mut terminal = ratatui::init();
+    let action = event_loop(app, client, &mut terminal).await;
+    ratatui::restore();
+    action
+}
+
+async fn event_loop(
+    app: &mut App,
+    client: &YouTubeClient,
+    terminal: &mut DefaultTerminal,
+) -> Result<Action, Box<dyn std::error::Error>> {
+    loop {
+        terminal.draw(|frame| draw(app, frame))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if let Some(editor) = &mut app.graph {
+            if editor.key(key.code) {
+                let saved = editor.saved;
+                app.graph = None;
+                if saved {
+                    app.reload()?;
+                }
+            }
+            continue;
+        }
+
+        if let Detail::Sources(picker) = &mut app.detail {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    move_in(&mut picker.state, picker.candidates.len(), -1)
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    move_in(&mut picker.state, picker.candidates.len(), 1)
+                }
+                KeyCode::Char(' ') => picker.toggle(),
+                KeyCode::Enter => save_sources(app),
+                KeyCode::Esc => {
+                    app.detail = Detail::Overview;
+                    app.message = "Sources unchanged".to_string();
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Action::Quit),
+            KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.select(1),
+            KeyCode::PageUp => app.scroll = app.scroll.saturating_sub(10),
+            KeyCode::PageDown => app.scroll = app.scroll.saturating_add(10),
+            KeyCode::Enter | KeyCode::Char('c') => {
+                // Fetching takes a while, say so first
+                app.message = "Checking the sources…".to_string();
+                terminal.draw(|frame| draw(app, frame))?;
+                check(app, client).await;
+                // Sources may have logged warnings over the screen
+                terminal.clear()?;
+            }
+            KeyCode::Char('i') => {
+                app.message = "Fetching the items…".to_string();
+                terminal.draw(|frame| draw(app, frame))?;
+                inspect(app, client).await;
+                terminal.clear()?;
+            }
+            KeyCode::Char('e') => edit_sources(app),
+            KeyCode::Char('g') => app.graph = Some(GraphEditor::new(app.cfg.clone())),
+            KeyCode::Char('s') | KeyCode::Char('d') => {
+                if let Some(playlist) = app.selected() {
+                    return Ok(Action::Sync {
+                        playlist_id: playlist.id,
+                        dry_run: key.code == KeyCode::Char('d'),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Move the selection of a list of `len` items, staying in the list
+fn move_in(state: &mut ListState, len: usize, offset: isize) {
+    if len == 0 {
+        return;
+    }
+    let index = state.selected().unwrap_or_default() as isize + offset;
+    state.select(Some(index.clamp(0, len as isize - 1) as usize));
+}
+
+/// Compare the selected playlist against its sources and show the videos it lacks
+async fn check(app: &mut App, client: &YouTubeClient) {
+    let Some(playlist) = app.selected() else {
+        return;
+    };
+    let Some(sync_from) = playlist.sync_from.as_deref() else {
+        app.message = format!("'{}' has no sources", playlist.title);
+        return;
+    };
+
+    match sync::pending_changes(client, &playlist.id, sync_from).await {
+        Ok(pending) => {
+            app.behind
+                .insert(playlist.id.clone(), pending.videos_to_add.len());
+            app.message = if pending.videos_to_add.is_empty() {
+                format!("'{}' is up to date", playlist.title)
+            } else {
+                // Filters, scripts and limits only apply when syncing
+                format!(
+                    "{} videos of the sources are missing, before filters: press d for a dry run",
+                    pending.videos_to_add.len()
+                )
+            };
+            app.detail = Detail::Pending(diff::render(
+                &playlist.title,
+                &pending.target_videos,
+                &pending.videos_to_add,
+                &[],
+                playlist.position == Some(InsertPosition::Start),
+            ));
+            app.scroll = 0;
+        }
+        Err(e) => app.message = format!("Failed to check '{}': {}", playlist.title, e),
+    }
+}
+
+/// Show the items of the selected playlist
+async fn inspect(app: &mut App, client: &YouTubeClient) {
+    let Some(playlist) = app.selected() else {
+        return;
+    };
+
+    match client.get_playlist_items(&playlist.id).await {
+        Ok(items) => {
+            app.message = format!("{} items in '{}'", items.len(), playlist.title);
+            app.detail = Detail::Items(items);
+            app.scroll = 0;
+        }
+        Err(e) => app.message = format!("Failed to fetch '{}': {}", playlist.title, e),
+    }
+}
+
+/// Pick the sources of the selected playlist among the other playlists
+fn edit_sources(app: &mut App) {
+    let Some(playlist) = app.selected() else {
+        return;
+    };
+
+    let candidates = app
+        .cfg
+        .playlists
+        .iter()
+        .filter(|p| p.id != playlist.id)
+        .map(|p| (p.id.clone(), p.title.clone()))
+        .collect();
+    app.detail = Detail::Sources(SourcePicker {
+        candidates,
+        chosen: playlist.sync_from.clone().unwrap_or_default(),
+        state: ListState::default().with_selected(Some(0)),
+    });
+    app.message = "space: toggle a source, enter: save, esc: cancel".to_string();
+}
+
+impl SourcePicker {
+    fn toggle(&mut self) {
+        let Some((id, _)) = self
+            .state
+            .selected()
+            .and_then(|index| self.candidates.get(index))
+        else {
+            return;
+        };
+        match self.chosen.iter().position(|source| source == id) {
+            Some(index) => {
+                self.chosen.remove(index);
+            }
+            None => self.chosen.push(id.clone()),
+        }
+    }
+}
+
+/// Save the sources picked for the selected playlist, unless they make a loop
+fn save_sources(app: &mut App) {
+    let (Some(index), Detail::Sources(picker)) = (app.list.selected(), &app.detail) else {
+        return;
+    };
+
+    let mut cfg = app.cfg.clone();
+    let playlist = &mut cfg.playlists[index];
+    playlist.sync_from = (!picker.chosen.is_empty()).then(|| picker.chosen.clone());
+    let title = playlist.title.clone();
+
+    if let Some(cycle) = cfg.find_cycle() {
+        app.message = format!(
+            "Not saved, playlists would sync in a loop: {}",
+            cycle.join(" → ")
+        );
+        return;
+    }
+    // Only the sources are saved, onto the configuration as it is now
+    let id = cfg.playlists[index].id.clone();
+    let saved = Config::update(|file| {
+        let playlist = file
+            .playlists
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or("the playlist was removed from the configuration")?;
+        playlist.sync_from = (!picker.chosen.is_empty()).then(|| picker.chosen.clone());
+        Ok(())
+    });
+    if let Err(e) = saved {
+        app.message = format!("Failed to save the configuration: {}", e);
+        return;
+    }
+
+    app.cfg = cfg;
+    app.behind.remove(&app.cfg.playlists[index].id);
+    app.detail = Detail::Overview;
+    app.message = format!("Saved the sources of '{}'", title);
+}
+
+fn draw(app: &mut App, frame: &mut Frame) {
+    if let Some(editor) = &mut app.graph {
+        draw_graph(editor, frame);
+        return;
+    }
+
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::raw(" playsync ").bold().reversed(),
+            Span::raw(format!(" {} playlists", app.cfg.playlists.len())),
+        ])),
+        header,
+    );
+
+    draw_playlists(app, frame, left);
+    draw_detail(app, frame, right);
+
+    let keys = match app.detail {
+        Detail::Sources(_) => "↑↓ move  space toggle  enter save  esc cancel",
+        _ => {
+            "↑↓ move  enter check  i items  e sources  g graph  s sync  d dry run  pgup/pgdn scroll  q quit"
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(app.message.clone()),
+            Line::from(keys).dim(),
+        ]),
+        footer,
+    );
+}
+
+fn draw_playlists(app: &mut App, frame: &mut Frame, area: Rect) {
+    let items: Vec<ListItem> = app
+        .cfg
+        .playlists
+        .iter()
+        .map(|playlist| {
+            let status = match (app.behind.get(&playlist.id), &playlist.sync_from) {
+                (_, None) => Span::raw(" manual").dim(),
+                (Some(0), _) => Span::raw(" ✓ up to date").green(),
+                (Some(behind), _) => Span::raw(format!(" {} behind", behind)).yellow(),
+                (None, Some(_)) => Span::raw(""),
+            };
+            ListItem::new(Line::from(vec![Span::raw(playlist.title.clone()), status]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::bordered().title(" Playlists "))
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, area, &mut app.list);
+}
+
+fn draw_detail(app: &mut App, frame: &mut Frame, area: Rect) {
+    let Some(playlist) = app.selected() else {
+        frame.render_widget(
+            Paragraph::new("No playlists configured, add some with `playsync config --add`")
+                .block(Block::bordered()),
+            area,
+        );
+        return;
+    };
+
+    if let Detail::Sources(picker) = &mut app.detail {
+        let items: Vec<ListItem> = picker
+            .candidates
+            .iter()
+            .map(|(id, title)| {
+                let mark = if picker.chosen.contains(id) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                ListItem::new(format!("{} {}", mark, title))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" Sources of {} ", playlist.title)))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, area, &mut picker.state);
+        return;
+    }
+
+    let (title, lines) = match &app.detail {
+        Detail::Sources(_) => return,
+        Detail::Overview => (" Overview ", overview(app, &playlist)),
+        Detail::Pending(diff) => (" Missing from the playlist ", diff_lines(diff)),
+        Detail::Items(items) => (
+            " Items ",
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, video)| {
+                    Line::from(vec![
+                        Span::raw(format!("{:>4}. ", index + 1)).dim(),
+                        Span::raw(video.title.clone()),
+                        Span::raw(
+                            video
+                                .channel
+                                .as_ref()
+                                .map(|channel| format!(" · {}", channel))
+                                .unwrap_or_default(),
+                        )
+                        .dim(),
+                    ])
+                })
+                .collect(),
+        ),
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((app.scroll, 0)),
+        area,
+    );
+}
+
+/// Color the lines of a diff like `git diff` does
+fn diff_lines(diff: &str) -> Vec<Line<'static>> {
+    diff.lines()
+        .map(|line| {
+            let span = Span::raw(line.to_string());
+            match line.chars().next() {
+                Some('+') => span.green(),
+                Some('-') => span.red(),
+                Some('@') => span.cyan(),
+                _ => span,
+            }
+            .into()
+        })
+        .collect()
+}
+
+fn overview(app: &App, playlist: &Playlist) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(playlist.title.clone()).bold(),
+        Line::from(format!("ID: {}", playlist.id)),
+    ];
+    if let Some(alias) = &playlist.alias {
+        lines.push(Line::from(format!("Alias: {}", alias)));
+    }
+    if !playlist.tags.is_empty() {
+        lines.push(Line::from(format!("Tags: {}", playlist.tags.join(", "))));
+    }
+
+    lines.push(Line::default());
+    match &playlist.sync_from {
+        Some(sources) => {
+            lines.push(Line::from("Syncs from:"));
+            for source in sources {
+                let title = app
+                    .cfg
+                    .playlists
+                    .iter()
+                    .find(|p| p.id == *source)
+                    .map(|p| p.title.clone())
+                    .unwrap_or_else(|| Source::parse(source).describe());
+                lines.push(Line::from(format!("  • {}", title)));
+            }
+        }
+        None => lines.push(Line::from("No sources, videos are only added by hand").dim()),
+    }
+
+    lines.push(Line::default());
+    let last_run = app.history.iter().rev().find_map(|entry| {
+        let record = entry.playlists.iter().find(|p| p.id == playlist.id)?;
+        Some((entry, record))
+    });
+    match last_run {
+        Some((entry, record)) => {
+            lines.push(Line::from(format!(
+                "Last {}{}: {}",
+                entry.command,
+                if entry.dry_run { " (dry run)" } else { "" },
+                entry.timestamp.format("%Y-%m-%d %H:%M UTC")
+            )));
+            lines.push(Line::from(format!(
+                "  +{} -{} ✗{}",
+                record.added, record.removed, record.failed
+            )));
+            if let Some(count) = record.item_count {
+                lines.push(Line::from(format!("  {} items", count)));
+            }
+        }
+        None => lines.push(Line::from("Never synced").dim()),
+    }
+
+    lines
+}
+
+/// Which list of the graph editor the keys move in
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Targets,
+    Sources,
+}
+
+/// Editor of the sync graph: the playlists on the left, the sources of the selected
+/// one in the middle, and the whole graph on the right, checked for loops as it changes
+pub struct GraphEditor {
+    /// The configuration with the changes, written when saved
+    draft: Config,
+    targets: ListState,
+    sources: ListState,
+    focus: Focus,
+    /// The loop the changes make, if any, which keeps them from being saved
+    cycle: Option<Vec<String>>,
+    /// Changes not saved yet
+    changed: bool,
+    /// Whether changes were saved since the editor was opened
+    pub saved: bool,
+    /// Set when leaving with unsaved changes, so a second press discards them
+    leaving: bool,
+    message: String,
+}
+
+impl GraphEditor {
+    pub fn new(cfg: Config) -> Self {
+        GraphEditor {
+            cycle: cfg.find_cycle(),
+            draft: cfg,
+            targets: ListState::default().with_selected(Some(0)),
+            sources: ListState::default().with_selected(Some(0)),
+            focus: Focus::Targets,
+            changed: false,
+            saved: false,
+            leaving: false,
+            message: String::new(),
+        }
+    }
+
+    fn target(&self) -> Option<&Playlist> {
+        self.draft.playlists.get(self.targets.selected()?)
+    }
+
+    /// The sources the selected playlist can have: the other playlists, and the
+    /// sources it has that aren't playlists, with their titles
+    fn candidates(&self) -> Vec<(String, String)> {
+        let Some(target) = self.target() else {
+            return Vec::new();
+        };
+
+        let mut candidates: Vec<(String, String)> = self
+            .draft
+            .playlists
+            .iter()
+            .filter(|p| p.id != target.id)
+            .map(|p| (p.id.clone(), p.title.clone()))
+            .collect();
+        for source in target.sync_from.iter().flatten() {
+            if !candidates.iter().any(|(id, _)| id == source) {
+                candidates.push((source.clone(), Source::parse(source).describe()));
+            }
+        }
+        candidates
+    }
+
+    fn toggle(&mut self) {
+        let candidates = self.candidates();
+        let (Some(index), Some(selected)) = (
+            self.targets.selected(),
+            self.sources.selected().and_then(|i| candidates.get(i)),
+        ) else {
+            return;
+        };
+
+        let target = &mut self.draft.playlists[index];
+        let mut sources = target.sync_from.take().unwrap_or_default();
+        match sources.iter().position(|source| *source == selected.0) {
+            Some(position) => {
+                sources.remove(position);
+            }
+            None => sources.push(selected.0.clone()),
+        }
+        target.sync_from = (!sources.is_empty()).then_some(sources);
+
+        self.cycle = self.draft.find_cycle();
+        self.changed = true;
+        self.leaving = false;
+        self.message.clear();
+    }
+
+    fn save(&mut self) {
+        if let Some(cycle) = &self.cycle {
+            self.message = format!("Not saved, break the loop first: {}", self.titles(cycle));
+            return;
+        }
+        // Only the sources are saved, onto the configuration as it is now
+        let saved = Config::update(|file| {
+            for playlist in &mut file.playlists {
+                if let Some(edited) = self.draft.playlists.iter().find(|p| p.id == playlist.id) {
+                    playlist.sync_from = edited.sync_from.clone();
+                }
+            }
+            Ok(())
+        });
+        match saved {
+            Ok(()) => {
+                self.changed = false;
+                self.saved = true;
+                self.message = "Saved the sync graph".to_string();
+            }
+            Err(e) => self.message = format!("Failed to save the configuration: {}", e),
+        }
+    }
+
+    /// Titles of the playlists along a loop
+    fn titles(&self, ids: &[String]) -> String {
+        ids.iter()
+            .map(|id| {
+                self.draft
+                    .playlists
+                    .iter()
+                    .find(|p| p.id == *id)
+                    .map_or(id.as_str(), |p| p.title.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    /// Handle a key, returning whether the editor is closed
+    fn key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if self.changed && !self.leaving {
+                    self.leaving = true;
+                    self.message =
+                        "The changes aren't saved: press s to save, or esc again to discard them"
+                            .to_string();
+                    return false;
+                }
+                return true;
+            }
+            KeyCode::Tab
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Char('h')
+            | KeyCode::Char('l') => {
+                self.focus = match self.focus {
+                    Focus::Targets => Focus::Sources,
+                    Focus::Sources => Focus::Targets,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char(' ') | KeyCode::Enter if self.focus == Focus::Sources => self.toggle(),
+            KeyCode::Enter => self.focus = Focus::Sources,
+            KeyCode::Char('s') => self.save(),
+            _ => {}
+        }
+        false
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        match self.focus {
+            Focus::Targets => {
+                move_in(&mut self.targets, self.draft.playlists.len(), offset);
+                self.sources.select(Some(0));
+            }
+            Focus::Sources => {
+                let len = self.candidates().len();
+                move_in(&mut self.sources, len, offset);
+            }
+        }
+    }
+}
+
+/// Edit the sync graph on its own, for `playsync config edit-graph`.
+/// Returns whether changes were saved.
+pub fn edit_graph(cfg: Config) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut editor = GraphEditor::new(cfg);
+    let mut terminal = ratatui::init();
+    let result = graph_loop(&mut editor, &mut terminal);
+    ratatui::restore();
+    result.map(|_| editor.saved)
+}
+
+fn graph_loop(
+    editor: &mut GraphEditor,
+    terminal: &mut DefaultTerminal,
+) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        terminal.draw(|frame| draw_graph(editor, frame))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && editor.key(key.code)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn draw_graph(editor: &mut GraphEditor, frame: &mut Frame) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+    let [left, middle, right] = Layout::horizontal([
+        Constraint::Percentage(30),
+        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+    ])
+    .areas(body);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::raw(" playsync ").bold().reversed(),
+            Span::raw(" Sync graph"),
+            Span::raw(if editor.changed { " (not saved)" } else { "" }).yellow(),
+        ])),
+        header,
+    );
+
+    let focused = |focus: Focus| {
+        if editor.focus == focus {
+            Style::new().cyan()
+        } else {
+            Style::new()
+        }
+    };
+
+    let targets: Vec<ListItem> = editor
+        .draft
+        .playlists
+        .iter()
+        .map(|playlist| {
+            let count = playlist.sync_from.as_ref().map_or(0, Vec::len);
+            ListItem::new(Line::from(vec![
+                Span::raw(playlist.title.clone()),
+                Span::raw(format!(" ◀ {}", count)).dim(),
+            ]))
+        })
+        .collect();
+    let targets = List::new(targets)
+        .block(
+            Block::bordered()
+                .title(" Playlists ")
+                .border_style(focused(Focus::Targets)),
+        )
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(targets, left, &mut editor.targets);
+
+    let chosen = editor
+        .target()
+        .and_then(|target| target.sync_from.clone())
+        .unwrap_or_default();
+    let sources: Vec<ListItem> = editor
+        .candidates()
+        .into_iter()
+        .map(|(id, title)| {
+            let mark = if chosen.contains(&id) { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", mark, title))
+        })
+        .collect();
+    let title = editor
+        .target()
+        .map(|target| format!(" {} syncs from ", target.title))
+        .unwrap_or_default();
+    let sources = List::new(sources)
+        .block(
+            Block::bordered()
+                .title(title)
+                .border_style(focused(Focus::Sources)),
+        )
+        .highlight_style(Style::new().reversed());
+    frame.render_stateful_widget(sources, middle, &mut editor.sources);
+
+    frame.render_widget(
+        Paragraph::new(topology(editor))
+            .block(Block::bordered().title(" Graph "))
+            .wrap(Wrap { trim: false }),
+        right,
+    );
+
+    let status = match &editor.cycle {
+        Some(cycle) => Line::from(format!("Loop: {}", editor.titles(cycle))).red(),
+        None if editor.message.is_empty() => Line::from("No loops").green(),
+        None => Line::from(editor.message.clone()),
+    };
+    frame.render_widget(
+        Paragraph::new(vec![
+            status,
+            Line::from("tab switch list  ↑↓ move  space toggle a source  s save  esc leave").dim(),
+        ]),
+        footer,
+    );
+}
+
+/// The sync graph as lines: each playlist with sources, in the order videos flow, with
+/// its sources under it. Edges in a loop are red, the selected playlist is bold.
+fn topology(editor: &GraphEditor) -> Vec<Line<'static>> {
+    let cfg = &editor.draft;
+    let selected = editor.target().map(|target| target.id.as_str());
+    let cycle = editor.cycle.as_deref().unwrap_or_default();
+
+    let mut lines = Vec::new();
+    for playlist in graph::flow_order(cfg) {
+        let Some(sync_from) = &playlist.sync_from else {
+            continue;
+        };
+
+        let title = Line::from(playlist.title.clone());
+        lines.push(if selected == Some(playlist.id.as_str()) {
+            title.bold()
+        } else {
+            title
+        });
+        for source in sync_from {
+            let (title, configured) = match cfg.playlists.iter().find(|p| p.id == *source) {
+                Some(p) => (p.title.clone(), true),
+                None => (Source::parse(source).describe(), false),
+            };
+            let edge = Line::from(format!("  ◀── {}", title));
+            lines.push(if graph::in_cycle(cycle, &playlist.id, source) {
+                edge.red()
+            } else if configured {
+                edge
+            } else {
+                edge.dim()
+            });
+        }
+    }
+
+    let unconnected: Vec<&str> = cfg
+        .playlists
+        .iter()
+        .filter(|p| {
+            p.sync_from.is_none()
+                && !cfg
+                    .playlists
+                    .iter()
+                    .any(|other| other.sync_from.iter().flatten().any(|s| *s == p.id))
+        })
+        .map(|p| p.title.as_str())
+        .collect();
+    if !unconnected.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from(format!("Not connected: {}", unconnected.join(", "))).dim());
+    }
+
+    lines
+}
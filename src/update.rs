@@ -0,0 +1,203 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/KilDesu/playsync/releases/latest";
+
+/// GitHub rejects API requests without a user agent
+const USER_AGENT: &str = concat!("playsync/", env!("CARGO_PKG_VERSION"));
+
+/// A release published on GitHub
+#[derive(Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    /// The version of the release, from its `v1.2.3` tag
+    pub fn version(&self) -> Result<semver::Version, Box<dyn std::error::Error>> {
+        Ok(semver::Version::parse(
+            self.tag_name.trim_start_matches('v'),
+        )?)
+    }
+
+    fn asset_url(&self, name: &str) -> Result<&str, Box<dyn std::error::Error>> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.as_str())
+            .ok_or_else(|| format!("The release {} has no {}", self.tag_name, name).into())
+    }
+}
+
+/// The version of this build
+pub fn current_version() -> semver::Version {
+    semver::Version::parse(env!("CARGO_PKG_VERSION")).expect("The package version is valid")
+}
+
+/// The target of the release archive for this platform, as named by the release workflow
+pub fn target() -> Option<&'static str> {
+    match (std::env::consts::ARCH, std::env::consts::OS) {
+        ("x86_64", "linux") => Some("x86_64-unknown-linux-gnu"),
+        ("x86_64", "macos") => Some("x86_64-apple-darwin"),
+        ("aarch64", "macos") => Some("aarch64-apple-darwin"),
+        ("x86_64", "windows") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+/// The package manager this binary was installed with, if any, which should update it
+/// instead so it keeps track of the installed version
+pub fn package_manager() -> Option<&'static str> {
+    let exe = std::env::current_exe().ok()?;
+    let path = exe.to_string_lossy().to_lowercase();
+    if path.contains("/cellar/") || path.contains("/homebrew/") {
+        Some("Homebrew (`brew upgrade playsync`)")
+    } else if path.contains("\\scoop\\") {
+        Some("Scoop (`scoop update playsync`)")
+    } else {
+        None
+    }
+}
+
+/// Public minisign key the release archives are signed with. The release workflow
+/// builds it in; builds without it can only check the published SHA-256 checksums,
+/// and only update with `--insecure`.
+pub fn public_key() -> Option<&'static str> {
+    option_env!("PLAYSYNC_MINISIGN_PUBLIC_KEY").filter(|key| !key.is_empty())
+}
+
+/// Why a build without the signing key doesn't update itself
+pub const NO_PUBLIC_KEY: &str = "This build has no release signing key to check the download with. Download the release by hand, or add --insecure to only check its checksum";
+
+/// Get the latest release published on GitHub
+pub async fn latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    Ok(reqwest::Client::new()
+        .get(LATEST_RELEASE_URL)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    Ok(reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+/// Check an archive against its `.sha256` file, which holds the hex digest, followed by
+/// the file name on Linux and macOS
+fn verify_checksum(archive: &[u8], checksum_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or("The checksum file is empty")?;
+    let actual: String = Sha256::digest(archive)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "The checksum of the download doesn't match the release: expected {}, got {}",
+            expected, actual
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Check the minisign signature of an archive
+fn verify_signature(
+    archive: &[u8],
+    signature: &str,
+    public_key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public_key = minisign_verify::PublicKey::from_base64(public_key)?;
+    let signature = minisign_verify::Signature::decode(signature)?;
+    public_key
+        .verify(archive, &signature, false)
+        .map_err(|e| format!("The signature of the download is invalid: {}", e).into())
+}
+
+/// Take the binary out of a release archive
+fn extract_binary(archive: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let name = if cfg!(windows) {
+        "playsync.exe"
+    } else {
+        "playsync"
+    };
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive))?;
+    let mut file = archive
+        .by_name(name)
+        .map_err(|_| format!("The release archive has no {}", name))?;
+
+    let mut binary = Vec::new();
+    file.read_to_end(&mut binary)?;
+    Ok(binary)
+}
+
+/// Download the archive of a release for this platform, check its checksum and its
+/// signature, then replace the running binary. The signature is only left unchecked
+/// with `insecure`, when this build doesn't know the signing key.
+pub async fn install(
+    release: &Release,
+    target: &str,
+    insecure: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let archive_name = format!("playsync-{}.zip", target);
+    let archive = download(release.asset_url(&archive_name)?).await?;
+
+    let checksum_file =
+        download(release.asset_url(&format!("playsync-{}.sha256", target))?).await?;
+    verify_checksum(&archive, &String::from_utf8_lossy(&checksum_file))?;
+
+    match public_key() {
+        Some(public_key) => {
+            let signature =
+                download(release.asset_url(&format!("{}.minisig", archive_name))?).await?;
+            verify_signature(&archive, &String::from_utf8_lossy(&signature), public_key)?;
+        }
+        None if insecure => {}
+        None => return Err(NO_PUBLIC_KEY.into()),
+    }
+
+    let binary = extract_binary(archive)?;
+
+    // The checked binary goes to a new file only this user can write to, next to the
+    // running one, so nobody can swap it before it's installed. It's removed once dropped.
+    let exe = std::env::current_exe()?;
+    let dir = exe.parent().ok_or("The playsync binary has no directory")?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(&binary)?;
+    file.flush()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // The running binary can't be overwritten on Windows, self_replace moves the new
+    // one in its place in a way that works on every platform
+    self_replace::self_replace(file.path())
+        .map_err(|e| format!("Failed to replace the playsync binary: {}", e).into())
+}